@@ -0,0 +1,28 @@
+// Service worker showing the Web Push notifications of the hex chess server.
+
+self.addEventListener("push", (event) => {
+	const payload = event.data ? event.data.json() : {};
+	event.waitUntil(
+		self.registration.showNotification("Hex Chess", {
+			body: pushBody(payload.kind),
+			icon: "/favicon.ico",
+			data: { url: payload.url || "/games" },
+		})
+	);
+});
+
+self.addEventListener("notificationclick", (event) => {
+	event.notification.close();
+	event.waitUntil(self.clients.openWindow(event.notification.data.url));
+});
+
+function pushBody(kind) {
+	switch (kind) {
+		case "correspondence_move":
+			return "Your opponent moved in a correspondence game.";
+		case "challenge":
+			return "You have been challenged to a game.";
+		default:
+			return "Something happened in one of your games.";
+	}
+}
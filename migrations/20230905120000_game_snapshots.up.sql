@@ -0,0 +1,10 @@
+-- Add up migration script here
+
+CREATE TABLE
+    IF NOT EXISTS game_snapshots (
+        game_id UUID PRIMARY KEY,
+        white_player_id VARCHAR(255) NOT NULL,
+        black_player_id VARCHAR(255) NOT NULL,
+        board JSONB NOT NULL,
+        created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+    );
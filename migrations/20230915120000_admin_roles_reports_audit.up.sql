@@ -0,0 +1,24 @@
+-- Add up migration script here
+
+ALTER TABLE users ADD COLUMN IF NOT EXISTS role VARCHAR(32) NOT NULL DEFAULT 'player';
+
+CREATE TABLE
+    IF NOT EXISTS reports (
+        id serial PRIMARY KEY UNIQUE,
+        reporter_id VARCHAR(255) NOT NULL,
+        reported_id VARCHAR(255) NOT NULL,
+        game_id UUID,
+        reason VARCHAR(64) NOT NULL,
+        comment TEXT,
+        created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+    );
+
+CREATE TABLE
+    IF NOT EXISTS admin_audit_log (
+        id serial PRIMARY KEY UNIQUE,
+        admin_user_id VARCHAR(255) NOT NULL,
+        action VARCHAR(64) NOT NULL,
+        target VARCHAR(255) NOT NULL,
+        details TEXT,
+        created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+    );
@@ -0,0 +1,5 @@
+-- Add down migration script here
+
+DROP TABLE IF EXISTS game_records;
+
+ALTER TABLE game_snapshots DROP COLUMN IF EXISTS chat;
\ No newline at end of file
@@ -0,0 +1,14 @@
+-- Add up migration script here
+
+ALTER TABLE game_snapshots ADD COLUMN IF NOT EXISTS chat JSONB NOT NULL DEFAULT '[]';
+
+CREATE TABLE
+    IF NOT EXISTS game_records (
+        game_id UUID PRIMARY KEY,
+        white_player_id VARCHAR(255) NOT NULL,
+        black_player_id VARCHAR(255) NOT NULL,
+        board JSONB NOT NULL,
+        chat JSONB NOT NULL DEFAULT '[]',
+        outcome VARCHAR(32) NOT NULL,
+        ended_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+    );
@@ -0,0 +1,11 @@
+-- Add down migration script here
+
+DROP TABLE IF EXISTS user_blocks;
+
+DROP INDEX IF EXISTS reports_reporter_game;
+
+ALTER TABLE reports DROP COLUMN IF EXISTS resolved_at;
+
+ALTER TABLE reports DROP COLUMN IF EXISTS resolved_by;
+
+ALTER TABLE reports DROP COLUMN IF EXISTS status;
\ No newline at end of file
@@ -0,0 +1,17 @@
+-- Add up migration script here
+
+ALTER TABLE reports ADD COLUMN IF NOT EXISTS status VARCHAR(16) NOT NULL DEFAULT 'open';
+
+ALTER TABLE reports ADD COLUMN IF NOT EXISTS resolved_by VARCHAR(255);
+
+ALTER TABLE reports ADD COLUMN IF NOT EXISTS resolved_at TIMESTAMPTZ;
+
+CREATE UNIQUE INDEX IF NOT EXISTS reports_reporter_game ON reports (reporter_id, game_id);
+
+CREATE TABLE
+    IF NOT EXISTS user_blocks (
+        blocker_id VARCHAR(255) NOT NULL,
+        blocked_id VARCHAR(255) NOT NULL,
+        created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+        PRIMARY KEY (blocker_id, blocked_id)
+    );
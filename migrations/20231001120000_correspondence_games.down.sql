@@ -0,0 +1,5 @@
+-- Add down migration script here
+
+DROP INDEX IF EXISTS correspondence_games_move_deadline;
+
+DROP TABLE IF EXISTS correspondence_games;
\ No newline at end of file
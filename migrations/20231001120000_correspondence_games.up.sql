@@ -0,0 +1,15 @@
+-- Add up migration script here
+
+CREATE TABLE
+    IF NOT EXISTS correspondence_games (
+        game_id UUID PRIMARY KEY,
+        white_player_id VARCHAR(255) NOT NULL,
+        black_player_id VARCHAR(255) NOT NULL,
+        board JSONB NOT NULL,
+        chat JSONB NOT NULL DEFAULT '[]',
+        days_per_move INT NOT NULL,
+        move_deadline TIMESTAMPTZ NOT NULL,
+        created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+    );
+
+CREATE INDEX IF NOT EXISTS correspondence_games_move_deadline ON correspondence_games (move_deadline);
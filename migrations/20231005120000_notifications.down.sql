@@ -0,0 +1,7 @@
+-- Add down migration script here
+
+DROP TABLE IF EXISTS push_subscriptions;
+
+DROP INDEX IF EXISTS notifications_user_created;
+
+DROP TABLE IF EXISTS notifications;
\ No newline at end of file
@@ -0,0 +1,5 @@
+-- Add down migration script here
+
+DROP INDEX IF EXISTS user_follows_followed;
+
+DROP TABLE IF EXISTS user_follows;
\ No newline at end of file
@@ -0,0 +1,11 @@
+-- Add up migration script here
+
+CREATE TABLE
+    IF NOT EXISTS user_follows (
+        follower_id VARCHAR(255) NOT NULL,
+        followed_id VARCHAR(255) NOT NULL,
+        created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+        PRIMARY KEY (follower_id, followed_id)
+    );
+
+CREATE INDEX IF NOT EXISTS user_follows_followed ON user_follows (followed_id);
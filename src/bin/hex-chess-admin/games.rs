@@ -0,0 +1,158 @@
+use std::path::PathBuf;
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use clap::Subcommand;
+use hex_chess_app::server::{board::GameSummary, config::Config};
+use reqwest::{Method, RequestBuilder, Response};
+use uuid::Uuid;
+
+use crate::CliResult;
+
+#[derive(Debug, Subcommand)]
+pub enum GamesCommand {
+    /// List the live games and the players waiting for one.
+    List,
+    /// End a game, its players are told it was ended by a moderator.
+    End { game_id: Uuid },
+    /// Remove a game without notifying its players.
+    Delete { game_id: Uuid },
+    /// Export the state of live games, or the records of finished games, as JSON.
+    Export {
+        /// Only export this game.
+        #[arg(long)]
+        game: Option<Uuid>,
+        /// Export the records of finished games instead of the live games.
+        #[arg(long)]
+        archived: bool,
+        /// Only export the games that ended at or after this time, like `2023-10-01T00:00:00Z`.
+        #[arg(long, requires = "archived", conflicts_with = "game")]
+        from: Option<DateTime<Utc>>,
+        /// Only export the games that ended before this time.
+        #[arg(long, requires = "archived", conflicts_with = "game")]
+        to: Option<DateTime<Utc>>,
+        /// File to write to, defaults to stdout.
+        #[arg(long, short)]
+        out: Option<PathBuf>,
+    },
+}
+
+pub struct AdminClient {
+    http: reqwest::Client,
+    base_url: String,
+    token: String,
+}
+
+impl AdminClient {
+    pub fn new(config: &Config, server: Option<String>) -> CliResult<Self> {
+        let base_url = server.unwrap_or_else(|| config.server.base_url.clone());
+        if base_url.is_empty() {
+            return Err("missing server url, use --server or set `server.base_url`.".into());
+        }
+        let token = config
+            .server
+            .admin_token
+            .clone()
+            .ok_or("missing admin token, set `server.admin_token` or HEX_CHESS_ADMIN_TOKEN.")?;
+        Ok(AdminClient {
+            http: reqwest::Client::new(),
+            base_url: base_url.trim_end_matches('/').to_owned(),
+            token,
+        })
+    }
+
+    fn request(&self, method: Method, path: &str) -> RequestBuilder {
+        self.http
+            .request(method, format!("{}/api/admin{}", self.base_url, path))
+            .bearer_auth(&self.token)
+    }
+
+    async fn send(&self, method: Method, path: &str) -> CliResult<Response> {
+        let res = self.request(method, path).send().await?;
+        if res.status().is_success() {
+            Ok(res)
+        } else {
+            let status = res.status();
+            let body = res.text().await.unwrap_or_default();
+            Err(format!("server answered {}: {}", status, body).into())
+        }
+    }
+}
+
+fn print_summaries(summaries: &[GameSummary]) {
+    if summaries.is_empty() {
+        println!("No live games.");
+        return;
+    }
+    for summary in summaries {
+        let game_id = summary
+            .game_id
+            .map_or_else(|| "-".to_owned(), |id| id.to_string());
+        let turn = summary
+            .player_turn
+            .map_or_else(|| "-".to_owned(), |color| format!("{:?}", color));
+        println!(
+            "{:<36}  {:<7}  turn: {:<5}  spectators: {:<3}  players: {}",
+            game_id,
+            format!("{:?}", summary.kind),
+            turn,
+            summary.spectators,
+            summary.players.join(", ")
+        );
+    }
+}
+
+/// Path of the archive export, the bounds are sent in UTC so they need no escaping.
+fn archive_path(from: Option<DateTime<Utc>>, to: Option<DateTime<Utc>>) -> String {
+    let bounds: Vec<String> = [("from", from), ("to", to)]
+        .into_iter()
+        .filter_map(|(name, bound)| {
+            let bound = bound?.to_rfc3339_opts(SecondsFormat::Secs, true);
+            Some(format!("{}={}", name, bound))
+        })
+        .collect();
+    if bounds.is_empty() {
+        "/games/archive".to_owned()
+    } else {
+        format!("/games/archive?{}", bounds.join("&"))
+    }
+}
+
+pub async fn run(command: GamesCommand, client: &AdminClient) -> CliResult {
+    match command {
+        GamesCommand::List => {
+            let summaries: Vec<GameSummary> =
+                client.send(Method::GET, "/games").await?.json().await?;
+            print_summaries(&summaries);
+        }
+        GamesCommand::End { game_id } => {
+            let path = format!("/games/{}/end", game_id);
+            client.send(Method::POST, &path).await?;
+            println!("Game {} ended.", game_id);
+        }
+        GamesCommand::Delete { game_id } => {
+            let path = format!("/games/{}", game_id);
+            client.send(Method::DELETE, &path).await?;
+            println!("Game {} deleted.", game_id);
+        }
+        GamesCommand::Export {
+            game,
+            archived,
+            from,
+            to,
+            out,
+        } => {
+            let path = match (game, archived) {
+                (Some(game_id), false) => format!("/games/{}", game_id),
+                (None, false) => "/games/export".to_owned(),
+                (Some(game_id), true) => format!("/games/archive/{}", game_id),
+                (None, true) => archive_path(from, to),
+            };
+            let body = client.send(Method::GET, &path).await?.text().await?;
+            match out {
+                Some(out) => std::fs::write(out, body)?,
+                None => println!("{}", body),
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,75 @@
+//! Operator tool for the hex chess server.
+//!
+//! Live games are managed through the admin API of a running server, users and the database
+//! schema are managed directly in Postgres.
+
+mod games;
+mod migrate;
+mod users;
+
+use clap::{Parser, Subcommand};
+use hex_chess_app::server::config::Config;
+use sqlx::PgPool;
+
+pub type CliResult<T = ()> = Result<T, Box<dyn std::error::Error>>;
+
+#[derive(Debug, Parser)]
+#[command(name = "hex-chess-admin", about = "Operate a hex chess server.")]
+struct Cli {
+    /// Base url of the running server, defaults to `server.base_url` of the config.
+    #[arg(long, global = true)]
+    server: Option<String>,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Manage the live games of the running server.
+    #[command(subcommand)]
+    Games(games::GamesCommand),
+    /// Manage user accounts.
+    #[command(subcommand)]
+    Users(users::UsersCommand),
+    /// Manage the database schema.
+    #[command(subcommand)]
+    Migrate(migrate::MigrateCommand),
+}
+
+async fn connect(config: &Config) -> CliResult<PgPool> {
+    config.database.validate()?;
+    Ok(PgPool::connect(&config.database.url).await?)
+}
+
+async fn run(cli: Cli) -> CliResult {
+    let config = Config::load_unvalidated()?;
+    match cli.command {
+        Command::Games(command) => {
+            let client = games::AdminClient::new(&config, cli.server)?;
+            games::run(command, &client).await
+        }
+        Command::Users(command) => {
+            let pool = connect(&config).await?;
+            users::run(command, &pool).await
+        }
+        Command::Migrate(command) => {
+            let pool = connect(&config).await?;
+            migrate::run(command, &pool).await
+        }
+    }
+}
+
+#[actix_web::main]
+async fn main() -> std::process::ExitCode {
+    dotenvy::dotenv().ok();
+
+    let cli = Cli::parse();
+
+    match run(cli).await {
+        Ok(()) => std::process::ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("error: {}", err);
+            std::process::ExitCode::FAILURE
+        }
+    }
+}
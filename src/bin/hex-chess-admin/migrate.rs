@@ -0,0 +1,47 @@
+use clap::Subcommand;
+use hex_chess_app::server::db::migrations;
+use sqlx::PgPool;
+
+use crate::CliResult;
+
+#[derive(Debug, Subcommand)]
+pub enum MigrateCommand {
+    /// Apply every pending migration.
+    Run,
+    /// Revert applied migrations.
+    Rollback {
+        /// Version to roll back to, defaults to reverting only the last migration.
+        #[arg(long)]
+        target: Option<i64>,
+    },
+    /// List applied and pending migrations.
+    Status,
+}
+
+pub async fn run(command: MigrateCommand, pool: &PgPool) -> CliResult {
+    match command {
+        MigrateCommand::Run => {
+            migrations::run(pool).await?;
+            println!("Database is up to date.");
+        }
+        MigrateCommand::Rollback { target } => {
+            let target = migrations::rollback(pool, target).await?;
+            println!("Database rolled back to version {}.", target);
+        }
+        MigrateCommand::Status => {
+            let status = migrations::status(pool).await?;
+            for version in &status.applied {
+                let description = migrations::description(*version).unwrap_or("unknown");
+                println!("applied  {} {}", version, description);
+            }
+            for version in &status.pending {
+                let description = migrations::description(*version).unwrap_or("unknown");
+                println!("pending  {} {}", version, description);
+            }
+            if let Some(version) = status.dirty {
+                println!("migration {} is dirty, it failed halfway.", version);
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,85 @@
+use clap::Subcommand;
+use hex_chess_app::server::db::{
+    audit::{AuditEntry, ADMIN_CLI_ACTOR},
+    user::{User, ROLE_ADMIN, ROLE_PLAYER},
+};
+use sqlx::PgPool;
+
+use crate::CliResult;
+
+#[derive(Debug, Subcommand)]
+pub enum UsersCommand {
+    /// Show a user account.
+    Show { user_id: String },
+    /// Change the username of a user.
+    Rename { user_id: String, username: String },
+    /// Prevent a user from starting or joining games.
+    Ban { user_id: String },
+    /// Lift the ban of a user.
+    Unban { user_id: String },
+    /// Give a user access to the admin dashboard.
+    Promote { user_id: String },
+    /// Remove the admin role of a user.
+    Demote { user_id: String },
+}
+
+fn check_found(rows: u64, user_id: &str) -> CliResult {
+    if rows == 0 {
+        Err(format!("no user with id {} to update.", user_id).into())
+    } else {
+        Ok(())
+    }
+}
+
+async fn audit(action: &str, user_id: &str, details: Option<&str>, pool: &PgPool) -> CliResult {
+    AuditEntry::record(ADMIN_CLI_ACTOR, action, user_id, details, pool).await?;
+    Ok(())
+}
+
+pub async fn run(command: UsersCommand, pool: &PgPool) -> CliResult {
+    match command {
+        UsersCommand::Show { user_id } => {
+            let user = User::get_from_user_id(&user_id, pool).await?;
+            println!("id:         {}", user.id);
+            println!("user id:    {}", user.user_id);
+            println!("username:   {}", user.username);
+            println!("role:       {}", user.role);
+            println!("created at: {}", user.created_at);
+            match user.banned_at {
+                Some(banned_at) => println!("banned at:  {}", banned_at),
+                None => println!("banned:     no"),
+            }
+        }
+        UsersCommand::Rename { user_id, username } => {
+            let rows = User::update_username_from_user_id(&user_id, &username, pool).await?;
+            check_found(rows, &user_id)?;
+            audit("rename_user", &user_id, Some(&username), pool).await?;
+            println!("User {} renamed to {}.", user_id, username);
+        }
+        UsersCommand::Ban { user_id } => {
+            let rows = User::ban_from_user_id(&user_id, pool).await?;
+            check_found(rows, &user_id)?;
+            audit("ban_user", &user_id, None, pool).await?;
+            println!("User {} banned.", user_id);
+        }
+        UsersCommand::Unban { user_id } => {
+            let rows = User::unban_from_user_id(&user_id, pool).await?;
+            check_found(rows, &user_id)?;
+            audit("unban_user", &user_id, None, pool).await?;
+            println!("User {} unbanned.", user_id);
+        }
+        UsersCommand::Promote { user_id } => {
+            let rows = User::set_role_from_user_id(&user_id, ROLE_ADMIN, pool).await?;
+            check_found(rows, &user_id)?;
+            audit("set_role", &user_id, Some(ROLE_ADMIN), pool).await?;
+            println!("User {} is now an admin.", user_id);
+        }
+        UsersCommand::Demote { user_id } => {
+            let rows = User::set_role_from_user_id(&user_id, ROLE_PLAYER, pool).await?;
+            check_found(rows, &user_id)?;
+            audit("set_role", &user_id, Some(ROLE_PLAYER), pool).await?;
+            println!("User {} is no longer an admin.", user_id);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,88 @@
+//! Stand-in Web Push service for local testing.
+//!
+//! Subscriptions saved with an endpoint on this server receive the push messages of the hex
+//! chess server instead of a browser vendor's service. The messages stay encrypted, only what
+//! the push service sees is recorded and listed on `GET /messages`. Endpoints under `/gone/`
+//! answer as an expired subscription would.
+
+use std::sync::Mutex;
+
+use actix_web::{get, post, web, App, HttpRequest, HttpResponse, HttpServer};
+use clap::Parser;
+use serde::Serialize;
+
+#[derive(Debug, Parser)]
+#[command(
+    name = "hex-chess-push-sink",
+    about = "Record the Web Push messages of a hex chess server."
+)]
+struct Cli {
+    /// Address to listen on.
+    #[arg(long, default_value = "127.0.0.1:3002")]
+    addr: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct PushMessage {
+    subscription: String,
+    ttl: Option<String>,
+    encoding: Option<String>,
+    body_len: usize,
+    received_at: String,
+}
+
+#[derive(Default)]
+struct Messages(Mutex<Vec<PushMessage>>);
+
+fn header(req: &HttpRequest, name: &str) -> Option<String> {
+    req.headers()
+        .get(name)
+        .and_then(|value| value.to_str().ok())
+        .map(ToOwned::to_owned)
+}
+
+#[post("/push/{subscription}")]
+async fn push(
+    req: HttpRequest,
+    subscription: web::Path<String>,
+    body: web::Bytes,
+    messages: web::Data<Messages>,
+) -> HttpResponse {
+    let message = PushMessage {
+        subscription: subscription.into_inner(),
+        ttl: header(&req, "ttl"),
+        encoding: header(&req, "content-encoding"),
+        body_len: body.len(),
+        received_at: chrono::Utc::now().to_rfc3339(),
+    };
+    println!("{}", serde_json::to_string(&message).unwrap());
+    messages.0.lock().unwrap().push(message);
+    HttpResponse::Created().finish()
+}
+
+#[post("/gone/{subscription}")]
+async fn gone() -> HttpResponse {
+    HttpResponse::Gone().finish()
+}
+
+#[get("/messages")]
+async fn list_messages(messages: web::Data<Messages>) -> HttpResponse {
+    HttpResponse::Ok().json(&*messages.0.lock().unwrap())
+}
+
+#[actix_web::main]
+async fn main() -> std::io::Result<()> {
+    let cli = Cli::parse();
+    let messages = web::Data::new(Messages::default());
+    println!("push sink listening on http://{}", cli.addr);
+    HttpServer::new(move || {
+        App::new()
+            .app_data(messages.clone())
+            .service(push)
+            .service(gone)
+            .service(list_messages)
+    })
+    .bind(&cli.addr)?
+    .run()
+    .await
+}
@@ -7,11 +7,24 @@ use hex_chess_core::{
 };
 use leptos::*;
 use std::collections::HashSet;
+use std::time::Duration;
 
 use crate::{
-    pages::play::GameEventStream,
+    components::report::ReportPanel,
+    hooks::use_toggle_interval,
+    i18n::i18n_context,
+    pages::{play::GameEventStream, tournaments::BackToTournament},
     server::board::{GameEvent, PlayMove},
+    utils::{
+        clipboard::copy_to_clipboard,
+        local_storage::{get_item, set_item},
+        marks::{MarkColor, Marks},
+        notation::{cell_name, move_text, parse_move},
+        rate_limit::check_rate_limit,
+        variations::{rewind, Annotation, PlayedMove, Token, Variations},
+    },
 };
+use leptos_i18n::t;
 
 // use leptos_meta::*;
 
@@ -22,6 +35,7 @@ enum HexColor {
     White,
     Selected,
     LastMove,
+    Queued,
 }
 
 impl HexColor {
@@ -113,8 +127,20 @@ fn get_piece_url_and_alt(piece: Piece) -> (String, String) {
     (url, alt)
 }
 
+/// Piece being dragged, `position` is the pointer in the units of [`GRID_WIDTH`].
+#[derive(Debug, Clone, Copy)]
+struct Drag {
+    from: HexVector,
+    piece: Piece,
+    position: (f64, f64),
+    /// Only a drag once the pointer went far enough, before that it may be a click.
+    is_moving: bool,
+    /// Legal destination the piece snaps to.
+    target: Option<HexVector>,
+}
+
 #[allow(clippy::too_many_arguments)]
-fn hexagon<F>(
+fn hexagon<F, M>(
     cx: Scope,
     vector: HexVector,
     color: HexColor,
@@ -125,10 +151,16 @@ fn hexagon<F>(
     orientation: ReadSignal<Orientation>,
     can_promote: ReadSignal<Option<CanPromoteMove>>,
     last_move: ReadSignal<Option<(HexVector, HexVector)>>,
+    queued: ReadSignal<Option<(HexVector, HexVector)>>,
+    drag: ReadSignal<Option<Drag>>,
+    keyboard_focus: RwSignal<Option<HexVector>>,
+    on_mark: M,
 ) -> impl IntoView
 where
     F: Fn(HexVector, Option<PieceKind>) + Copy + 'static,
+    M: Fn(HexVector, ev::MouseEvent, bool) + Copy + 'static,
 {
+    let grid_vector = vector;
     let vector = move || {
         if orientation.get() == Orientation::Normal {
             vector
@@ -142,8 +174,11 @@ where
         let selected = selected.get();
         let orientation = orientation.get();
         let last_move = last_move.get();
+        let queued = queued.get();
         if selected.is_some_and(|pos| pos == vector) {
             HexColor::Selected
+        } else if queued.is_some_and(|(from, to)| from == vector || to == vector) {
+            HexColor::Queued
         } else if last_move.is_some_and(|(from, to)| from == vector || to == vector) {
             HexColor::LastMove
         } else if orientation == Orientation::Normal {
@@ -169,10 +204,51 @@ where
 
     let is_piece_and_dest = move || piece.get().is_some() && is_move_dest.get();
 
+    let is_dragged =
+        move || drag.with(|drag| drag.is_some_and(|drag| drag.is_moving && drag.from == vector()));
+    let is_drag_target =
+        move || drag.with(|drag| drag.is_some_and(|drag| drag.target == Some(vector())));
+
     let on_click = move |_| {
         on_select(vector(), None);
     };
 
+    // only the focused cell is in the tab order, the arrow keys move the focus between cells
+    let cell_ref = create_node_ref::<html::Li>(cx);
+    let is_focus_cell = move || {
+        keyboard_focus
+            .get()
+            .unwrap_or_else(|| HexVector::new_axial(0, 0))
+            == vector()
+    };
+    create_effect(cx, move |_| {
+        let focus = keyboard_focus.get();
+        let vector = match orientation.get_untracked() {
+            Orientation::Normal => grid_vector,
+            Orientation::Reversed => -grid_vector,
+        };
+        if focus == Some(vector) {
+            if let Some(cell) = cell_ref.get_untracked() {
+                let _ = cell.focus();
+            }
+        }
+    });
+
+    // the cell is named by this hidden text, the translations can't go in an attribute
+    let i18n = i18n_context(cx);
+    let label = move || {
+        let vector = vector();
+        let contents = match piece.get() {
+            Some(piece) => get_piece_url_and_alt(piece).1.into_view(cx),
+            None => t!(i18n, board_cell_empty).into_view(cx),
+        };
+        view! { cx,
+            {cell_name(vector).unwrap_or_default()}", "{contents}
+            {(selected.get() == Some(vector)).then(|| view! { cx, ", "{t!(i18n, board_cell_selected)} })}
+            {is_move_dest.get().then(|| view! { cx, ", "{t!(i18n, board_cell_move)} })}
+        }
+    };
+
     let is_promote = move || {
         can_promote.get().and_then(|promote_move| {
             if promote_move.to != vector() {
@@ -188,22 +264,34 @@ where
             class="hex-grid__item"
             class=("hex-grid__item__hide", hide)
             class=("hex-grid__item__is_piece", move || piece.get().is_some())
+            class=("hex-grid__item__dragged", is_dragged)
+            node_ref=cell_ref
+            role="button"
+            tabindex=move || if is_focus_cell() { 0 } else { -1 }
+            aria-hidden=move || hide().then_some("true")
+            on:focus=move |_| keyboard_focus.set(Some(vector()))
             on:click=on_click
+            on:mousedown=move |ev| on_mark(vector(), ev, true)
+            on:mouseup=move |ev| on_mark(vector(), ev, false)
         >
+            <span class="visually_hidden">{label}</span>
             <div
                 class="hex-grid__content"
+                aria-hidden="true"
                 class=("hex-grid__content__black", move || color() == HexColor::Black)
                 class=("hex-grid__content__grey", move || color() == HexColor::Grey)
                 class=("hex-grid__content__white", move || color() == HexColor::White)
                 class=("hex-grid__content__selected", move || color() == HexColor::Selected)
                 class=("hex-grid__content__last_move", move || color() == HexColor::LastMove)
+                class=("hex-grid__content__queued", move || color() == HexColor::Queued)
                 class=("hex-grid__content__is_dest", move || is_move_dest.get() && !is_piece_and_dest())
                 class=("hex-grid__content__is_piece_and_dest", is_piece_and_dest)
+                class=("hex-grid__content__drag_target", is_drag_target)
             >
                 {move || piece_image_url.get().map(|(url, alt)| {
                     let alt = format!("icon for a {}", alt);
                     view! { cx,
-                        <img class="piece_image" src=url alt=alt />
+                        <img class="piece_image" src=url alt=alt draggable="false" />
                     }
                 })}
             </div>
@@ -223,8 +311,23 @@ where
     let (piece_url, alt) = get_piece_url_and_alt(piece);
     let title = format!("Promote to a {}", alt);
     let alt = format!("button icon to promote to a {}", alt);
+    let on_key_down = move |ev: ev::KeyboardEvent| {
+        if matches!(ev.key().as_str(), "Enter" | " ") {
+            ev.prevent_default();
+            promote_fn(piece.kind);
+        }
+    };
     view! { cx,
-        <img on:click=on_click class="piece_image" src=piece_url alt=alt title=title />
+        <img
+            on:click=on_click
+            on:keydown=on_key_down
+            class="piece_image"
+            src=piece_url
+            alt=alt
+            title=title
+            role="button"
+            tabindex="0"
+        />
     }
 }
 
@@ -265,13 +368,17 @@ fn draw_hex_board<OS>(
     selected: ReadSignal<Option<HexVector>>,
     can_promote: ReadSignal<Option<CanPromoteMove>>,
     last_move: ReadSignal<Option<(HexVector, HexVector)>>,
+    queued: ReadSignal<Option<(HexVector, HexVector)>>,
+    marks: RwSignal<Marks>,
     color: impl Fn() -> PieceColor + 'static,
     on_select: OS,
 ) -> impl IntoView
 where
     OS: Fn(HexVector, Option<PieceKind>) + Copy + 'static,
 {
-    let legal_moves = create_memo(cx, move |_| board.get().get_legal_moves_for(color()));
+    let i18n = i18n_context(cx);
+    let color = create_memo(cx, move |_| color());
+    let legal_moves = create_memo(cx, move |_| board.get().get_legal_moves_for(color.get()));
 
     let current_legal_moves = create_memo(cx, move |_| {
         legal_moves.track();
@@ -288,10 +395,341 @@ where
         })
     });
 
+    // the marks of a position are gone once a move is played from it
+    create_effect(cx, move |prev: Option<()>| {
+        last_move.track();
+        if prev.is_some() {
+            marks.set(Marks::default());
+        }
+    });
+
+    // right button pressed and released on the same cell for a highlight, on another for an arrow
+    let (mark_start, set_mark_start) = create_signal(cx, None);
+    let on_mark = move |vector: HexVector, ev: ev::MouseEvent, is_down: bool| {
+        if ev.button() != 2 {
+            return;
+        }
+        if is_down {
+            set_mark_start.set(Some(vector));
+            return;
+        }
+        let Some(start) = mark_start.get_untracked() else {
+            return;
+        };
+        set_mark_start.set(None);
+        let color = MarkColor::from_modifiers(ev.shift_key(), ev.alt_key(), ev.ctrl_key());
+        marks.update(|marks| {
+            if start == vector {
+                marks.toggle_highlight(vector, color);
+            } else {
+                marks.toggle_arrow(start, vector, color);
+            }
+        });
+    };
+
+    // a piece of the player can be dragged to a legal destination, a press and release without
+    // moving is left to the click handlers
+    let board_ref = create_node_ref::<html::Div>(cx);
+    let drag = create_rw_signal(cx, None::<Drag>);
+    let on_pointer_down = move |ev: ev::PointerEvent| {
+        if ev.button() != 0 {
+            return;
+        }
+        let Some(position) = grid_position(board_ref, &ev) else {
+            return;
+        };
+        let Some(from) = nearest_cell(board_cells(), position, orientation.get_untracked(), 2.0)
+        else {
+            return;
+        };
+        let piece = board.with_untracked(|board| board.get_piece_at(from));
+        if let Some(piece) = piece.filter(|piece| piece.color == color.get_untracked()) {
+            drag.set(Some(Drag {
+                from,
+                piece,
+                position,
+                is_moving: false,
+                target: None,
+            }));
+        }
+    };
+    let on_pointer_move = move |ev: ev::PointerEvent| {
+        let Some(mut current) = drag.get_untracked() else {
+            return;
+        };
+        let Some(position) = grid_position(board_ref, &ev) else {
+            return;
+        };
+        if !current.is_moving {
+            let (x, y) = current.position;
+            if (position.0 - x).hypot(position.1 - y) < DRAG_THRESHOLD {
+                return;
+            }
+            on_select(current.from, None);
+            current.is_moving = true;
+        }
+        current.position = position;
+        current.target = current_legal_moves.with_untracked(|moves| {
+            let moves = moves.as_ref()?.iter().copied();
+            nearest_cell(moves, position, orientation.get_untracked(), SNAP_DISTANCE)
+        });
+        drag.set(Some(current));
+    };
+    let on_pointer_up = move |_| {
+        let current = drag.get_untracked();
+        drag.set(None);
+        if let Some(Drag {
+            is_moving: true,
+            target: Some(to),
+            ..
+        }) = current
+        {
+            // a promotion opens the picker on the destination, as with a click
+            on_select(to, None);
+        }
+    };
+    let on_drag_cancel = move |_| drag.set(None);
+
+    let ghost_image = create_memo(cx, move |_| {
+        drag.get()
+            .filter(|drag| drag.is_moving)
+            .map(|drag| get_piece_url_and_alt(drag.piece))
+    });
+    let ghost_style = move || {
+        drag.get()
+            .map(
+                |Drag {
+                     position: (x, y), ..
+                 }| {
+                    format!(
+                        "left: {}%; top: {}%;",
+                        x / GRID_WIDTH * 100.0,
+                        y / GRID_HEIGHT * 100.0
+                    )
+                },
+            )
+            .unwrap_or_default()
+    };
+
+    let drag_state = drag.read_only();
+
+    let keyboard_focus = create_rw_signal(cx, None);
+    let on_key_down = move |ev: ev::KeyboardEvent| {
+        let Some(cell) = keyboard_focus.get_untracked() else {
+            return;
+        };
+        let key = ev.key();
+        if matches!(key.as_str(), "Enter" | " ") {
+            ev.prevent_default();
+            on_select(cell, None);
+        } else if let Some(next) = neighbour_cell(cell, &key, orientation.get_untracked()) {
+            ev.prevent_default();
+            keyboard_focus.set(Some(next));
+        }
+    };
+
     view! { cx,
-        <ul class="hex-grid__list">
-        {move || GridIterator::new().map(|(vector, color)| hexagon(cx, vector, color, board, selected, on_select, current_legal_moves, orientation, can_promote, last_move)).collect_view(cx)}
-        </ul>
+        <div
+            class="hex-grid__board"
+            node_ref=board_ref
+            on:contextmenu=|ev| ev.prevent_default()
+            on:pointerdown=on_pointer_down
+            on:pointermove=on_pointer_move
+            on:pointerup=on_pointer_up
+            on:pointercancel=on_drag_cancel
+            on:pointerleave=on_drag_cancel
+        >
+            <h2 class="visually_hidden">{t!(i18n, board_label)}</h2>
+            <ul class="hex-grid__list" on:keydown=on_key_down>
+            {move || GridIterator::new().map(|(vector, color)| hexagon(cx, vector, color, board, selected, on_select, current_legal_moves, orientation, can_promote, last_move, queued, drag_state, keyboard_focus, on_mark)).collect_view(cx)}
+            </ul>
+            {draw_marks(cx, marks, orientation)}
+            {move || ghost_image.get().map(|(url, alt)| view! { cx,
+                <img class="piece_image hex-grid__ghost" src=url alt=alt style=ghost_style/>
+            })}
+        </div>
+    }
+}
+
+/// Distance the pointer goes before a press on a piece becomes a drag.
+const DRAG_THRESHOLD: f64 = 1.0;
+/// Farthest a dragged piece is from the centre of a legal destination to snap to it.
+const SNAP_DISTANCE: f64 = 3.0;
+
+/// Size of the grid in units of a third of the distance between two columns: a hexagon is 4
+/// wide, 3.6 high, and the columns are shifted by half a hexagon.
+const GRID_WIDTH: f64 = 34.0;
+const GRID_HEIGHT: f64 = 41.4;
+
+/// Centre of the hexagon of `cell` in the units of [`GRID_WIDTH`], following the placement of
+/// the items of `hex-grid__list` in the style sheet.
+fn cell_center(cell: HexVector, orientation: Orientation) -> Option<(f64, f64)> {
+    let cell = match orientation {
+        Orientation::Normal => cell,
+        Orientation::Reversed => -cell,
+    };
+    let index = GridIterator::new().position(|(vector, _)| vector == cell)?;
+    let column = index % 11;
+    let line = (index / 11 + 1) as f64;
+    let row = if column % 2 == 1 {
+        2.0 * line - 1.0
+    } else {
+        2.0 * line
+    };
+    Some((3.0 * column as f64 + 2.0, 1.8 * row))
+}
+
+/// Cells of the board, without the ones of the grid that are hidden.
+fn board_cells() -> impl Iterator<Item = HexVector> {
+    GridIterator::new()
+        .map(|(vector, _)| vector)
+        .filter(|vector| vector.mag() <= 5)
+}
+
+/// Cell next to `cell` on the screen in the direction of an arrow key. Left and right go to the
+/// neighbour in the next column that gets back to the same line on the following press, so a
+/// row is crossed in a zigzag.
+fn neighbour_cell(cell: HexVector, key: &str, orientation: Orientation) -> Option<HexVector> {
+    let (x, y) = cell_center(cell, orientation)?;
+    // the odd columns are half a cell higher
+    let column = ((x - 2.0) / 3.0).round() as usize;
+    let side = if column % 2 == 1 { 1.8 } else { -1.8 };
+    let position = match key {
+        "ArrowUp" => (x, y - 3.6),
+        "ArrowDown" => (x, y + 3.6),
+        "ArrowLeft" => (x - 3.0, y + side),
+        "ArrowRight" => (x + 3.0, y + side),
+        _ => return None,
+    };
+    nearest_cell(board_cells(), position, orientation, 1.0)
+}
+
+/// Cell whose centre is the closest to `position`, if it is within `max_distance`.
+fn nearest_cell(
+    cells: impl Iterator<Item = HexVector>,
+    (x, y): (f64, f64),
+    orientation: Orientation,
+    max_distance: f64,
+) -> Option<HexVector> {
+    cells
+        .filter_map(|cell| {
+            let (cell_x, cell_y) = cell_center(cell, orientation)?;
+            Some((cell, (cell_x - x).hypot(cell_y - y)))
+        })
+        .filter(|&(_, distance)| distance <= max_distance)
+        .min_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(cell, _)| cell)
+}
+
+/// Position of the pointer over the board in the units of [`GRID_WIDTH`].
+#[cfg(feature = "hydrate")]
+fn grid_position(board_ref: NodeRef<html::Div>, ev: &ev::PointerEvent) -> Option<(f64, f64)> {
+    let rect = board_ref.get_untracked()?.get_bounding_client_rect();
+    if rect.width() == 0.0 || rect.height() == 0.0 {
+        return None;
+    }
+    let x = (ev.client_x() as f64 - rect.left()) / rect.width() * GRID_WIDTH;
+    let y = (ev.client_y() as f64 - rect.top()) / rect.height() * GRID_HEIGHT;
+    Some((x, y))
+}
+
+#[cfg(not(feature = "hydrate"))]
+fn grid_position(_board_ref: NodeRef<html::Div>, _ev: &ev::PointerEvent) -> Option<(f64, f64)> {
+    None
+}
+
+fn hexagon_points((x, y): (f64, f64)) -> String {
+    [
+        (-2.0, 0.0),
+        (-1.0, -1.8),
+        (1.0, -1.8),
+        (2.0, 0.0),
+        (1.0, 1.8),
+        (-1.0, 1.8),
+    ]
+    .into_iter()
+    .map(|(dx, dy)| format!("{},{}", x + dx, y + dy))
+    .collect::<Vec<_>>()
+    .join(" ")
+}
+
+/// Highlights and arrows drawn over the cells, the pointer goes through to the board.
+fn draw_marks(
+    cx: Scope,
+    marks: RwSignal<Marks>,
+    orientation: ReadSignal<Orientation>,
+) -> impl IntoView {
+    let heads = MarkColor::ALL
+        .into_iter()
+        .map(|color| {
+            view! { cx,
+                <marker
+                    id=format!("hex-grid__arrow_head--{}", color.name())
+                    class=format!("hex-grid__mark--{}", color.name())
+                    viewBox="0 0 10 10"
+                    refX="5"
+                    refY="5"
+                    markerWidth="3"
+                    markerHeight="3"
+                    orient="auto-start-reverse"
+                >
+                    <path d="M 0 0 L 10 5 L 0 10 z"/>
+                </marker>
+            }
+        })
+        .collect_view(cx);
+
+    let highlights = move || {
+        let orientation = orientation.get();
+        marks.with(|marks| {
+            marks
+                .highlights
+                .iter()
+                .filter_map(|&(cell, color)| {
+                    let points = hexagon_points(cell_center(cell, orientation)?);
+                    Some(view! { cx,
+                        <polygon class=format!("hex-grid__highlight hex-grid__mark--{}", color.name()) points=points/>
+                    })
+                })
+                .collect_view(cx)
+        })
+    };
+
+    let arrows = move || {
+        let orientation = orientation.get();
+        marks.with(|marks| {
+            marks
+                .arrows
+                .iter()
+                .filter_map(|arrow| {
+                    let (x1, y1) = cell_center(arrow.from, orientation)?;
+                    let (x2, y2) = cell_center(arrow.to, orientation)?;
+                    // stop short of the centre so that the head stays inside the cell
+                    let length = ((x2 - x1).powi(2) + (y2 - y1).powi(2)).sqrt();
+                    let ratio = (length - 1.0) / length;
+                    let (x2, y2) = (x1 + (x2 - x1) * ratio, y1 + (y2 - y1) * ratio);
+                    Some(view! { cx,
+                        <line
+                            class=format!("hex-grid__arrow hex-grid__mark--{}", arrow.color.name())
+                            x1=x1 y1=y1 x2=x2 y2=y2
+                            marker-end=format!("url(#hex-grid__arrow_head--{})", arrow.color.name())
+                        />
+                    })
+                })
+                .collect_view(cx)
+        })
+    };
+
+    view! { cx,
+        <svg
+            class="hex-grid__marks"
+            viewBox=format!("0 0 {} {}", GRID_WIDTH, GRID_HEIGHT)
+            preserveAspectRatio="none"
+        >
+            <defs>{heads}</defs>
+            {highlights}
+            {arrows}
+        </svg>
     }
 }
 
@@ -303,6 +741,8 @@ fn orientation_manager(
     player_color: impl Fn() -> PieceColor + Copy + 'static,
     can_promote: ReadSignal<Option<CanPromoteMove>>,
     last_move: ReadSignal<Option<(HexVector, HexVector)>>,
+    queued: ReadSignal<Option<(HexVector, HexVector)>>,
+    marks: RwSignal<Marks>,
     is_solo: bool,
     on_select: impl Fn(HexVector, Option<PieceKind>) + Copy + 'static,
     back_one_turn: impl Fn(ev::MouseEvent) + 'static,
@@ -324,7 +764,7 @@ fn orientation_manager(
 
     view! { cx,
         <div>
-            {draw_hex_board(cx, board, orientation, selected, can_promote, last_move, player_color, on_select)}
+            {draw_hex_board(cx, board, orientation, selected, can_promote, last_move, queued, marks, player_color, on_select)}
             <div class="under_board">
                 <div class="history_movement">
                     <img on:click=back_one_turn class="board_button" src="/assets/icons/backward.svg" alt="button icon for going back one turn" title="Go back one turn"/>
@@ -337,6 +777,64 @@ fn orientation_manager(
     }
 }
 
+/// Text field to play a move by typing it in notation, such as `f5-f6`.
+fn move_entry(
+    cx: Scope,
+    board: ReadSignal<Board>,
+    player_color: impl Fn() -> PieceColor + 'static,
+    on_select: impl Fn(HexVector, Option<PieceKind>) + 'static,
+) -> impl IntoView {
+    let i18n = i18n_context(cx);
+    let (typed_move, set_typed_move) = create_signal(cx, String::new());
+    let (is_invalid, set_is_invalid) = create_signal(cx, false);
+
+    let on_submit = move |ev: ev::SubmitEvent| {
+        ev.prevent_default();
+        let color = player_color();
+        let mov = parse_move(&typed_move.get_untracked()).filter(|&(from, to, _)| {
+            board.with_untracked(|board| {
+                board
+                    .get_legal_moves_for(color)
+                    .get(&from)
+                    .is_some_and(|moves| {
+                        moves
+                            .iter()
+                            .copied()
+                            .any(|mov| MaybePromoteMove::to(mov) == to)
+                    })
+            })
+        });
+        match mov {
+            Some((from, to, promote_to)) => {
+                on_select(from, None);
+                on_select(to, promote_to);
+                set_typed_move.set(String::new());
+                set_is_invalid.set(false);
+            }
+            None => set_is_invalid.set(true),
+        }
+    };
+
+    view! { cx,
+        <form class="move_entry" on:submit=on_submit>
+            <label>
+                {t!(i18n, move_input)}
+                <input
+                    type="text"
+                    autocomplete="off"
+                    placeholder="f5-f6"
+                    prop:value=typed_move
+                    on:input=move |ev| set_typed_move.set(event_target_value(&ev))
+                />
+            </label>
+            <button type="submit" class="small_button">{t!(i18n, move_play)}</button>
+            <span role="alert">
+                {move || is_invalid.get().then(|| t!(i18n, move_invalid))}
+            </span>
+        </form>
+    }
+}
+
 fn get_board_buttons_func(
     set_selected: WriteSignal<Option<HexVector>>,
     set_board: WriteSignal<Board>,
@@ -382,8 +880,317 @@ fn get_board_buttons_func(
     (back_one_turn, advance_history, unwind_history)
 }
 
+/// Archived game: the history buttons step through its moves, and autoplay plays them one after
+/// the other at the chosen speed.
+#[component]
+pub fn ReplayBoard(cx: Scope, board: Board) -> impl IntoView {
+    const SPEEDS_MS: [u64; 4] = [500, 1000, 2000, 4000];
+
+    let i18n = i18n_context(cx);
+    let last_move = board.get_last_played_move().map(|mov| (mov.from, mov.to));
+    let (board, set_board) = create_signal(cx, board);
+    let (last_move, set_last_move) = create_signal(cx, last_move);
+    let (selected, set_selected) = create_signal(cx, None);
+    let (can_promote, _) = create_signal(cx, None);
+    let (autoplay, set_autoplay) = create_signal(cx, false);
+    let (speed_ms, set_speed_ms) = create_signal(cx, 1000);
+    let marks = create_rw_signal(cx, Marks::default());
+    let (queued, _) = create_signal(cx, None);
+
+    let (back_one_turn, advance_history, unwind_history) =
+        get_board_buttons_func(set_selected, set_board, set_last_move);
+
+    let is_at_end = move || board.with_untracked(|board| board.get_next_moves().is_empty());
+
+    let step = move || {
+        set_board.update(|board| {
+            board.advance_history();
+            set_last_move.set(board.get_last_played_move().map(|mov| (mov.from, mov.to)));
+        });
+        if is_at_end() {
+            set_autoplay.set(false);
+        }
+    };
+    use_toggle_interval(
+        cx,
+        move || {
+            autoplay
+                .get()
+                .then(|| Duration::from_millis(speed_ms.get()))
+        },
+        step,
+    );
+
+    let on_autoplay = move |_| {
+        if !autoplay.get_untracked() && is_at_end() {
+            set_board.update(rewind);
+            set_last_move.set(None);
+        }
+        set_autoplay.update(|autoplay| *autoplay = !*autoplay);
+    };
+    let autoplay_text = move || {
+        if autoplay.get() {
+            t!(i18n, replay_pause).into_view(cx)
+        } else {
+            t!(i18n, replay_autoplay).into_view(cx)
+        }
+    };
+    let speed_options = SPEEDS_MS
+        .into_iter()
+        .map(|ms| {
+            view! { cx,
+                <option value=ms.to_string() selected=move || speed_ms.get() == ms>
+                    {format!("{} s", ms as f64 / 1000.0)}
+                </option>
+            }
+        })
+        .collect_view(cx);
+
+    view! { cx,
+        <div>
+            {orientation_manager(cx, board, selected, || PieceColor::White, can_promote, last_move, queued, marks, true, |_, _| (), back_one_turn, advance_history, unwind_history)}
+            <div class="replay__controls">
+                <button class="small_button" on:click=on_autoplay>{autoplay_text}</button>
+                <label>
+                    {t!(i18n, replay_speed)}
+                    <select on:change=move |ev| {
+                        if let Ok(ms) = event_target_value(&ev).parse::<u64>() {
+                            set_speed_ms.set(ms);
+                        }
+                    }>
+                        {speed_options}
+                    </select>
+                </label>
+            </div>
+        </div>
+    }
+}
+
+/// Free analysis of a line: a move played from an earlier position starts a variation, listed
+/// with the comments and annotations in the sidebar.
 #[component]
-pub fn MultiBoard(cx: Scope, events: GameEventStream) -> impl IntoView {
+pub fn AnalysisBoard(cx: Scope, moves: Vec<PlayedMove>) -> impl IntoView {
+    let i18n = i18n_context(cx);
+    let (tree, set_tree) = create_signal(cx, Variations::from_moves(moves));
+    let (current, set_current) = create_signal(cx, Variations::ROOT);
+    let (board, set_board) = create_signal(cx, Board::new());
+    let (last_move, set_last_move) = create_signal(cx, None);
+    let (selected, set_selected) = create_signal(cx, None);
+    let (dest, set_dest) = create_signal(cx, None);
+    let (can_promote, set_can_promote) = create_signal(cx, None);
+    let (exported, set_exported) = create_signal(cx, None::<String>);
+    let marks = create_rw_signal(cx, Marks::default());
+    let (queued, _) = create_signal(cx, None);
+
+    let is_end = create_memo(cx, move |_| board.get().is_end());
+
+    create_effect(cx, move |_| {
+        let node = current.get();
+        let (node_board, node_move, node_marks) = tree.with_untracked(|tree| {
+            (
+                tree.board_at(node),
+                tree.mov(node),
+                tree.marks(node).clone(),
+            )
+        });
+        set_board.set(node_board);
+        set_last_move.set(node_move.map(|mov| (mov.from, mov.to)));
+        // after the last move, whose change clears the marks of the board
+        marks.set(node_marks);
+        set_selected.set(None);
+        set_dest.set(None);
+        set_can_promote.set(None);
+    });
+
+    // the marks belong to the position, to be written in the export
+    create_effect(cx, move |_| {
+        let node_marks = marks.get();
+        let node = current.get_untracked();
+        if tree.with_untracked(|tree| tree.marks(node) != &node_marks) {
+            set_tree.update(|tree| tree.set_marks(node, node_marks));
+        }
+    });
+
+    let on_select = move |pos: HexVector, promote_to: Option<PieceKind>| {
+        let (target_piece, color) =
+            board.with(|board| (board.get_piece_at(pos), board.get_player_turn()));
+        if is_end.get().is_some() {
+            return;
+        }
+        match (selected.get(), target_piece) {
+            (_, Some(piece)) if piece.color == color => {
+                set_selected.set(Some(pos));
+                set_dest.set(None);
+            }
+            (Some(_), _) => set_dest.set(Some((pos, promote_to))),
+            (None, _) => (),
+        }
+    };
+
+    create_effect(cx, move |_| {
+        let (Some(from), Some((to, promote_to))) = (selected.get(), dest.get()) else {
+            return;
+        };
+        let res = board.with_untracked(|board| board.clone().play_move(from, to, promote_to));
+        match res {
+            Ok(Some(promote_move)) => set_can_promote.set(Some(promote_move)),
+            Ok(None) => {
+                let mut node = current.get_untracked();
+                let mov = PlayedMove {
+                    from,
+                    to,
+                    promote_to,
+                };
+                set_tree.update(|tree| node = tree.play(node, mov));
+                set_current.set(node);
+            }
+            Err(_) => {
+                set_selected.set(None);
+                set_dest.set(None);
+                set_can_promote.set(None);
+            }
+        }
+    });
+
+    let color = move || board.with(Board::get_player_turn);
+
+    let back_one_turn = move |_| {
+        if let Some(parent) = tree.with(|tree| tree.parent(current.get())) {
+            set_current.set(parent);
+        }
+    };
+    let advance_history = move |_| {
+        if let Some(child) = tree.with(|tree| tree.main_child(current.get())) {
+            set_current.set(child);
+        }
+    };
+    let unwind_history = move |_| set_current.set(tree.with(|tree| tree.line_end(current.get())));
+
+    let render_token = move |token: Token| match token {
+        Token::MoveNumber(number) => view! { cx,
+            <span class="analysis__number">{number}</span>
+        }
+        .into_view(cx),
+        Token::Move { id, text } => view! { cx,
+            <span
+                class="analysis__move"
+                class=("analysis__move--current", move || current.get() == id)
+                on:click=move |_| set_current.set(id)
+            >
+                {text}
+            </span>
+        }
+        .into_view(cx),
+        Token::Comment(comment) => view! { cx,
+            <span class="analysis__comment">{comment}</span>
+        }
+        .into_view(cx),
+        Token::Marks(_) => ().into_view(cx),
+        Token::StartVariation => view! { cx, <span>"("</span> }.into_view(cx),
+        Token::EndVariation => view! { cx, <span>")"</span> }.into_view(cx),
+    };
+    let moves_list = move || {
+        tree.with(Variations::tokens)
+            .into_iter()
+            .map(render_token)
+            .collect_view(cx)
+    };
+
+    let annotation_button = move |annotation: Annotation| {
+        let is_set = move || tree.with(|tree| tree.annotation(current.get()) == Some(annotation));
+        let on_click = move |_| {
+            let node = current.get_untracked();
+            let annotation = (!is_set()).then_some(annotation);
+            set_tree.update(|tree| tree.set_annotation(node, annotation));
+        };
+        view! { cx,
+            <button class="small_button" class=("analysis__annotation--set", is_set) on:click=on_click>
+                {annotation.symbol()}
+            </button>
+        }
+    };
+
+    let node_actions = move || {
+        let node = current.get();
+        (node != Variations::ROOT).then(|| {
+            let is_variation = tree.with(|tree| tree.is_variation(node));
+            let comment = tree.with(|tree| tree.comment(node).to_owned());
+            view! { cx,
+                <div class="analysis__actions">
+                    {Annotation::ALL.into_iter().map(annotation_button).collect_view(cx)}
+                    <button
+                        class="small_button"
+                        disabled=!is_variation
+                        on:click=move |_| set_tree.update(|tree| tree.promote(node))
+                    >
+                        {t!(i18n, analysis_promote)}
+                    </button>
+                    <button class="small_button" on:click=move |_| {
+                        let mut parent = node;
+                        set_tree.update(|tree| parent = tree.delete(node));
+                        set_current.set(parent);
+                    }>
+                        {t!(i18n, analysis_delete)}
+                    </button>
+                </div>
+                <label class="analysis__comment_input">
+                    {t!(i18n, analysis_comment)}
+                    <textarea
+                        prop:value=comment
+                        on:change=move |ev| {
+                            let comment = event_target_value(&ev).trim().to_owned();
+                            set_tree.update(|tree| tree.set_comment(node, comment));
+                        }
+                    />
+                </label>
+            }
+        })
+    };
+
+    let export = move || {
+        exported.get().map(|notation| {
+            let on_copy = {
+                let notation = notation.clone();
+                move |_| {
+                    copy_to_clipboard(&notation);
+                }
+            };
+            view! { cx,
+                <textarea class="analysis__export" readonly prop:value=notation/>
+                <button class="small_button" on:click=on_copy>{t!(i18n, analysis_copy)}</button>
+            }
+        })
+    };
+
+    view! { cx,
+        <div class="analysis">
+            <div class="board">
+                {orientation_manager(cx, board, selected, color, can_promote, last_move, queued, marks, true, on_select, back_one_turn, advance_history, unwind_history)}
+                {move_entry(cx, board, color, on_select)}
+            </div>
+            <div class="analysis__sidebar">
+                <p class="analysis__moves">{moves_list}</p>
+                {node_actions}
+                <button class="small_button" on:click=move |_| set_exported.set(Some(tree.with(Variations::to_notation)))>
+                    {t!(i18n, analysis_export)}
+                </button>
+                {export}
+            </div>
+        </div>
+    }
+}
+
+/// Key in the local storage of the setting to confirm moves.
+const CONFIRM_MOVES_KEY: &str = "confirm_moves";
+
+/// Board of a live game, `compact` leaves out the history buttons and the report panel to fit
+/// in a grid of boards.
+#[component]
+pub fn MultiBoard(
+    cx: Scope,
+    events: GameEventStream,
+    #[prop(optional)] compact: bool,
+) -> impl IntoView {
     let (selected, set_selected) = create_signal(cx, None);
     let (dest, set_dest) = create_signal(cx, None);
     let (can_promote, set_can_promote) = create_signal(cx, None);
@@ -391,8 +1198,26 @@ pub fn MultiBoard(cx: Scope, events: GameEventStream) -> impl IntoView {
     let (player_infos, set_player_infos) = create_signal(cx, (PieceColor::White, None));
 
     let (last_move, set_last_move) = create_signal(cx, None);
+    let (server_shutting_down, set_server_shutting_down) = create_signal(cx, false);
+    let (terminated, set_terminated) = create_signal(cx, false);
+    let (timed_out, set_timed_out) = create_signal(cx, None);
+    let (spectating, set_spectating) = create_signal(cx, false);
+    let marks = create_rw_signal(cx, Marks::default());
+    let (opponent_move, set_opponent_move) = create_signal(cx, None::<String>);
+    // a move chosen during the turn of the opponent, played as soon as it is the player's turn
+    let (premove, set_premove) = create_signal(cx, None);
+    // with `confirm_moves`, a move waits for a second click on its destination before being sent
+    let (confirm_moves, set_confirm_moves) = create_signal(cx, false);
+    let (to_confirm, set_to_confirm) = create_signal(cx, None);
+    let (confirmed, set_confirmed) = create_signal(cx, None);
+    let (queued, set_queued) = create_signal(cx, None);
+    let is_interrupted =
+        move || server_shutting_down.get() || terminated.get() || timed_out.get().is_some();
+
+    let i18n = i18n_context(cx);
 
     let is_end = create_memo(cx, move |_| board.get().is_end());
+    let is_over = move || is_end.get().is_some() || timed_out.get().is_some();
 
     create_effect(cx, move |_| {
         if is_end.get().is_some() {
@@ -421,14 +1246,32 @@ pub fn MultiBoard(cx: Scope, events: GameEventStream) -> impl IntoView {
                 board.play_move(from, to, promote_to).unwrap();
             });
             set_last_move.set(Some((from, to)));
+            set_opponent_move.set(Some(move_text(from, to, promote_to)));
             if is_in_history || selected.get_untracked().is_some_and(|pos| pos == to) {
                 set_selected.set(None);
             }
+            if let Some((from, to, promote_to)) = premove.get_untracked() {
+                set_premove.set(None);
+                let res =
+                    board.with_untracked(|board| board.clone().play_move(from, to, promote_to));
+                // a premove that promotes without a chosen piece promotes to a queen
+                let promote_to = match res {
+                    Ok(None) => Some(promote_to),
+                    Ok(Some(_)) => Some(promote_to.or(Some(PieceKind::Queen))),
+                    Err(_) => None,
+                };
+                if let Some(promote_to) = promote_to {
+                    set_confirmed.set(Some((from, to, promote_to)));
+                    set_selected.set(Some(from));
+                    set_dest.set(Some((to, promote_to)));
+                }
+            }
         }
         GameEvent::RejoinedGame {
             game_id,
             player_color,
             board,
+            ..
         } => {
             if let Some(board) = board {
                 if let Some(last_move) = board.get_last_played_move() {
@@ -438,6 +1281,19 @@ pub fn MultiBoard(cx: Scope, events: GameEventStream) -> impl IntoView {
             }
             set_player_infos.set((player_color, Some(game_id)))
         }
+        // no game id in the player infos, the spectator can't select pieces
+        GameEvent::Spectating { board, .. } => {
+            if let Some(board) = board {
+                if let Some(last_move) = board.get_last_played_move() {
+                    set_last_move.set(Some((last_move.from, last_move.to)));
+                }
+                set_board.set(board);
+            }
+            set_spectating.set(true);
+        }
+        GameEvent::ServerShuttingDown => set_server_shutting_down.set(true),
+        GameEvent::GameTerminated => set_terminated.set(true),
+        GameEvent::TimedOut { loser } => set_timed_out.set(Some(loser)),
         _ => (),
     });
 
@@ -445,27 +1301,53 @@ pub fn MultiBoard(cx: Scope, events: GameEventStream) -> impl IntoView {
 
     let play_server_move = create_server_action::<PlayMove>(cx);
 
+    create_effect(cx, move |_| {
+        if let Some(Err(_)) = play_server_move.value().get() {
+            check_rate_limit(cx);
+        }
+    });
+
     let on_select = move |pos: HexVector, promote_to: Option<PieceKind>| {
         let (color, ids) = player_infos.get();
 
         let selected = selected.get();
         let (target_piece, is_turn) =
             board.with(|board| (board.get_piece_at(pos), board.get_player_turn() == color));
-        if is_end.get().is_some() || ids.is_none() {
+        if is_end.get().is_some() || ids.is_none() || is_interrupted() {
             return;
         }
+        if let Some(mov) = to_confirm.get_untracked() {
+            set_to_confirm.set(None);
+            if mov.1 == pos && promote_to.is_none() {
+                set_confirmed.set(Some(mov));
+                return;
+            }
+        }
         match (selected, target_piece) {
             (_, Some(piece)) if piece.color == color => {
                 set_selected.set(Some(pos));
                 set_dest.set(None);
+                set_premove.set(None);
             }
             (Some(_), _) if is_turn => {
                 set_dest.set(Some((pos, promote_to)));
             }
-            _ => (),
+            (Some(from), _) => {
+                set_premove.set(Some((from, pos, promote_to)));
+                set_selected.set(None);
+            }
+            (None, _) => set_premove.set(None),
         }
     };
 
+    create_effect(cx, move |_| {
+        set_confirm_moves.set(get_item(CONFIRM_MOVES_KEY).as_deref() == Some("true"));
+    });
+    create_effect(cx, move |_| {
+        let mov = premove.get().or(to_confirm.get());
+        set_queued.set(mov.map(|(from, to, _)| (from, to)));
+    });
+
     let (back_one_turn, advance_history, unwind_history) =
         get_board_buttons_func(set_selected, set_board, set_last_move);
 
@@ -479,6 +1361,15 @@ pub fn MultiBoard(cx: Scope, events: GameEventStream) -> impl IntoView {
             return;
         }
         if let (Some(game_id), Some(from), Some((to, promote_to))) = (ids, from, to) {
+            if confirm_moves.get_untracked() && confirmed.get() != Some((from, to, promote_to)) {
+                let res =
+                    board.with_untracked(|board| board.clone().play_move(from, to, promote_to));
+                // a promotion is confirmed once its piece is chosen
+                if let Ok(None) = res {
+                    set_to_confirm.set(Some((from, to, promote_to)));
+                    return;
+                }
+            }
             let mut move_res = Ok(None);
             set_board.update(|board| {
                 move_res = board.play_move(from, to, promote_to);
@@ -487,6 +1378,8 @@ pub fn MultiBoard(cx: Scope, events: GameEventStream) -> impl IntoView {
                 Ok(Some(promote_move)) => set_can_promote.set(Some(promote_move)),
                 Ok(None) => {
                     set_can_promote.set(None);
+                    set_to_confirm.set(None);
+                    set_confirmed.set(None);
                     set_dest.set(None);
                     set_selected.set(None);
                     set_last_move.set(Some((from, to)));
@@ -502,28 +1395,115 @@ pub fn MultiBoard(cx: Scope, events: GameEventStream) -> impl IntoView {
         }
     });
 
+    let board_view = if compact {
+        let (orientation, set_orientation) = create_signal(cx, Orientation::Normal);
+        create_effect(cx, move |_| {
+            set_orientation.set(match player_color() {
+                PieceColor::Black => Orientation::Reversed,
+                PieceColor::White => Orientation::Normal,
+            })
+        });
+        let to_play =
+            move || !is_over() && board.with(|board| board.get_player_turn() == player_color());
+        view! { cx,
+            <div class="compact_board" class=("compact_board__to_play", to_play)>
+                {draw_hex_board(cx, board, orientation, selected, can_promote, last_move, queued, marks, player_color, on_select)}
+            </div>
+        }
+        .into_view(cx)
+    } else {
+        orientation_manager(
+            cx,
+            board,
+            selected,
+            player_color,
+            can_promote,
+            last_move,
+            queued,
+            marks,
+            false,
+            on_select,
+            back_one_turn,
+            advance_history,
+            unwind_history,
+        )
+        .into_view(cx)
+    };
+
     view! { cx,
 
         <div>
-            {orientation_manager(cx, board, selected, player_color, can_promote, last_move, false, on_select, back_one_turn, advance_history, unwind_history)}
-            {move || is_end.get().map(|end| {
-                match end {
-                    hex_chess_core::board::GameEnd::Win(color) => {
-                        view! { cx,
-                            <p>{format!("{:?} wins!", color)}</p>
-                        }
-                    },
-                    hex_chess_core::board::GameEnd::Draw => {
-                        view! { cx,
-                            <p>"Draw"</p>
-                        }
-                    },
-                    hex_chess_core::board::GameEnd::Stalemate { winner } => {
-                        view! { cx,
-                            <p>{format!("Stalemate, {:?} wins 3/4 of the points", winner)}</p>
+            {board_view}
+            {move || to_confirm.get().map(|mov| view! { cx,
+                <div class="move_confirm">
+                    <button class="small_button" on:click=move |_| set_confirmed.set(Some(mov))>
+                        {t!(i18n, move_confirm)}
+                    </button>
+                    <button class="small_button" on:click=move |_| {
+                        set_to_confirm.set(None);
+                        set_dest.set(None);
+                    }>
+                        {t!(i18n, move_cancel)}
+                    </button>
+                </div>
+            })}
+            {(!compact).then(|| view! { cx,
+                {move_entry(cx, board, player_color, on_select)}
+                <label class="confirm_moves">
+                    <input
+                        type="checkbox"
+                        prop:checked=confirm_moves
+                        on:change=move |ev| {
+                            let checked = event_target_checked(&ev);
+                            set_confirm_moves.set(checked);
+                            set_item(CONFIRM_MOVES_KEY, &checked.to_string());
                         }
-                    },
-                }
+                    />
+                    {t!(i18n, confirm_moves)}
+                </label>
+            })}
+            <p class="visually_hidden" aria-live="polite">
+                {move || opponent_move.get().map(|text| view! { cx,
+                    {t!(i18n, opponent_played)} " " {text}
+                })}
+            </p>
+            <div aria-live="polite">
+                {move || spectating.get().then(|| view! { cx,
+                    <p>{t!(i18n, spectating)}</p>
+                })}
+                {move || server_shutting_down.get().then(|| view! { cx,
+                    <p>{t!(i18n, server_restarting)}</p>
+                })}
+                {move || terminated.get().then(|| view! { cx,
+                    <p>{t!(i18n, game_terminated)}</p>
+                })}
+                {move || timed_out.get().map(|loser| view! { cx,
+                    <p>{format!("{:?} ", loser)}{t!(i18n, ran_out_of_time)}</p>
+                })}
+                {move || is_end.get().map(|end| {
+                    match end {
+                        hex_chess_core::board::GameEnd::Win(color) => {
+                            view! { cx,
+                                <p>{format!("{:?} wins!", color)}</p>
+                            }
+                        },
+                        hex_chess_core::board::GameEnd::Draw => {
+                            view! { cx,
+                                <p>"Draw"</p>
+                            }
+                        },
+                        hex_chess_core::board::GameEnd::Stalemate { winner } => {
+                            view! { cx,
+                                <p>{format!("Stalemate, {:?} wins 3/4 of the points", winner)}</p>
+                            }
+                        },
+                    }
+                })}
+            </div>
+            {move || player_infos.get().1.filter(|_| is_over() && !compact).map(|game_id| view! { cx,
+                <BackToTournament game_id=game_id/>
+                <a href=format!("/game/{}", game_id) class="small_button">{t!(i18n, replay)}</a>
+                <ReportPanel game_id=game_id/>
             })}
         </div>
 
@@ -537,6 +1517,8 @@ pub fn SoloBoard(cx: Scope) -> impl IntoView {
     let (can_promote, set_can_promote) = create_signal(cx, None);
     let (dest, set_dest) = create_signal(cx, None);
     let (last_move, set_last_move) = create_signal(cx, None);
+    let marks = create_rw_signal(cx, Marks::default());
+    let (queued, _) = create_signal(cx, None);
 
     let is_end = create_memo(cx, move |_| board.get().is_end());
 
@@ -597,31 +1579,36 @@ pub fn SoloBoard(cx: Scope) -> impl IntoView {
                 color,
                 can_promote,
                 last_move,
+                queued,
+                marks,
                 true,
                 on_select,
                 back_one_turn,
                 advance_history,
                 unwind_history
             )}
-            {move || is_end.get().map(|end| {
-                match end {
-                    hex_chess_core::board::GameEnd::Win(color) => {
-                        view! { cx,
-                            <p>{format!("{:?} wins!", color)}</p>
-                        }
-                    },
-                    hex_chess_core::board::GameEnd::Draw => {
-                        view! { cx,
-                            <p>"Draw"</p>
-                        }
-                    },
-                    hex_chess_core::board::GameEnd::Stalemate { winner } => {
-                        view! { cx,
-                            <p>{format!("Stalemate, {:?} wins 3/4 of the points", winner)}</p>
-                        }
-                    },
-                }
-            })}
+            {move_entry(cx, board, color, on_select)}
+            <div aria-live="polite">
+                {move || is_end.get().map(|end| {
+                    match end {
+                        hex_chess_core::board::GameEnd::Win(color) => {
+                            view! { cx,
+                                <p>{format!("{:?} wins!", color)}</p>
+                            }
+                        },
+                        hex_chess_core::board::GameEnd::Draw => {
+                            view! { cx,
+                                <p>"Draw"</p>
+                            }
+                        },
+                        hex_chess_core::board::GameEnd::Stalemate { winner } => {
+                            view! { cx,
+                                <p>{format!("Stalemate, {:?} wins 3/4 of the points", winner)}</p>
+                            }
+                        },
+                    }
+                })}
+            </div>
         </div>
 
     }
@@ -0,0 +1,126 @@
+use leptos::*;
+use leptos_i18n::t;
+use uuid::Uuid;
+
+use crate::{
+    i18n::i18n_context,
+    pages::play::GameEventStream,
+    server::board::{ChatAuthor, ChatError, ChatMessage, GameEvent, SendChatMessage, SetChatMuted},
+    utils::rate_limit::check_rate_limit,
+};
+use hex_chess_core::piece::Color as PieceColor;
+
+fn render_author(cx: Scope, author: ChatAuthor) -> impl IntoView {
+    let i18n = i18n_context(cx);
+    match author {
+        ChatAuthor::Player(PieceColor::White) => t!(i18n, chat_white).into_view(cx),
+        ChatAuthor::Player(PieceColor::Black) => t!(i18n, chat_black).into_view(cx),
+        ChatAuthor::Spectator(username) => username.into_view(cx),
+    }
+}
+
+fn render_error(cx: Scope, err: ChatError) -> impl IntoView {
+    let i18n = i18n_context(cx);
+    match err {
+        ChatError::Empty => t!(i18n, chat_empty).into_view(cx),
+        ChatError::TooLong { .. } => t!(i18n, chat_too_long).into_view(cx),
+        ChatError::RateLimited => t!(i18n, chat_rate_limited).into_view(cx),
+        ChatError::Rejected => t!(i18n, chat_rejected).into_view(cx),
+        ChatError::Banned => t!(i18n, chat_banned).into_view(cx),
+    }
+}
+
+/// Chat panel of a game, fed by the same event stream as the board.
+#[component]
+pub fn GameChat(cx: Scope, events: GameEventStream) -> impl IntoView {
+    let (game_id, set_game_id) = create_signal(cx, None::<Uuid>);
+    let (messages, set_messages) = create_signal(cx, Vec::<ChatMessage>::new());
+    let (input, set_input) = create_signal(cx, String::new());
+    let (muted, set_muted) = create_signal(cx, false);
+
+    let i18n = i18n_context(cx);
+
+    events.listen(cx, move |event| match event {
+        GameEvent::GameStart { game_id, .. } => set_game_id.set(Some(game_id)),
+        GameEvent::RejoinedGame { game_id, chat, .. } => {
+            set_game_id.set(Some(game_id));
+            set_messages.set(chat);
+        }
+        GameEvent::Spectating { game_id, .. } => set_game_id.set(Some(game_id)),
+        GameEvent::ChatMessage(message) => set_messages.update(|messages| messages.push(message)),
+        _ => (),
+    });
+
+    let send_message = create_server_action::<SendChatMessage>(cx);
+    let set_server_muted = create_server_action::<SetChatMuted>(cx);
+
+    create_effect(cx, move |_| {
+        if let Some(Err(_)) = send_message.value().get() {
+            check_rate_limit(cx);
+        }
+    });
+
+    let error = move || match send_message.value().get() {
+        Some(Ok(Err(err))) => Some(render_error(cx, err)),
+        _ => None,
+    };
+
+    let on_submit = move |ev: ev::SubmitEvent| {
+        ev.prevent_default();
+        let Some(game_id) = game_id.get_untracked() else {
+            return;
+        };
+        let text = input.get_untracked();
+        if text.trim().is_empty() {
+            return;
+        }
+        send_message.dispatch(SendChatMessage { game_id, text });
+        set_input.set(String::new());
+    };
+
+    let on_toggle_mute = move |_| {
+        let muted = !muted.get_untracked();
+        set_muted.set(muted);
+        if let Some(game_id) = game_id.get_untracked() {
+            set_server_muted.dispatch(SetChatMuted { game_id, muted });
+        }
+    };
+
+    let render_messages = move || {
+        messages
+            .get()
+            .into_iter()
+            .map(|message| {
+                view! { cx,
+                    <li class="chat__message">
+                        <span class="chat__author">{render_author(cx, message.author)}</span>
+                        <span>{message.text}</span>
+                    </li>
+                }
+            })
+            .collect_view(cx)
+    };
+
+    view! { cx,
+        <div class="chat">
+            <div class="chat__header">
+                <p>{t!(i18n, chat)}</p>
+                <button class="small_button" on:click=on_toggle_mute>
+                    {move || if muted.get() { t!(i18n, chat_unmute).into_view(cx) } else { t!(i18n, chat_mute).into_view(cx) }}
+                </button>
+            </div>
+            {move || (!muted.get()).then(|| view! { cx,
+                <ul class="chat__messages">{render_messages}</ul>
+                <form class="chat__form" on:submit=on_submit>
+                    <input
+                        type="text"
+                        prop:value=input
+                        on:input=move |ev| set_input.set(event_target_value(&ev))
+                    />
+                    <button class="small_button" type="submit">{t!(i18n, chat_send)}</button>
+                </form>
+                <p class="chat__error">{error}</p>
+            })}
+        </div>
+    }
+}
@@ -1,7 +1,10 @@
 use crate::hooks::{use_scroll, ScrollDirection};
 
-use super::super::auth::{LoggedIn, NotLoggedIn};
+use super::super::auth::{IsLoggedIn, LoggedIn, NotLoggedIn};
+use super::super::notifications::notification_bell;
+use crate::hooks::use_toggle_interval;
 use crate::i18n::i18n_context;
+use crate::server::board::{get_my_games, MY_GAMES_REFRESH};
 use leptos::*;
 use leptos_i18n::t;
 
@@ -38,15 +41,81 @@ pub fn header(cx: Scope) -> impl IntoView {
 
 pub fn navigation_list(cx: Scope) -> impl IntoView {
     let i18n = i18n_context(cx);
+    let my_turn_count = use_my_turn_count(cx);
     view! { cx,
         <ol>
             <li class="link">
                 <a href="/about">{t!(i18n, about)}</a>
             </li>
+            <li class="link">
+                <a href="/analysis">{t!(i18n, analysis)}</a>
+            </li>
+            <li class="link">
+                <a href="/tournaments">{t!(i18n, tournaments)}</a>
+            </li>
+            <li class="link">
+                <a href="/simuls">{t!(i18n, simuls)}</a>
+            </li>
+            <LoggedIn>
+                <li class="link">
+                    <a href="/games">{t!(i18n, my_games)} {my_turn_badge(cx, my_turn_count)}</a>
+                </li>
+                <li class="link">
+                    <a href="/friends">{t!(i18n, friends)}</a>
+                </li>
+                <li class="link">
+                    <a href="/blocked">{t!(i18n, blocked_players)}</a>
+                </li>
+                <li class="link">
+                    {notification_bell(cx)}
+                </li>
+            </LoggedIn>
         </ol>
     }
 }
 
+/// Number of games waiting for the user's move, only polled while they are logged in.
+fn use_my_turn_count(cx: Scope) -> Resource<(u64, bool), usize> {
+    let is_logged_in = use_context::<IsLoggedIn>(cx)
+        .expect("the header is rendered inside the AuthentificationContext");
+    let is_logged_in = move || is_logged_in.0.read(cx).unwrap_or(false);
+
+    let (refresh, set_refresh) = create_signal(cx, 0);
+    use_toggle_interval(
+        cx,
+        move || is_logged_in().then_some(MY_GAMES_REFRESH),
+        move || set_refresh.update(|ticks| *ticks += 1),
+    );
+
+    create_resource(
+        cx,
+        move || (refresh.get(), is_logged_in()),
+        move |(_, is_logged_in)| async move {
+            if !is_logged_in {
+                return 0;
+            }
+            get_my_games(cx).await.map_or(0, |games| {
+                games.iter().filter(|game| game.is_my_turn()).count()
+            })
+        },
+    )
+}
+
+fn my_turn_badge(cx: Scope, my_turn_count: Resource<(u64, bool), usize>) -> impl IntoView {
+    let render = move || {
+        my_turn_count
+            .read(cx)
+            .filter(|count| *count > 0)
+            .map(|count| view! { cx, <span class="badge">{count}</span> })
+    };
+
+    view! { cx,
+        <Transition fallback=|| ()>
+            {render}
+        </Transition>
+    }
+}
+
 pub fn login_button(cx: Scope) -> impl IntoView {
     let location = leptos_router::use_location(cx);
 
@@ -1,14 +1,32 @@
 mod footer;
 mod header;
 
+use crate::components::notifications::provide_notification_stream;
+use crate::{i18n::i18n_context, utils::rate_limit::use_rate_limited};
 use leptos::*;
+use leptos_i18n::t;
+
+fn rate_limit_notice(cx: Scope) -> impl IntoView {
+    let i18n = i18n_context(cx);
+    let rate_limited = use_rate_limited(cx);
+    move || {
+        rate_limited.get().then(|| {
+            view! { cx,
+                <p class="rate_limit_notice">{t!(i18n, rate_limited)}</p>
+            }
+        })
+    }
+}
 
 #[component]
 pub fn Layout(cx: Scope, children: ChildrenFn) -> impl IntoView {
+    provide_notification_stream(cx);
+
     view! { cx,
         <>
             {header::header(cx)}
             <main>
+                {rate_limit_notice(cx)}
                 {children(cx)}
             </main>
             {footer::footer(cx)}
@@ -1,3 +1,6 @@
 pub mod auth;
 pub mod board;
+pub mod chat;
 pub mod layout;
+pub mod notifications;
+pub mod report;
@@ -0,0 +1,218 @@
+use crate::components::auth::IsLoggedIn;
+use crate::i18n::i18n_context;
+use crate::server::notifications::{
+    get_notifications, get_push_public_key, MarkNotificationsRead, NotificationKind,
+    NotificationSummary, SavePushSubscription,
+};
+use crate::utils::push::subscribe_to_push;
+use leptos::*;
+use leptos_i18n::t;
+
+/// Latest notification received on the notification stream of the user.
+#[derive(Debug, Clone, Copy)]
+pub struct NotificationStream(pub RwSignal<Option<NotificationSummary>>);
+
+/// Open the notification stream of the user once they are logged in, for the whole session.
+pub fn provide_notification_stream(cx: Scope) {
+    let latest = create_rw_signal(cx, None);
+    provide_context(cx, NotificationStream(latest));
+    subscribe_to_notifications(cx, latest);
+}
+
+#[cfg(all(feature = "hydrate", not(feature = "ssr")))]
+fn subscribe_to_notifications(cx: Scope, latest: RwSignal<Option<NotificationSummary>>) {
+    use crate::server::notifications::NotificationEvent;
+    use futures::StreamExt;
+    use gloo_net::eventsource::futures::EventSource;
+    use std::{cell::RefCell, rc::Rc};
+
+    let is_logged_in = use_context::<IsLoggedIn>(cx)
+        .expect("the notification stream is provided inside the AuthentificationContext");
+    let source = Rc::new(RefCell::new(None::<EventSource>));
+    let opened_source = source.clone();
+
+    create_effect(cx, move |_| {
+        let is_logged_in = is_logged_in.0.read(cx).unwrap_or(false);
+        if !is_logged_in || opened_source.borrow().is_some() {
+            return;
+        }
+        let Ok(mut new_source) = EventSource::new("/api/notifications/stream") else {
+            return;
+        };
+        let Ok(mut subscription) = new_source.subscribe("message") else {
+            return;
+        };
+        *opened_source.borrow_mut() = Some(new_source);
+        spawn_local(async move {
+            // errors are followed by the automatic reconnection of the source
+            while let Some(value) = subscription.next().await {
+                let Ok((_, event)) = value else {
+                    continue;
+                };
+                let Some(data) = event.data().as_string() else {
+                    continue;
+                };
+                if let Ok(NotificationEvent::Notification(notification)) =
+                    serde_json::from_str(&data)
+                {
+                    latest.set(Some(notification));
+                }
+            }
+        });
+    });
+
+    on_cleanup(cx, move || {
+        if let Some(source) = source.borrow_mut().take() {
+            source.close();
+        }
+    });
+}
+
+#[cfg(not(all(feature = "hydrate", not(feature = "ssr"))))]
+fn subscribe_to_notifications(_cx: Scope, _latest: RwSignal<Option<NotificationSummary>>) {}
+
+fn notification_text(cx: Scope, kind: NotificationKind) -> impl IntoView {
+    let i18n = i18n_context(cx);
+    match kind {
+        NotificationKind::OpponentMoved => t!(i18n, notification_opponent_moved).into_view(cx),
+        NotificationKind::CorrespondenceMove => {
+            t!(i18n, notification_correspondence_move).into_view(cx)
+        }
+        NotificationKind::Challenge => t!(i18n, notification_challenge).into_view(cx),
+        NotificationKind::GameEnded => t!(i18n, notification_game_ended).into_view(cx),
+    }
+}
+
+fn notification_item(cx: Scope, notification: NotificationSummary) -> impl IntoView {
+    let url = notification.kind.url(notification.game_id);
+    view! { cx,
+        <li class="notifications__item" class:notifications__item--unread=!notification.read>
+            <a href=url>{notification_text(cx, notification.kind)}</a>
+        </li>
+    }
+}
+
+/// Subscribe this browser to the Web Push messages, only shown when the server sends them.
+fn enable_push_button(cx: Scope) -> impl IntoView {
+    let i18n = i18n_context(cx);
+    let public_key = create_resource(
+        cx,
+        || (),
+        move |_| async move { get_push_public_key(cx).await.ok().flatten() },
+    );
+    let save_subscription = create_server_action::<SavePushSubscription>(cx);
+    let enable_push = create_action(cx, move |public_key: &String| {
+        let public_key = public_key.clone();
+        async move {
+            if let Some(subscription) = subscribe_to_push(&public_key).await {
+                save_subscription.dispatch(SavePushSubscription {
+                    endpoint: subscription.endpoint,
+                    p256dh: subscription.p256dh,
+                    auth: subscription.auth,
+                });
+            }
+        }
+    });
+    let enabled = move || {
+        save_subscription
+            .value()
+            .with(|res| matches!(res, Some(Ok(()))))
+    };
+
+    let render = move || {
+        let public_key = public_key.read(cx).flatten()?;
+        let button = move || {
+            if enabled() {
+                view! { cx, <p>{t!(i18n, push_enabled)}</p> }.into_view(cx)
+            } else {
+                let public_key = public_key.clone();
+                view! { cx,
+                    <button on:click=move |_| enable_push.dispatch(public_key.clone())>
+                        {t!(i18n, enable_push)}
+                    </button>
+                }
+                .into_view(cx)
+            }
+        };
+        Some(view! { cx, <div class="notifications__push">{button}</div> })
+    };
+
+    view! { cx,
+        <Transition fallback=|| ()>
+            {render}
+        </Transition>
+    }
+}
+
+/// Bell of the header with the number of unread notifications, opening the latest ones.
+pub fn notification_bell(cx: Scope) -> impl IntoView {
+    let i18n = i18n_context(cx);
+    let latest = use_context::<NotificationStream>(cx)
+        .expect("the notification stream is provided by the Layout")
+        .0;
+    let mark_read = create_server_action::<MarkNotificationsRead>(cx);
+    let centre = create_resource(
+        cx,
+        move || {
+            (
+                latest.with(|n| n.as_ref().map(|n| n.id)),
+                mark_read.version().get(),
+            )
+        },
+        move |_| async move { get_notifications(cx).await.ok() },
+    );
+    let (open, set_open) = create_signal(cx, false);
+
+    let toggle = move |_| {
+        let opening = !open.get();
+        set_open.set(opening);
+        let has_unread = centre.with(cx, |c| c.as_ref().map_or(false, |c| c.unread > 0));
+        if opening && has_unread == Some(true) {
+            mark_read.dispatch(MarkNotificationsRead {});
+        }
+    };
+
+    let badge = move || {
+        centre
+            .read(cx)
+            .flatten()
+            .filter(|centre| centre.unread > 0)
+            .map(|centre| view! { cx, <span class="badge">{centre.unread}</span> })
+    };
+
+    let list = move || {
+        let notifications = centre
+            .read(cx)
+            .flatten()
+            .map(|centre| centre.notifications)
+            .unwrap_or_default();
+        if notifications.is_empty() {
+            return view! { cx, <p>{t!(i18n, no_notifications)}</p> }.into_view(cx);
+        }
+        let items = notifications
+            .into_iter()
+            .map(|notification| notification_item(cx, notification))
+            .collect_view(cx);
+        view! { cx, <ol class="notifications__list">{items}</ol> }.into_view(cx)
+    };
+
+    view! { cx,
+        <div class="notifications">
+            <button class="notifications__bell" on:click=toggle>
+                "🔔"
+                <Transition fallback=|| ()>
+                    {badge}
+                </Transition>
+            </button>
+            <Show when=move || open.get() fallback=|_| ()>
+                <div class="notifications__dropdown">
+                    <h3>{t!(i18n, notifications)}</h3>
+                    <Transition fallback=|| ()>
+                        {list}
+                    </Transition>
+                    {enable_push_button(cx)}
+                </div>
+            </Show>
+        </div>
+    }
+}
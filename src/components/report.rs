@@ -0,0 +1,114 @@
+use leptos::*;
+use leptos_i18n::t;
+use uuid::Uuid;
+
+use crate::{
+    i18n::i18n_context,
+    server::moderation::{BlockOpponent, ReportPlayer, ReportReason, MAX_REPORT_COMMENT_LEN},
+};
+
+fn reason_label(cx: Scope, reason: ReportReason) -> impl IntoView {
+    let i18n = i18n_context(cx);
+    match reason {
+        ReportReason::Cheating => t!(i18n, report_cheating).into_view(cx),
+        ReportReason::Harassment => t!(i18n, report_harassment).into_view(cx),
+        ReportReason::OffensiveUsername => t!(i18n, report_offensive_username).into_view(cx),
+        ReportReason::Other => t!(i18n, report_other).into_view(cx),
+    }
+}
+
+/// Report and block buttons shown once a game is over.
+#[component]
+pub fn ReportPanel(cx: Scope, game_id: Uuid) -> impl IntoView {
+    let (open, set_open) = create_signal(cx, false);
+    let (reason, set_reason) = create_signal(cx, ReportReason::Cheating);
+    let (comment, set_comment) = create_signal(cx, String::new());
+    let (block, set_block) = create_signal(cx, false);
+
+    let report_player = create_server_action::<ReportPlayer>(cx);
+    let block_opponent = create_server_action::<BlockOpponent>(cx);
+
+    let i18n = i18n_context(cx);
+
+    let is_reported = move || matches!(report_player.value().get(), Some(Ok(())));
+    let is_blocked = move || {
+        matches!(block_opponent.value().get(), Some(Ok(()))) || (is_reported() && block.get())
+    };
+
+    let on_submit = move |ev: ev::SubmitEvent| {
+        ev.prevent_default();
+        let comment = comment.get_untracked();
+        report_player.dispatch(ReportPlayer {
+            game_id,
+            reason: reason.get_untracked(),
+            comment: (!comment.trim().is_empty()).then_some(comment),
+            block: block.get_untracked(),
+        });
+    };
+
+    let on_reason_change = move |ev| {
+        let value = event_target_value(&ev);
+        if let Some(reason) = ReportReason::ALL
+            .into_iter()
+            .find(|reason| reason.as_str() == value)
+        {
+            set_reason.set(reason);
+        }
+    };
+
+    let options = move || {
+        ReportReason::ALL
+            .into_iter()
+            .map(|reason| {
+                view! { cx,
+                    <option value=reason.as_str()>{reason_label(cx, reason)}</option>
+                }
+            })
+            .collect_view(cx)
+    };
+
+    let form = move || {
+        (open.get() && !is_reported()).then(|| {
+            view! { cx,
+                <form class="report__form" on:submit=on_submit>
+                    <select on:change=on_reason_change>{options()}</select>
+                    <p>{t!(i18n, report_comment)}</p>
+                    <textarea
+                        maxlength=MAX_REPORT_COMMENT_LEN
+                        prop:value=comment
+                        on:input=move |ev| set_comment.set(event_target_value(&ev))
+                    />
+                    <label>
+                        <input
+                            type="checkbox"
+                            prop:checked=block
+                            on:change=move |ev| set_block.set(event_target_checked(&ev))
+                        />
+                        {t!(i18n, report_also_block)}
+                    </label>
+                    <button class="small_button" type="submit">{t!(i18n, report_send)}</button>
+                </form>
+            }
+        })
+    };
+
+    view! { cx,
+        <div class="report">
+            <div class="report__buttons">
+                {move || (!is_reported()).then(|| view! { cx,
+                    <button class="small_button" on:click=move |_| set_open.update(|open| *open = !*open)>
+                        {t!(i18n, report_player)}
+                    </button>
+                })}
+                {move || (!is_blocked()).then(|| view! { cx,
+                    <button class="small_button" on:click=move |_| block_opponent.dispatch(BlockOpponent { game_id })>
+                        {t!(i18n, block_player)}
+                    </button>
+                })}
+            </div>
+            {form}
+            {move || is_reported().then(|| view! { cx, <p>{t!(i18n, report_sent)}</p> })}
+            {move || is_blocked().then(|| view! { cx, <p>{t!(i18n, player_blocked)}</p> })}
+        </div>
+    }
+}
@@ -1,5 +1,7 @@
+mod use_interval;
 mod use_scroll;
 
+pub use use_interval::*;
 pub use use_scroll::*;
 
 #[cfg(feature = "hydrate")]
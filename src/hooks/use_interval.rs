@@ -0,0 +1,53 @@
+use leptos::*;
+use std::time::Duration;
+
+/// A counter incremented every `period`, to refetch resources that are not pushed by the server.
+#[cfg(feature = "hydrate")]
+pub fn use_interval(cx: Scope, period: Duration) -> ReadSignal<u64> {
+    let (ticks, set_ticks) = create_signal(cx, 0);
+
+    if let Ok(handle) =
+        set_interval_with_handle(move || set_ticks.update(|ticks| *ticks += 1), period)
+    {
+        on_cleanup(cx, move || handle.clear());
+    }
+
+    ticks
+}
+
+#[cfg(not(feature = "hydrate"))]
+pub fn use_interval(cx: Scope, _period: Duration) -> ReadSignal<u64> {
+    create_signal(cx, 0).0
+}
+
+/// Call `callback` every `period` while it is `Some`, the timer restarts when it changes.
+#[cfg(feature = "hydrate")]
+pub fn use_toggle_interval(
+    cx: Scope,
+    period: impl Fn() -> Option<Duration> + 'static,
+    callback: impl Fn() + Clone + 'static,
+) {
+    let handle = store_value(cx, None::<IntervalHandle>);
+
+    create_effect(cx, move |_| {
+        if let Some(handle) = handle.get_value() {
+            handle.clear();
+        }
+        let new_handle =
+            period().and_then(|period| set_interval_with_handle(callback.clone(), period).ok());
+        handle.set_value(new_handle);
+    });
+    on_cleanup(cx, move || {
+        if let Some(handle) = handle.get_value() {
+            handle.clear();
+        }
+    });
+}
+
+#[cfg(not(feature = "hydrate"))]
+pub fn use_toggle_interval(
+    _cx: Scope,
+    _period: impl Fn() -> Option<Duration> + 'static,
+    _callback: impl Fn() + Clone + 'static,
+) {
+}
@@ -13,25 +13,90 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     dotenvy::dotenv().ok();
 
-    let pool = db::create_db_pool().await?;
+    let config = match config::Config::load() {
+        Ok(config) => config,
+        Err(err) => {
+            eprintln!("Invalid configuration: {}", err);
+            std::process::exit(1);
+        }
+    };
+
+    let log_level_handle = web::Data::new(telemetry::init(&config.logging)?);
+
+    let pool = db::create_db_pool(&config.database).await?;
+
+    if config.database.run_migrations {
+        db::migrations::run(&pool).await?;
+    }
+    if let Err(err) = db::migrations::check_schema(&pool).await {
+        tracing::error!("{}", err);
+        std::process::exit(1);
+    }
 
     let conf = get_configuration(None).await.unwrap();
     let addr = conf.leptos_options.site_addr;
     // Generate the list of routes in your Leptos App
     let routes = generate_route_list(|cx| view! { cx, <App/> });
 
-    let auth_client = auth::auth_client::AuthClient::new().unwrap();
+    let auth_client = auth::auth_client::AuthClient::new(&config.auth)?;
     let auth_client = web::Data::new(auth_client);
 
     let secret_key = get_secret_key();
 
-    let base_url = web::Data::new(BaseUrl::new());
+    let base_url = web::Data::new(BaseUrl::new(&config.server));
+
+    let metrics = web::Data::new(metrics::Metrics::new()?);
+
+    let games = web::Data::new(board::server::Games::new(
+        config.games.clone(),
+        config.chat.clone(),
+        metrics.clone(),
+    ));
+    games.restore(&pool).await?;
+    board::server::spawn_stale_games_sweep(games.clone(), pool.clone());
+
+    let notifier = web::Data::new(notifications::server::Notifier::new(
+        config.notifications.clone(),
+        notifications::push::WebPush::new(&config.notifications)?,
+        pool.clone(),
+        config.games.ping_timeout(),
+        metrics.clone(),
+    ));
+    notifications::server::spawn_prune(notifier.clone(), config.games.stale_sweep_interval());
+    board::server::spawn_correspondence_timeouts(games.clone(), pool.clone(), notifier.clone());
+
+    let tournaments = web::Data::new(tournaments::server::Tournaments::new(
+        config.tournaments.clone(),
+    ));
+    tournaments::server::spawn_tournament_ticks(tournaments.clone(), games.clone(), pool.clone());
+
+    let simuls = web::Data::new(simul::server::Simuls::new(config.simuls.clone()));
+    simul::server::spawn_simul_ticks(simuls.clone(), games.clone(), pool.clone());
+
+    let rate_limiter = web::Data::new(rate_limit::RateLimiter::new(&config.rate_limit));
+    rate_limit::spawn_prune(rate_limiter.clone());
+
+    let shutdown_timeout = config.server.shutdown_timeout_secs;
+    let shutdown_games = games.clone();
+    let shutdown_pool = pool.clone();
+
+    let config = web::Data::new(config);
     let leptos_options = web::Data::new(conf.leptos_options);
 
-    HttpServer::new(move || {
+    tracing::info!(%addr, "starting server");
+
+    let server = HttpServer::new(move || {
         App::new()
             .service(web::scope("/api/board").configure(board::server::config))
             .service(web::scope("/api/auth").configure(auth::config))
+            .service(web::scope("/api/telemetry").configure(telemetry::config))
+            .service(web::scope("/api/admin").configure(admin_api::config))
+            .service(web::scope("/api/notifications").configure(notifications::server::config))
+            .service(web::scope("/api/tournaments").configure(tournaments::server::config))
+            .service(web::scope("/api/simuls").configure(simul::server::config))
+            .configure(metrics::config)
+            .configure(health::config)
+            .configure(rate_limit::config)
             .route("/api/{tail:.*}", leptos_actix::handle_server_fns())
             // serve JS/WASM/CSS from `pkg`
             .service(Files::new(
@@ -43,6 +108,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             // serve the favicon from /favicon.ico
             .service(favicon)
             .service(manifest)
+            .service(notifications_service_worker)
             .leptos_routes(
                 leptos_options.get_ref().clone(),
                 routes.clone(),
@@ -52,19 +118,62 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             .app_data(web::Data::clone(&pool))
             .app_data(auth_client.clone())
             .app_data(base_url.clone())
+            .app_data(games.clone())
+            .app_data(notifier.clone())
+            .app_data(tournaments.clone())
+            .app_data(simuls.clone())
+            .app_data(metrics.clone())
+            .app_data(config.clone())
+            .app_data(log_level_handle.clone())
+            .app_data(rate_limiter.clone())
+            .wrap(actix_web_lab::middleware::from_fn(rate_limit::rate_limit))
             .wrap(actix_session::SessionMiddleware::new(
                 actix_session::storage::CookieSessionStore::default(),
                 secret_key.clone(),
             ))
+            .wrap(tracing_actix_web::TracingLogger::default())
         //.wrap(middleware::Compress::default())
     })
     .bind(&addr)?
-    .run()
-    .await?;
+    .shutdown_timeout(shutdown_timeout)
+    .disable_signals()
+    .run();
+
+    let server_handle = server.handle();
+    actix_web::rt::spawn(async move {
+        wait_for_shutdown_signal().await;
+        tracing::info!("shutdown signal received, stopping server");
+        if let Err(err) = shutdown_games.shutdown(&shutdown_pool).await {
+            tracing::error!(error = %err, "could not save games before shutdown");
+        }
+        server_handle.stop(true).await;
+    });
+
+    server.await?;
 
     Ok(())
 }
 
+#[cfg(feature = "ssr")]
+async fn wait_for_shutdown_signal() {
+    use futures::future::select;
+
+    let ctrl_c = Box::pin(actix_web::rt::signal::ctrl_c());
+
+    #[cfg(unix)]
+    {
+        use actix_web::rt::signal::unix::{signal, SignalKind};
+        let mut terminate = signal(SignalKind::terminate()).expect("failed to listen for SIGTERM");
+        let terminate = Box::pin(terminate.recv());
+        let _ = select(ctrl_c, terminate).await;
+    }
+
+    #[cfg(not(unix))]
+    {
+        let _ = ctrl_c.await;
+    }
+}
+
 #[cfg(feature = "ssr")]
 #[actix_web::get("favicon.ico")]
 async fn favicon(
@@ -87,6 +196,18 @@ async fn manifest(
         "{site_root}/manifest.json"
     ))?)
 }
+// served from the root, a service worker only controls the pages under its own path
+#[cfg(feature = "ssr")]
+#[actix_web::get("notifications-sw.js")]
+async fn notifications_service_worker(
+    leptos_options: actix_web::web::Data<leptos::LeptosOptions>,
+) -> actix_web::Result<actix_files::NamedFile> {
+    let leptos_options = leptos_options.into_inner();
+    let site_root = &leptos_options.site_root;
+    Ok(actix_files::NamedFile::open(format!(
+        "{site_root}/notifications-sw.js"
+    ))?)
+}
 
 #[cfg(not(any(feature = "ssr", feature = "csr")))]
 pub fn main() {
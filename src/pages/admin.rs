@@ -0,0 +1,241 @@
+use crate::components::auth::CheckLoggedIn;
+use crate::i18n::i18n_context;
+use crate::server::admin::{
+    get_admin_overview, AdminOverview, AuditSummary, BanUser, ReportResolution, ReportSummary,
+    ResetUsername, TerminateGame, TriageReport,
+};
+use crate::server::board::GameSummary;
+use leptos::*;
+use leptos_i18n::t;
+use leptos_meta::*;
+
+#[derive(Clone, Copy)]
+struct AdminActions {
+    terminate_game: Action<TerminateGame, Result<(), ServerFnError>>,
+    ban_user: Action<BanUser, Result<(), ServerFnError>>,
+    reset_username: Action<ResetUsername, Result<String, ServerFnError>>,
+    triage_report: Action<TriageReport, Result<(), ServerFnError>>,
+}
+
+fn render_games(cx: Scope, games: Vec<GameSummary>, actions: AdminActions) -> impl IntoView {
+    let i18n = i18n_context(cx);
+
+    let rows = games
+        .into_iter()
+        .map(|game| {
+            let terminate = game.game_id.map(|game_id| {
+                view! { cx,
+                    <button
+                        class="small_button"
+                        on:click=move |_| actions.terminate_game.dispatch(TerminateGame { game_id })
+                    >
+                        {t!(i18n, admin_terminate)}
+                    </button>
+                }
+            });
+            view! { cx,
+                <tr>
+                    <td>{game.game_id.map(|id| id.to_string()).unwrap_or_default()}</td>
+                    <td>{format!("{:?}", game.kind)}</td>
+                    <td>{game.players.join(", ")}</td>
+                    <td>{game.player_turn.map(|color| format!("{:?}", color)).unwrap_or_default()}</td>
+                    <td>{game.spectators}</td>
+                    <td>{terminate}</td>
+                </tr>
+            }
+        })
+        .collect_view(cx);
+
+    view! { cx,
+        <h2>{t!(i18n, admin_games)}</h2>
+        <table class="admin_table">
+            <tr>
+                <th>{t!(i18n, admin_game)}</th>
+                <th>{t!(i18n, admin_kind)}</th>
+                <th>{t!(i18n, admin_players)}</th>
+                <th>{t!(i18n, admin_turn)}</th>
+                <th>{t!(i18n, admin_spectators)}</th>
+                <th></th>
+            </tr>
+            {rows}
+        </table>
+    }
+}
+
+fn render_user_actions(cx: Scope, user_id: String, actions: AdminActions) -> impl IntoView {
+    let i18n = i18n_context(cx);
+    let ban_id = user_id.clone();
+
+    view! { cx,
+        <button
+            class="small_button"
+            on:click=move |_| actions.ban_user.dispatch(BanUser { user_id: ban_id.clone() })
+        >
+            {t!(i18n, admin_ban)}
+        </button>
+        <button
+            class="small_button"
+            on:click=move |_| actions.reset_username.dispatch(ResetUsername { user_id: user_id.clone() })
+        >
+            {t!(i18n, admin_reset_username)}
+        </button>
+    }
+}
+
+fn render_players(cx: Scope, players: Vec<String>, actions: AdminActions) -> impl IntoView {
+    let i18n = i18n_context(cx);
+
+    let rows = players
+        .into_iter()
+        .map(|player| {
+            view! { cx,
+                <tr>
+                    <td>{player.clone()}</td>
+                    <td>{render_user_actions(cx, player, actions)}</td>
+                </tr>
+            }
+        })
+        .collect_view(cx);
+
+    view! { cx,
+        <h2>{t!(i18n, admin_active_players)}</h2>
+        <table class="admin_table">{rows}</table>
+    }
+}
+
+fn render_reports(cx: Scope, reports: Vec<ReportSummary>, actions: AdminActions) -> impl IntoView {
+    let i18n = i18n_context(cx);
+
+    let rows = reports
+        .into_iter()
+        .map(|report| {
+            let report_id = report.id;
+            let triage = move |resolution| {
+                actions.triage_report.dispatch(TriageReport {
+                    report_id,
+                    resolution,
+                })
+            };
+            view! { cx,
+                <tr>
+                    <td>{report.created_at}</td>
+                    <td>{report.reporter_id}</td>
+                    <td>{report.reported_id.clone()}</td>
+                    <td>{report.reason}</td>
+                    <td>{report.comment.unwrap_or_default()}</td>
+                    <td>
+                        {render_user_actions(cx, report.reported_id, actions)}
+                        <button class="small_button" on:click=move |_| triage(ReportResolution::Resolved)>
+                            {t!(i18n, admin_resolve)}
+                        </button>
+                        <button class="small_button" on:click=move |_| triage(ReportResolution::Dismissed)>
+                            {t!(i18n, admin_dismiss)}
+                        </button>
+                    </td>
+                </tr>
+            }
+        })
+        .collect_view(cx);
+
+    view! { cx,
+        <h2>{t!(i18n, admin_reports)}</h2>
+        <table class="admin_table">
+            <tr>
+                <th>{t!(i18n, admin_date)}</th>
+                <th>{t!(i18n, admin_reporter)}</th>
+                <th>{t!(i18n, admin_reported)}</th>
+                <th>{t!(i18n, admin_reason)}</th>
+                <th>{t!(i18n, admin_comment)}</th>
+                <th></th>
+            </tr>
+            {rows}
+        </table>
+    }
+}
+
+fn render_audit_log(cx: Scope, entries: Vec<AuditSummary>) -> impl IntoView {
+    let i18n = i18n_context(cx);
+
+    let rows = entries
+        .into_iter()
+        .map(|entry| {
+            view! { cx,
+                <tr>
+                    <td>{entry.created_at}</td>
+                    <td>{entry.admin_user_id}</td>
+                    <td>{entry.action}</td>
+                    <td>{entry.target}</td>
+                    <td>{entry.details.unwrap_or_default()}</td>
+                </tr>
+            }
+        })
+        .collect_view(cx);
+
+    view! { cx,
+        <h2>{t!(i18n, admin_audit_log)}</h2>
+        <table class="admin_table">
+            <tr>
+                <th>{t!(i18n, admin_date)}</th>
+                <th>{t!(i18n, admin_admin)}</th>
+                <th>{t!(i18n, admin_action)}</th>
+                <th>{t!(i18n, admin_target)}</th>
+                <th>{t!(i18n, admin_details)}</th>
+            </tr>
+            {rows}
+        </table>
+    }
+}
+
+fn render_overview(cx: Scope, overview: AdminOverview, actions: AdminActions) -> impl IntoView {
+    view! { cx,
+        {render_games(cx, overview.games, actions)}
+        {render_players(cx, overview.active_players, actions)}
+        {render_reports(cx, overview.reports, actions)}
+        {render_audit_log(cx, overview.audit_log)}
+    }
+}
+
+/// Dashboard listing live games, active players and recent reports, restricted to admins.
+#[component]
+pub fn AdminDashboard(cx: Scope) -> impl IntoView {
+    let i18n = i18n_context(cx);
+
+    let actions = AdminActions {
+        terminate_game: create_server_action::<TerminateGame>(cx),
+        ban_user: create_server_action::<BanUser>(cx),
+        reset_username: create_server_action::<ResetUsername>(cx),
+        triage_report: create_server_action::<TriageReport>(cx),
+    };
+
+    let overview = create_resource(
+        cx,
+        move || {
+            (
+                actions.terminate_game.version().get(),
+                actions.ban_user.version().get(),
+                actions.reset_username.version().get(),
+                actions.triage_report.version().get(),
+            )
+        },
+        move |_| get_admin_overview(cx),
+    );
+
+    let render = move || {
+        overview.read(cx).map(|overview| match overview {
+            Ok(overview) => render_overview(cx, overview, actions).into_view(cx),
+            Err(_) => view! { cx, <h1>{t!(i18n, admin_forbidden)}</h1> }.into_view(cx),
+        })
+    };
+
+    view! { cx,
+        <Title text="Hex Chess | Admin"/>
+        <CheckLoggedIn>
+            <h1 class="title">{t!(i18n, admin_title)}</h1>
+            <div class="admin_dashboard">
+                <Suspense fallback=|| ()>
+                    {render}
+                </Suspense>
+            </div>
+        </CheckLoggedIn>
+    }
+}
@@ -0,0 +1,41 @@
+use crate::components::board::AnalysisBoard;
+use crate::i18n::i18n_context;
+use crate::server::board::get_game_replay;
+use crate::utils::variations::game_moves;
+use leptos::*;
+use leptos_i18n::t;
+use leptos_meta::*;
+use leptos_router::*;
+use uuid::Uuid;
+
+/// Analysis from the starting position, or from the moves of the archived game in the url.
+#[component]
+pub fn Analysis(cx: Scope) -> impl IntoView {
+    let params = use_params_map(cx);
+    let i18n = i18n_context(cx);
+
+    let game_id = move || params.with(|params| params.get("id").cloned());
+    let moves = create_resource(cx, game_id, move |game_id| async move {
+        let Some(game_id) = game_id else {
+            return Some(vec![]);
+        };
+        let game_id = game_id.parse::<Uuid>().ok()?;
+        let replay = get_game_replay(cx, game_id).await.ok().flatten()?;
+        Some(game_moves(&replay.board))
+    });
+
+    let render = move || {
+        moves.read(cx).map(|moves| match moves {
+            Some(moves) => view! { cx, <AnalysisBoard moves=moves/> }.into_view(cx),
+            None => view! { cx, <p>{t!(i18n, replay_not_found)}</p> }.into_view(cx),
+        })
+    };
+
+    view! { cx,
+        <Title text="Hex Chess | Analysis"/>
+        <h1 class="title">{t!(i18n, analysis)}</h1>
+        <Suspense fallback=|| ()>
+            {render}
+        </Suspense>
+    }
+}
@@ -0,0 +1,57 @@
+use crate::components::auth::CheckLoggedIn;
+use crate::i18n::i18n_context;
+use crate::server::moderation::{get_blocked_users, BlockedUserSummary, UnblockUser};
+use leptos::*;
+use leptos_i18n::t;
+use leptos_meta::*;
+
+/// List of the players blocked by the user, they are never paired with them.
+#[component]
+pub fn BlockedPlayers(cx: Scope) -> impl IntoView {
+    let i18n = i18n_context(cx);
+
+    let unblock_user = create_server_action::<UnblockUser>(cx);
+
+    let blocked_users = create_resource(
+        cx,
+        move || unblock_user.version().get(),
+        move |_| get_blocked_users(cx),
+    );
+
+    let render_user = move |user: BlockedUserSummary| {
+        let id = user.id;
+        view! { cx,
+            <li>
+                <span>{user.username}</span>
+                <button class="small_button" on:click=move |_| unblock_user.dispatch(UnblockUser { id })>
+                    {t!(i18n, unblock)}
+                </button>
+            </li>
+        }
+    };
+
+    let render = move || {
+        blocked_users.read(cx).map(|users| match users {
+            Ok(users) if users.is_empty() => {
+                view! { cx, <p>{t!(i18n, no_blocked_players)}</p> }.into_view(cx)
+            }
+            Ok(users) => view! { cx,
+                <ul class="blocked_players">
+                    {users.into_iter().map(render_user).collect_view(cx)}
+                </ul>
+            }
+            .into_view(cx),
+            Err(_) => ().into_view(cx),
+        })
+    };
+
+    view! { cx,
+        <Title text="Hex Chess | Blocked players"/>
+        <CheckLoggedIn>
+            <h1 class="title">{t!(i18n, blocked_players)}</h1>
+            <Suspense fallback=|| ()>
+                {render}
+            </Suspense>
+        </CheckLoggedIn>
+    }
+}
@@ -0,0 +1,119 @@
+use crate::components::auth::CheckLoggedIn;
+use crate::hooks::use_interval;
+use crate::i18n::i18n_context;
+use crate::server::profile::{get_friends, FindPlayer, Friend, Presence, FRIENDS_REFRESH};
+use leptos::*;
+use leptos_i18n::t;
+use leptos_meta::*;
+
+fn presence_text(cx: Scope, presence: Presence) -> impl IntoView {
+    let i18n = i18n_context(cx);
+    match presence {
+        Presence::Offline => t!(i18n, offline).into_view(cx),
+        Presence::Online => t!(i18n, online).into_view(cx),
+        Presence::Playing(_) => t!(i18n, playing).into_view(cx),
+    }
+}
+
+/// Look a player up by username and go to their profile.
+fn find_player_form(cx: Scope) -> impl IntoView {
+    let i18n = i18n_context(cx);
+    let find_player = create_server_action::<FindPlayer>(cx);
+    let (username, set_username) = create_signal(cx, String::new());
+    let navigate = leptos_router::use_navigate(cx);
+
+    create_effect(cx, move |_| {
+        if let Some(Ok(Some(id))) = find_player.value().get() {
+            let _ = navigate(&format!("/player/{}", id), Default::default());
+        }
+    });
+
+    let not_found = move || {
+        matches!(find_player.value().get(), Some(Ok(None)))
+            .then(|| view! { cx, <p>{t!(i18n, player_not_found)}</p> })
+    };
+
+    let on_submit = move |ev: ev::SubmitEvent| {
+        ev.prevent_default();
+        let username = username.get_untracked();
+        if !username.trim().is_empty() {
+            find_player.dispatch(FindPlayer { username });
+        }
+    };
+
+    view! { cx,
+        <form class="friends__search" on:submit=on_submit>
+            <input
+                type="text"
+                prop:value=username
+                on:input=move |ev| set_username.set(event_target_value(&ev))
+            />
+            <button type="submit" class="small_button">{t!(i18n, find_player)}</button>
+        </form>
+        {not_found}
+    }
+}
+
+/// Followed players and what they are doing, with shortcuts to spectate or challenge them.
+#[component]
+pub fn Friends(cx: Scope) -> impl IntoView {
+    let i18n = i18n_context(cx);
+
+    let refresh = use_interval(cx, FRIENDS_REFRESH);
+    let friends = create_resource(cx, move || refresh.get(), move |_| get_friends(cx));
+
+    let render_friend = move |friend: Friend| {
+        let action = match friend.presence {
+            Presence::Playing(game_id) => view! { cx,
+                <a href=format!("/play/{}", game_id) class="small_button">{t!(i18n, spectate)}</a>
+            },
+            _ => view! { cx,
+                <a href=format!("/play/challenge/{}", friend.id) class="small_button">
+                    {t!(i18n, challenge)}
+                </a>
+            },
+        };
+        let presence_class = match friend.presence {
+            Presence::Offline => "friends__presence",
+            Presence::Online => "friends__presence friends__presence--online",
+            Presence::Playing(_) => "friends__presence friends__presence--playing",
+        };
+        view! { cx,
+            <li>
+                <span class=presence_class></span>
+                <a href=format!("/player/{}", friend.id) class="friends__name">{friend.username}</a>
+                {friend.is_mutual.then(|| view! { cx,
+                    <span class="friends__mutual">{t!(i18n, friend)}</span>
+                })}
+                <span class="friends__status">{presence_text(cx, friend.presence)}</span>
+                {action}
+            </li>
+        }
+    };
+
+    let render = move || {
+        friends.read(cx).map(|friends| match friends {
+            Ok(friends) if friends.is_empty() => {
+                view! { cx, <p>{t!(i18n, no_friends)}</p> }.into_view(cx)
+            }
+            Ok(friends) => view! { cx,
+                <ul class="friends">
+                    {friends.into_iter().map(render_friend).collect_view(cx)}
+                </ul>
+            }
+            .into_view(cx),
+            Err(_) => ().into_view(cx),
+        })
+    };
+
+    view! { cx,
+        <Title text="Hex Chess | Friends"/>
+        <CheckLoggedIn>
+            <h1 class="title">{t!(i18n, friends)}</h1>
+            {find_player_form(cx)}
+            <Transition fallback=|| ()>
+                {render}
+            </Transition>
+        </CheckLoggedIn>
+    }
+}
@@ -0,0 +1,67 @@
+use crate::components::auth::CheckLoggedIn;
+use crate::hooks::use_interval;
+use crate::i18n::i18n_context;
+use crate::server::board::{get_my_games, PlayerGame, MY_GAMES_REFRESH};
+use hex_chess_core::piece::Color;
+use leptos::*;
+use leptos_i18n::t;
+use leptos_meta::*;
+
+/// The ongoing games of the user, with whose turn it is in each of them.
+#[component]
+pub fn MyGames(cx: Scope) -> impl IntoView {
+    let i18n = i18n_context(cx);
+
+    let refresh = use_interval(cx, MY_GAMES_REFRESH);
+    let games = create_resource(cx, move || refresh.get(), move |_| get_my_games(cx));
+
+    let render_game = move |game: PlayerGame| {
+        let color = match game.color {
+            Color::White => t!(i18n, color_white).into_view(cx),
+            Color::Black => t!(i18n, color_black).into_view(cx),
+        };
+        let status = if game.is_ended {
+            t!(i18n, game_over).into_view(cx)
+        } else if game.is_my_turn() {
+            t!(i18n, your_turn).into_view(cx)
+        } else {
+            t!(i18n, opponent_turn).into_view(cx)
+        };
+        view! { cx,
+            <li class=("my_games__game--my_turn", game.is_my_turn())>
+                <a href=format!("/play/{}", game.game_id)>
+                    <span class="my_games__color">{color}</span>
+                    {game.days_per_move.map(|days| view! { cx,
+                        <span class="my_games__days">{days} " " {t!(i18n, days_per_move_short)}</span>
+                    })}
+                    <span>{status}</span>
+                </a>
+            </li>
+        }
+    };
+
+    let render = move || {
+        games.read(cx).map(|games| match games {
+            Ok(games) if games.is_empty() => {
+                view! { cx, <p>{t!(i18n, no_games)}</p> }.into_view(cx)
+            }
+            Ok(games) => view! { cx,
+                <ul class="my_games">
+                    {games.into_iter().map(render_game).collect_view(cx)}
+                </ul>
+            }
+            .into_view(cx),
+            Err(_) => ().into_view(cx),
+        })
+    };
+
+    view! { cx,
+        <Title text="Hex Chess | My games"/>
+        <CheckLoggedIn>
+            <h1 class="title">{t!(i18n, my_games)}</h1>
+            <Transition fallback=|| ()>
+                {render}
+            </Transition>
+        </CheckLoggedIn>
+    }
+}
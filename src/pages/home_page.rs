@@ -34,6 +34,9 @@ pub fn HomePage(cx: Scope) -> impl IntoView {
                 <div on:click=move |_| set_redirect.set(Some("custom")) class="big_button">
                     <p>{t!(i18n, custom)}</p>
                 </div>
+                <div on:click=move |_| set_redirect.set(Some("correspondence")) class="big_button">
+                    <p>{t!(i18n, correspondence)}</p>
+                </div>
             </div>
         </LoggedIn>
     }
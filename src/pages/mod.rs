@@ -1,14 +1,36 @@
+mod admin;
+mod analysis;
+mod blocked;
+mod friends;
+mod games;
 mod home_page;
 mod not_found;
 pub mod play;
+mod profile;
+mod replay;
+mod simul;
+pub mod tournaments;
 
 use crate::i18n::Locales;
+use crate::utils::rate_limit::provide_rate_limit_context;
+use admin::AdminDashboard;
+use analysis::Analysis;
+use blocked::BlockedPlayers;
+use friends::Friends;
+use games::MyGames;
 use home_page::HomePage;
 use leptos::*;
 use leptos_meta::*;
 use leptos_router::*;
 use not_found::NotFound;
-use play::{Play, WaitingCustom, WaitingCustomWithId, WaitingRandom};
+use play::{
+    ChallengeInvite, Play, WaitingChallenge, WaitingCorrespondence, WaitingCustom,
+    WaitingCustomWithId, WaitingRandom,
+};
+use profile::Profile;
+use replay::Replay;
+use simul::{Simul, Simuls};
+use tournaments::{Tournament, TournamentBracket, Tournaments};
 
 use crate::components::auth::AuthentificationContext;
 use crate::components::layout::Layout;
@@ -19,6 +41,7 @@ pub fn App(cx: Scope) -> impl IntoView {
     provide_meta_context(cx);
 
     leptos_i18n::provide_i18n_context::<Locales>(cx);
+    provide_rate_limit_context(cx);
 
     view! { cx,
         // injects a stylesheet into the document <head>
@@ -33,8 +56,24 @@ pub fn App(cx: Scope) -> impl IntoView {
                             <Route path="/" view=HomePage />
                             <Route path="/play/random" view=WaitingRandom/>
                             <Route path="/play/custom" view=WaitingCustom/>
+                            <Route path="/play/correspondence" view=WaitingCorrespondence/>
                             <Route path="/play/custom/:game_id" view=WaitingCustomWithId/>
+                            <Route path="/play/challenge/:id" view=WaitingChallenge/>
+                            <Route path="/challenge/:game_id" view=ChallengeInvite/>
+                            <Route path="/player/:id" view=Profile/>
                             <Route path="/play/:game_id" view=Play/>
+                            <Route path="/game/:id" view=Replay/>
+                            <Route path="/analysis" view=Analysis/>
+                            <Route path="/analysis/:id" view=Analysis/>
+                            <Route path="/admin" view=AdminDashboard/>
+                            <Route path="/blocked" view=BlockedPlayers/>
+                            <Route path="/games" view=MyGames/>
+                            <Route path="/friends" view=Friends/>
+                            <Route path="/tournaments" view=Tournaments/>
+                            <Route path="/tournament/:id" view=Tournament/>
+                            <Route path="/tournament/:id/bracket" view=TournamentBracket/>
+                            <Route path="/simuls" view=Simuls/>
+                            <Route path="/simul/:id" view=Simul/>
                             <Route path="/*any" view=NotFound />
                         </Routes>
                     </Layout>
@@ -1,36 +1,49 @@
 use crate::components::auth::CheckLoggedIn;
 use crate::components::board::MultiBoard;
+use crate::components::chat::GameChat;
 use crate::i18n::i18n_context;
-use crate::server::board::GameEvent;
+use crate::server::board::{get_challenge, DeclineChallenge, GameEvent};
+use crate::utils::rate_limit::check_rate_limit;
 use leptos::*;
 use leptos_i18n::t;
 use leptos_meta::*;
 use leptos_router::*;
+use std::collections::HashMap;
+use uuid::Uuid;
 
 #[component]
 pub fn WaitingRandom(cx: Scope) -> impl IntoView {
     let navigate = leptos_router::use_navigate(cx);
     let events = GameEventStream::new(cx, &GameEventKind::Random);
     let i18n = i18n_context(cx);
+    let (expired, set_expired) = create_signal(cx, false);
 
-    events.listen(cx, move |event| {
-        if let GameEvent::GameStart { game_id, .. } = event {
+    events.listen(cx, move |event| match event {
+        GameEvent::GameStart { game_id, .. } => {
             navigate(&format!("/play/{}", game_id), Default::default()).unwrap();
         }
+        GameEvent::InvitationExpired => set_expired.set(true),
+        _ => {}
     });
 
     view! { cx,
         <Title text="Hex Chess | Waiting for opponent..."/>
         <CheckLoggedIn>
-            <p>{t!(i18n, waiting)}</p>
+            <Show
+                when=move || expired.get()
+                fallback=move |cx| view! { cx, <p>{t!(i18n, waiting)}</p> }
+            >
+                <p>{t!(i18n, no_opponent_found)}</p>
+                <a href="/" class="big_button">{t!(i18n, back_home)}</a>
+            </Show>
         </CheckLoggedIn>
     }
 }
 
-#[component]
-pub fn WaitingCustom(cx: Scope) -> impl IntoView {
+/// Create a custom game and go to its link page once the server answers.
+fn create_custom_game(cx: Scope, game_kind: &GameEventKind) -> impl IntoView {
     let navigate = leptos_router::use_navigate(cx);
-    let events = GameEventStream::new(cx, &GameEventKind::Custom);
+    let events = GameEventStream::new(cx, game_kind);
     let i18n = i18n_context(cx);
 
     events.listen(cx, move |event| {
@@ -39,10 +52,136 @@ pub fn WaitingCustom(cx: Scope) -> impl IntoView {
         }
     });
 
+    view! { cx, <p>{t!(i18n, creating)}</p> }
+}
+
+#[component]
+pub fn WaitingCustom(cx: Scope) -> impl IntoView {
     view! { cx,
         <Title text="Hex Chess | Creating custom game..."/>
         <CheckLoggedIn>
-            <p>{t!(i18n, creating)}</p>
+            {create_custom_game(cx, &GameEventKind::Custom)}
+        </CheckLoggedIn>
+    }
+}
+
+/// Choose the time per move of a correspondence game, then create it like a custom game.
+#[component]
+pub fn WaitingCorrespondence(cx: Scope) -> impl IntoView {
+    const DAYS_PER_MOVE: [u32; 4] = [1, 3, 7, 14];
+
+    let i18n = i18n_context(cx);
+    let (days_per_move, set_days_per_move) = create_signal(cx, None);
+
+    let render = move || match days_per_move.get() {
+        Some(days) => create_custom_game(cx, &GameEventKind::Correspondence(days)).into_view(cx),
+        None => view! { cx,
+            <p>{t!(i18n, days_per_move)}</p>
+            <div class="link_to_games">
+                {DAYS_PER_MOVE
+                    .into_iter()
+                    .map(|days| view! { cx,
+                        <div on:click=move |_| set_days_per_move.set(Some(days)) class="big_button">
+                            <p>{days}</p>
+                        </div>
+                    })
+                    .collect_view(cx)}
+            </div>
+        }
+        .into_view(cx),
+    };
+
+    view! { cx,
+        <Title text="Hex Chess | Correspondence game"/>
+        <CheckLoggedIn>
+            {render}
+        </CheckLoggedIn>
+    }
+}
+
+/// Challenge the player with the public id in the url, as a correspondence game if the query
+/// sets `days_per_move`.
+#[component]
+pub fn WaitingChallenge(cx: Scope) -> impl IntoView {
+    let params = use_params_map(cx);
+    let query = use_query_map(cx);
+
+    let render = move || {
+        let opponent = params.with(|params| params.get("id")?.parse().ok())?;
+        let days_per_move = query.with(|query| query.get("days_per_move")?.parse().ok());
+        let game_kind = GameEventKind::Challenge {
+            opponent,
+            days_per_move,
+        };
+        Some(create_custom_game(cx, &game_kind))
+    };
+
+    view! { cx,
+        <Title text="Hex Chess | Challenge"/>
+        <CheckLoggedIn>
+            {render}
+        </CheckLoggedIn>
+    }
+}
+
+/// Challenge received by the user, linked from its notification.
+#[component]
+pub fn ChallengeInvite(cx: Scope) -> impl IntoView {
+    let params = use_params_map(cx);
+    let i18n = i18n_context(cx);
+    let decline = create_server_action::<DeclineChallenge>(cx);
+
+    let challenge = create_resource(
+        cx,
+        move || params.with(|params| params.get("game_id")?.parse::<Uuid>().ok()),
+        move |game_id| async move {
+            match game_id {
+                Some(game_id) => get_challenge(cx, game_id).await.ok(),
+                None => None,
+            }
+        },
+    );
+    let declined = move || decline.value().with(|res| matches!(res, Some(Ok(()))));
+
+    let render = move || {
+        let Some(challenge) = challenge.read(cx).flatten() else {
+            return view! { cx,
+                <p>{t!(i18n, challenge_unavailable)}</p>
+                <a href="/" class="big_button">{t!(i18n, back_home)}</a>
+            }
+            .into_view(cx);
+        };
+        if declined() {
+            return view! { cx,
+                <p>{t!(i18n, challenge_declined)}</p>
+                <a href="/" class="big_button">{t!(i18n, back_home)}</a>
+            }
+            .into_view(cx);
+        }
+        let game_id = challenge.game_id;
+        view! { cx,
+            <p>{challenge.challenger} " " {t!(i18n, challenges_you)}</p>
+            {challenge.days_per_move.map(|days| view! { cx,
+                <p>{t!(i18n, correspondence)} ": " {days} " " {t!(i18n, days_per_move_short)}</p>
+            })}
+            <div class="link_to_games">
+                <a href=format!("/play/{}", game_id) class="big_button">{t!(i18n, accept)}</a>
+                <div on:click=move |_| decline.dispatch(DeclineChallenge { game_id }) class="big_button">
+                    <p>{t!(i18n, decline)}</p>
+                </div>
+            </div>
+        }
+        .into_view(cx)
+    };
+
+    view! { cx,
+        <Title text="Hex Chess | Challenge"/>
+        <CheckLoggedIn>
+            <div class="custom_game_link">
+                <Suspense fallback=|| ()>
+                    {render}
+                </Suspense>
+            </div>
         </CheckLoggedIn>
     }
 }
@@ -63,16 +202,36 @@ pub fn WaitingCustomWithId(cx: Scope) -> impl IntoView {
                     copy_to_clipboard(&format!("{}/play/{}", origin, game_id.get_value()));
                 }
             };
-            events.listen(cx, move |event| {
-                if let GameEvent::GameStart { game_id, .. } = event {
+            let (expired, set_expired) = create_signal(cx, false);
+            let (declined, set_declined) = create_signal(cx, false);
+            events.listen(cx, move |event| match event {
+                GameEvent::GameStart { game_id, .. } => {
                     navigate(&format!("/play/{}", game_id), Default::default()).unwrap();
                 }
+                GameEvent::InvitationExpired => set_expired.set(true),
+                GameEvent::ChallengeDeclined => set_declined.set(true),
+                _ => {}
             });
+            let closed_message = move || {
+                if declined.get() {
+                    t!(i18n, challenge_declined).into_view(cx)
+                } else {
+                    t!(i18n, invitation_expired).into_view(cx)
+                }
+            };
 
             view! { cx,
-                <div on:click=on_copy class="big_button">
-                    <p>{t!(i18n, copy_link)}</p>
-                </div>
+                <Show
+                    when=move || expired.get() || declined.get()
+                    fallback=move |cx| view! { cx,
+                        <div on:click=on_copy class="big_button">
+                            <p>{t!(i18n, copy_link)}</p>
+                        </div>
+                    }
+                >
+                    <p>{closed_message}</p>
+                    <a href="/play/custom" class="big_button">{t!(i18n, new_invitation)}</a>
+                </Show>
             }
         })
     };
@@ -92,8 +251,20 @@ type EventSignal = ReadSignal<Option<Result<GameEvent, EventError>>>;
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub enum GameEventKind<'a> {
     Custom,
+    Correspondence(u32),
+    /// A custom game only the player with the public id `opponent` can join.
+    Challenge {
+        opponent: i32,
+        days_per_move: Option<u32>,
+    },
     Join(&'a str),
     Random,
+    /// Lobby of a tournament, sends `GameStart` when the player is paired.
+    Tournament(Uuid),
+    /// Lobby of a simul challenger, sends `GameStart` when the host starts the simul.
+    Simul(Uuid),
+    /// Stream of a simul host, the events of every board come wrapped in `SimulBoard`.
+    SimulHost(Uuid),
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -110,18 +281,52 @@ impl GameEventStream {
         log!("{:?}", game_kind);
         let url = match game_kind {
             GameEventKind::Custom => "/api/board/new_custom_game".into(),
+            GameEventKind::Correspondence(days_per_move) => {
+                format!("/api/board/new_custom_game?days_per_move={}", days_per_move)
+            }
+            GameEventKind::Challenge {
+                opponent,
+                days_per_move: Some(days_per_move),
+            } => format!(
+                "/api/board/new_custom_game?opponent={}&days_per_move={}",
+                opponent, days_per_move
+            ),
+            GameEventKind::Challenge {
+                opponent,
+                days_per_move: None,
+            } => format!("/api/board/new_custom_game?opponent={}", opponent),
             GameEventKind::Random => "/api/board/new_random_game".into(),
             GameEventKind::Join(id) => format!("/api/board/join_game/{}", id),
+            GameEventKind::Tournament(id) => format!("/api/tournaments/{}/lobby", id),
+            GameEventKind::Simul(id) => format!("/api/simuls/{}/lobby", id),
+            GameEventKind::SimulHost(id) => format!("/api/simuls/{}/host", id),
         };
         let mut source = gloo_net::eventsource::futures::EventSource::new(&url).unwrap();
-        let stream = source.subscribe("message").unwrap().map(|value| {
+        let subscription = source.subscribe("message").unwrap();
+        // Shared with the stream so an expired or declined invitation can stop the automatic
+        // reconnection, which would otherwise put the player back in the waiting room.
+        let source = std::rc::Rc::new(std::cell::RefCell::new(Some(source)));
+        let expired_source = source.clone();
+        let stream = subscription.map(move |value| {
             let (_, event) = value?;
             let data = event.data().as_string().unwrap();
             let event: GameEvent = serde_json::from_str(&data).unwrap();
+            if matches!(
+                event,
+                GameEvent::InvitationExpired | GameEvent::ChallengeDeclined
+            ) {
+                if let Some(source) = expired_source.borrow_mut().take() {
+                    source.close();
+                }
+            }
             Ok(event)
         });
         let events = create_signal_from_stream(cx, stream);
-        on_cleanup(cx, move || source.close());
+        on_cleanup(cx, move || {
+            if let Some(source) = source.borrow_mut().take() {
+                source.close();
+            }
+        });
         events
     }
 
@@ -136,6 +341,7 @@ impl GameEventStream {
                 Ok(ev) => ev,
                 Err(err) => {
                     log!("err: {:?}", err);
+                    check_rate_limit(cx);
                     return None;
                 }
             };
@@ -145,6 +351,31 @@ impl GameEventStream {
             None
         });
     }
+
+    /// Split the stream of a simul host into one stream per board, in the order the boards
+    /// started.
+    pub fn simul_boards(self, cx: Scope) -> ReadSignal<Vec<(Uuid, GameEventStream)>> {
+        let (boards, set_boards) = create_signal(cx, vec![]);
+        let senders = store_value(cx, HashMap::new());
+
+        self.listen(cx, move |event| {
+            let GameEvent::SimulBoard { game_id, event } = event else {
+                return;
+            };
+            match senders.with_value(|senders| senders.get(&game_id).copied()) {
+                Some(sender) => sender.set(Some(Ok(*event))),
+                None => {
+                    let (events, sender) = create_signal(cx, Some(Ok(*event)));
+                    senders.update_value(|senders| {
+                        senders.insert(game_id, sender);
+                    });
+                    set_boards.update(|boards| boards.push((game_id, GameEventStream(events))));
+                }
+            }
+        });
+
+        boards
+    }
 }
 
 #[component]
@@ -156,6 +387,7 @@ pub fn Play(cx: Scope) -> impl IntoView {
             let events = GameEventStream::new(cx, &GameEventKind::Join(game_id));
             view! { cx,
                 <MultiBoard events=events/>
+                <GameChat events=events/>
             }
         })
     };
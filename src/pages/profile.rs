@@ -0,0 +1,94 @@
+use crate::components::auth::LoggedIn;
+use crate::i18n::i18n_context;
+use crate::server::profile::{get_player_profile, FollowPlayer, PlayerProfile, UnfollowPlayer};
+use leptos::*;
+use leptos_i18n::t;
+use leptos_meta::*;
+use leptos_router::*;
+
+/// Ways to challenge a player: a live game or a correspondence game with these days per move.
+fn challenge_links(cx: Scope, id: i32) -> impl IntoView {
+    const DAYS_PER_MOVE: [u32; 4] = [1, 3, 7, 14];
+
+    let i18n = i18n_context(cx);
+    view! { cx,
+        <div class="link_to_games">
+            <a href=format!("/play/challenge/{}", id) class="big_button">{t!(i18n, challenge)}</a>
+        </div>
+        <p>{t!(i18n, correspondence)} " (" {t!(i18n, days_per_move)} ")"</p>
+        <div class="link_to_games">
+            {DAYS_PER_MOVE
+                .into_iter()
+                .map(|days| view! { cx,
+                    <a href=format!("/play/challenge/{}?days_per_move={}", id, days) class="big_button">
+                        {days}
+                    </a>
+                })
+                .collect_view(cx)}
+        </div>
+    }
+}
+
+/// Public page of a player, where they can be challenged.
+#[component]
+pub fn Profile(cx: Scope) -> impl IntoView {
+    let params = use_params_map(cx);
+    let i18n = i18n_context(cx);
+
+    let follow = create_server_action::<FollowPlayer>(cx);
+    let unfollow = create_server_action::<UnfollowPlayer>(cx);
+
+    let profile = create_resource(
+        cx,
+        move || {
+            let id = params.with(|params| params.get("id")?.parse::<i32>().ok());
+            (id, follow.version().get(), unfollow.version().get())
+        },
+        move |(id, ..)| async move {
+            match id {
+                Some(id) => get_player_profile(cx, id).await.ok(),
+                None => None,
+            }
+        },
+    );
+
+    let render_profile = move |profile: PlayerProfile| {
+        let id = profile.id;
+        let follow_button = if profile.is_followed {
+            view! { cx,
+                <button class="small_button" on:click=move |_| unfollow.dispatch(UnfollowPlayer { id })>
+                    {t!(i18n, unfollow)}
+                </button>
+            }
+        } else {
+            view! { cx,
+                <button class="small_button" on:click=move |_| follow.dispatch(FollowPlayer { id })>
+                    {t!(i18n, follow)}
+                </button>
+            }
+        };
+        view! { cx,
+            <h1 class="title">{profile.username}</h1>
+            {(!profile.is_me).then(|| view! { cx,
+                <LoggedIn>
+                    {follow_button.clone()}
+                    {challenge_links(cx, id)}
+                </LoggedIn>
+            })}
+        }
+    };
+
+    let render = move || {
+        profile.read(cx).map(|profile| match profile {
+            Some(profile) => render_profile(profile).into_view(cx),
+            None => view! { cx, <p>{t!(i18n, player_not_found)}</p> }.into_view(cx),
+        })
+    };
+
+    view! { cx,
+        <Title text="Hex Chess | Player"/>
+        <Suspense fallback=|| ()>
+            {render}
+        </Suspense>
+    }
+}
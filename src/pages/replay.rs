@@ -0,0 +1,75 @@
+use crate::components::board::ReplayBoard;
+use crate::i18n::i18n_context;
+use crate::server::board::{get_game_replay, GameReplay};
+use hex_chess_core::board::GameEnd;
+use leptos::*;
+use leptos_i18n::t;
+use leptos_meta::*;
+use leptos_router::*;
+use uuid::Uuid;
+
+/// How the game ended, from the outcome of its record.
+fn outcome_text(cx: Scope, replay: &GameReplay) -> impl IntoView {
+    let i18n = i18n_context(cx);
+    match replay.outcome.as_str() {
+        "timeout" => {
+            let loser = replay.board.get_player_turn();
+            view! { cx, {format!("{:?} ", loser)}{t!(i18n, ran_out_of_time)} }.into_view(cx)
+        }
+        "abandoned" => t!(i18n, replay_abandoned).into_view(cx),
+        "terminated" => t!(i18n, game_terminated).into_view(cx),
+        _ => match replay.board.is_end() {
+            Some(GameEnd::Win(color)) => format!("{:?} wins!", color).into_view(cx),
+            Some(GameEnd::Draw) => "Draw".into_view(cx),
+            Some(GameEnd::Stalemate { winner }) => {
+                format!("Stalemate, {:?} wins 3/4 of the points", winner).into_view(cx)
+            }
+            None => ().into_view(cx),
+        },
+    }
+}
+
+/// Replay of a game that is over, open to everyone.
+#[component]
+pub fn Replay(cx: Scope) -> impl IntoView {
+    let params = use_params_map(cx);
+    let i18n = i18n_context(cx);
+
+    let replay = create_resource(
+        cx,
+        move || params.with(|params| params.get("id")?.parse::<Uuid>().ok()),
+        move |game_id| async move {
+            match game_id {
+                Some(game_id) => get_game_replay(cx, game_id).await.ok().flatten(),
+                None => None,
+            }
+        },
+    );
+
+    let render = move || {
+        replay.read(cx).map(|replay| match replay {
+            Some(replay) => view! { cx,
+                <p class="replay__players">
+                    {replay.white.clone()} " (" {t!(i18n, white)} ") - "
+                    {replay.black.clone()} " (" {t!(i18n, black)} ")"
+                </p>
+                <p>{outcome_text(cx, &replay)}</p>
+                <ReplayBoard board=replay.board/>
+                <a href=format!("/analysis/{}", replay.game_id) class="small_button">
+                    {t!(i18n, analysis_open)}
+                </a>
+            }
+            .into_view(cx),
+            None => view! { cx, <p>{t!(i18n, replay_not_found)}</p> }.into_view(cx),
+        })
+    };
+
+    view! { cx,
+        <Title text="Hex Chess | Replay"/>
+        <div class="board">
+            <Suspense fallback=|| ()>
+                {render}
+            </Suspense>
+        </div>
+    }
+}
@@ -0,0 +1,304 @@
+use crate::components::auth::LoggedIn;
+use crate::components::board::MultiBoard;
+use crate::hooks::use_interval;
+use crate::i18n::i18n_context;
+use crate::pages::play::{GameEventKind, GameEventStream};
+use crate::server::board::GameEvent;
+use crate::server::simul::{
+    get_simul, get_simuls, CreateSimul, JoinSimul, LeaveSimul, SimulBoard, SimulDetails,
+    SimulStatus, SimulSummary, StartSimul, SIMUL_REFRESH,
+};
+use crate::server::tournaments::display_points;
+use leptos::*;
+use leptos_i18n::t;
+use leptos_meta::*;
+use leptos_router::*;
+use uuid::Uuid;
+
+fn status_text(cx: Scope, status: SimulStatus) -> impl IntoView {
+    let i18n = i18n_context(cx);
+    match status {
+        SimulStatus::Open => t!(i18n, simul_open).into_view(cx),
+        SimulStatus::Running => t!(i18n, simul_running).into_view(cx),
+        SimulStatus::Finished => t!(i18n, simul_finished).into_view(cx),
+    }
+}
+
+/// Open and running simuls, and a button to host one.
+#[component]
+pub fn Simuls(cx: Scope) -> impl IntoView {
+    let i18n = i18n_context(cx);
+    let create = create_server_action::<CreateSimul>(cx);
+    let navigate = leptos_router::use_navigate(cx);
+
+    create_effect(cx, move |_| {
+        if let Some(Ok(id)) = create.value().get() {
+            let _ = navigate(&format!("/simul/{}", id), Default::default());
+        }
+    });
+
+    let refresh = use_interval(cx, SIMUL_REFRESH);
+    let simuls = create_resource(cx, move || refresh.get(), move |_| get_simuls(cx));
+
+    let render_simul = move |summary: SimulSummary| {
+        view! { cx,
+            <li>
+                <a href=format!("/simul/{}", summary.id) class="tournament__name">
+                    {summary.host}
+                </a>
+                <span>{summary.challengers} " " {t!(i18n, simul_challengers)}</span>
+                <span class="tournament__status">{status_text(cx, summary.status)}</span>
+            </li>
+        }
+    };
+
+    let render = move || {
+        simuls.read(cx).map(|simuls| match simuls {
+            Ok(simuls) if simuls.is_empty() => {
+                view! { cx, <p>{t!(i18n, no_simuls)}</p> }.into_view(cx)
+            }
+            Ok(simuls) => view! { cx,
+                <ul class="tournament__list">
+                    {simuls.into_iter().map(render_simul).collect_view(cx)}
+                </ul>
+            }
+            .into_view(cx),
+            Err(_) => ().into_view(cx),
+        })
+    };
+
+    let error = move || {
+        create
+            .value()
+            .get()
+            .and_then(Result::err)
+            .map(|err| view! { cx, <p class="tournament__error">{err.to_string()}</p> })
+    };
+
+    view! { cx,
+        <Title text="Hex Chess | Simuls"/>
+        <h1 class="title">{t!(i18n, simuls)}</h1>
+        <Transition fallback=|| ()>
+            {render}
+        </Transition>
+        <LoggedIn>
+            <button class="small_button" on:click=move |_| create.dispatch(CreateSimul {})>
+                {t!(i18n, simul_host_new)}
+            </button>
+            {error}
+        </LoggedIn>
+    }
+}
+
+/// Keeps a challenger on the simul page and sends them to their board once the host starts.
+#[component]
+fn SimulLobby(cx: Scope, id: Uuid) -> impl IntoView {
+    let i18n = i18n_context(cx);
+    let navigate = leptos_router::use_navigate(cx);
+    let events = GameEventStream::new(cx, &GameEventKind::Simul(id));
+
+    events.listen(cx, move |event| {
+        if let GameEvent::GameStart { game_id, .. } = event {
+            let _ = navigate(&format!("/play/{}", game_id), Default::default());
+        }
+    });
+
+    view! { cx, <p class="tournament__lobby">{t!(i18n, simul_waiting_start)}</p> }
+}
+
+/// Every board of the host, played from a single page.
+#[component]
+fn SimulHostBoards(cx: Scope, id: Uuid) -> impl IntoView {
+    let events = GameEventStream::new(cx, &GameEventKind::SimulHost(id));
+    let boards = events.simul_boards(cx);
+
+    view! { cx,
+        <div class="simul__boards">
+            <For
+                each=move || boards.get()
+                key=|(game_id, _)| *game_id
+                view=move |cx, (_, events)| view! { cx,
+                    <div class="simul__board">
+                        <MultiBoard events=events compact=true/>
+                    </div>
+                }
+            />
+        </div>
+    }
+}
+
+/// Score of the host once every board is over.
+fn results_summary(cx: Scope, details: &SimulDetails) -> impl IntoView {
+    let i18n = i18n_context(cx);
+    let (host, challengers) = details.score();
+    let (wins, draws, losses) = details.host_record();
+    view! { cx,
+        <div class="simul__results">
+            <h2>{t!(i18n, simul_results)}</h2>
+            <p class="simul__score">
+                {details.summary.host.clone()} " " {display_points(host)} " - "
+                {display_points(challengers)} " " {t!(i18n, simul_challengers)}
+            </p>
+            <p class="tournament__info">
+                {wins} " " {t!(i18n, simul_wins)} " · "
+                {draws} " " {t!(i18n, simul_draws)} " · "
+                {losses} " " {t!(i18n, simul_losses)}
+            </p>
+        </div>
+    }
+}
+
+fn boards_table(cx: Scope, boards: Vec<SimulBoard>) -> impl IntoView {
+    let i18n = i18n_context(cx);
+    let rows = boards
+        .into_iter()
+        .map(|board| {
+            let result = match (board.result, board.game_id) {
+                (Some(result), _) => result.display().into_view(cx),
+                (None, Some(game_id)) => view! { cx,
+                    <a href=format!("/play/{}", game_id)>{t!(i18n, spectate)}</a>
+                }
+                .into_view(cx),
+                (None, None) if board.ready => t!(i18n, simul_ready).into_view(cx),
+                (None, None) => t!(i18n, simul_not_ready).into_view(cx),
+            };
+            view! { cx,
+                <tr>
+                    <td>{board.challenger}</td>
+                    <td>{result}</td>
+                </tr>
+            }
+        })
+        .collect_view(cx);
+    view! { cx,
+        <table class="tournament__table">
+            <tr>
+                <th>{t!(i18n, simul_challenger)}</th>
+                <th>{t!(i18n, tournament_result)}</th>
+            </tr>
+            {rows}
+        </table>
+    }
+}
+
+/// A simul: challengers join and wait for the host, who plays all the boards at once.
+#[component]
+pub fn Simul(cx: Scope) -> impl IntoView {
+    let params = use_params_map(cx);
+    let i18n = i18n_context(cx);
+
+    let join = create_server_action::<JoinSimul>(cx);
+    let leave = create_server_action::<LeaveSimul>(cx);
+    let start = create_server_action::<StartSimul>(cx);
+
+    let refresh = use_interval(cx, SIMUL_REFRESH);
+    let id = move || params.with(|params| params.get("id")?.parse::<Uuid>().ok());
+    let details = create_resource(
+        cx,
+        move || {
+            (
+                id(),
+                refresh.get(),
+                join.version().get(),
+                leave.version().get(),
+                start.version().get(),
+            )
+        },
+        move |(id, ..)| async move {
+            match id {
+                Some(id) => get_simul(cx, id).await.ok(),
+                None => None,
+            }
+        },
+    );
+
+    let in_lobby = move || {
+        details.read(cx).flatten().is_some_and(|details| {
+            details.is_challenger && details.summary.status != SimulStatus::Finished
+        })
+    };
+    let is_host = move || {
+        details
+            .read(cx)
+            .flatten()
+            .is_some_and(|details| details.is_host)
+    };
+
+    let error = move || {
+        [join.value(), leave.value(), start.value()]
+            .into_iter()
+            .find_map(|value| value.get().and_then(Result::err))
+            .map(|err| view! { cx, <p class="tournament__error">{err.to_string()}</p> })
+    };
+
+    let render_actions = move |details: &SimulDetails| {
+        let id = details.summary.id;
+        let is_open = details.summary.status == SimulStatus::Open;
+        let button = match (details.is_host, details.is_challenger) {
+            (true, _) => is_open.then(|| {
+                view! { cx,
+                    <button class="small_button" on:click=move |_| start.dispatch(StartSimul { id })>
+                        {t!(i18n, simul_start)}
+                    </button>
+                }
+            }),
+            (false, true) => is_open.then(|| {
+                view! { cx,
+                    <button class="small_button" on:click=move |_| leave.dispatch(LeaveSimul { id })>
+                        {t!(i18n, simul_leave)}
+                    </button>
+                }
+            }),
+            (false, false) => is_open.then(|| {
+                view! { cx,
+                    <button class="small_button" on:click=move |_| join.dispatch(JoinSimul { id })>
+                        {t!(i18n, simul_join)}
+                    </button>
+                }
+            }),
+        };
+        view! { cx,
+            <div class="tournament__actions">
+                <LoggedIn>{button.clone()}</LoggedIn>
+            </div>
+        }
+    };
+
+    let render_details = move |details: SimulDetails| {
+        let actions = render_actions(&details);
+        let results = (details.summary.status == SimulStatus::Finished)
+            .then(|| results_summary(cx, &details));
+        view! { cx,
+            <h1 class="title">{t!(i18n, simul)} " · " {details.summary.host.clone()}</h1>
+            <p class="tournament__info">
+                {details.summary.challengers} " " {t!(i18n, simul_challengers)} " · "
+                {status_text(cx, details.summary.status)}
+            </p>
+            {actions}
+            {results}
+            {boards_table(cx, details.boards)}
+        }
+    };
+
+    let render = move || {
+        details.read(cx).map(|details| match details {
+            Some(details) => render_details(details).into_view(cx),
+            None => view! { cx, <p>{t!(i18n, simul_not_found)}</p> }.into_view(cx),
+        })
+    };
+
+    view! { cx,
+        <Title text="Hex Chess | Simul"/>
+        <Transition fallback=|| ()>
+            {render}
+        </Transition>
+        {error}
+        // outside of the refreshed details so the boards keep their state
+        <Show when=is_host fallback=|_| ()>
+            {move || id().map(|id| view! { cx, <SimulHostBoards id=id/> })}
+        </Show>
+        <Show when=in_lobby fallback=|_| ()>
+            {move || id().map(|id| view! { cx, <SimulLobby id=id/> })}
+        </Show>
+    }
+}
@@ -0,0 +1,588 @@
+use crate::components::auth::LoggedIn;
+use crate::hooks::use_interval;
+use crate::i18n::i18n_context;
+use crate::pages::play::{GameEventKind, GameEventStream};
+use crate::server::board::GameEvent;
+use crate::server::tournaments::{
+    display_points, get_game_tournament, get_tournament, get_tournaments, BracketMatchSummary,
+    BracketSide, CreateTournament, PairingSummary, RegisterTournament, Standing, StartTournament,
+    TournamentDetails, TournamentFormat, TournamentStatus, TournamentSummary, WithdrawTournament,
+    TOURNAMENT_REFRESH,
+};
+use leptos::*;
+use leptos_i18n::t;
+use leptos_meta::*;
+use leptos_router::*;
+use uuid::Uuid;
+
+fn format_name(cx: Scope, format: TournamentFormat) -> impl IntoView {
+    let i18n = i18n_context(cx);
+    match format {
+        TournamentFormat::Swiss { .. } => t!(i18n, tournament_swiss).into_view(cx),
+        TournamentFormat::Arena { .. } => t!(i18n, tournament_arena).into_view(cx),
+        TournamentFormat::RoundRobin { .. } => t!(i18n, tournament_round_robin).into_view(cx),
+        TournamentFormat::SingleElimination { .. } => {
+            t!(i18n, tournament_single_elimination).into_view(cx)
+        }
+        TournamentFormat::DoubleElimination { .. } => {
+            t!(i18n, tournament_double_elimination).into_view(cx)
+        }
+    }
+}
+
+fn format_text(cx: Scope, format: TournamentFormat) -> impl IntoView {
+    let i18n = i18n_context(cx);
+    let length = match format {
+        TournamentFormat::Swiss { rounds } => view! { cx,
+            {rounds} " " {t!(i18n, tournament_rounds)}
+        }
+        .into_view(cx),
+        TournamentFormat::Arena { minutes } => view! { cx,
+            {minutes} " " {t!(i18n, tournament_minutes)}
+        }
+        .into_view(cx),
+        TournamentFormat::RoundRobin { round_minutes }
+        | TournamentFormat::SingleElimination { round_minutes }
+        | TournamentFormat::DoubleElimination { round_minutes } => view! { cx,
+            {round_minutes} " " {t!(i18n, tournament_minutes_between_rounds)}
+        }
+        .into_view(cx),
+    };
+    view! { cx, {format_name(cx, format)} " · " {length} }
+}
+
+fn status_text(cx: Scope, summary: &TournamentSummary) -> impl IntoView {
+    let i18n = i18n_context(cx);
+    match (summary.status, summary.format) {
+        (TournamentStatus::Registration, _) => t!(i18n, tournament_registration).into_view(cx),
+        (TournamentStatus::Running, TournamentFormat::Arena { .. }) => {
+            let minutes_left = summary.minutes_left.unwrap_or_default();
+            view! { cx,
+                {t!(i18n, tournament_running)} " · " {minutes_left} " " {t!(i18n, tournament_minutes)}
+            }
+            .into_view(cx)
+        }
+        (TournamentStatus::Running, _) => {
+            let rounds = summary.rounds.map(|rounds| format!("/{}", rounds));
+            let next_round = summary.next_round_in.map(|minutes| view! { cx,
+                " · " {t!(i18n, tournament_next_round_in)} " " {minutes} " " {t!(i18n, tournament_minutes)}
+            });
+            view! { cx,
+                {t!(i18n, tournament_round)} " " {summary.round} {rounds} {next_round}
+            }
+            .into_view(cx)
+        }
+        (TournamentStatus::Finished, _) => t!(i18n, tournament_finished).into_view(cx),
+    }
+}
+
+/// Form for admins to create a tournament in one of the formats.
+fn create_tournament_form(cx: Scope) -> impl IntoView {
+    let i18n = i18n_context(cx);
+    let create = create_server_action::<CreateTournament>(cx);
+    let (name, set_name) = create_signal(cx, String::new());
+    let (kind, set_kind) = create_signal(cx, String::from("swiss"));
+    let (length, set_length) = create_signal(cx, 5u32);
+    let navigate = leptos_router::use_navigate(cx);
+
+    create_effect(cx, move |_| {
+        if let Some(Ok(id)) = create.value().get() {
+            let _ = navigate(&format!("/tournament/{}", id), Default::default());
+        }
+    });
+
+    let error = move || {
+        create
+            .value()
+            .get()
+            .and_then(Result::err)
+            .map(|err| view! { cx, <p class="tournament__error">{err.to_string()}</p> })
+    };
+
+    let on_submit = move |ev: ev::SubmitEvent| {
+        ev.prevent_default();
+        let length = length.get_untracked();
+        let format = match kind.get_untracked().as_str() {
+            "arena" => TournamentFormat::Arena { minutes: length },
+            "round_robin" => TournamentFormat::RoundRobin {
+                round_minutes: length,
+            },
+            "single_elimination" => TournamentFormat::SingleElimination {
+                round_minutes: length,
+            },
+            "double_elimination" => TournamentFormat::DoubleElimination {
+                round_minutes: length,
+            },
+            _ => TournamentFormat::Swiss { rounds: length },
+        };
+        create.dispatch(CreateTournament {
+            name: name.get_untracked(),
+            format,
+        });
+    };
+
+    let length_label = move || match kind.get().as_str() {
+        "swiss" => t!(i18n, tournament_rounds).into_view(cx),
+        "arena" => t!(i18n, tournament_minutes).into_view(cx),
+        _ => t!(i18n, tournament_minutes_between_rounds).into_view(cx),
+    };
+
+    let option = move |value: &'static str, label: View| {
+        view! { cx,
+            <option value=value selected=move || kind.get() == value>{label}</option>
+        }
+    };
+
+    view! { cx,
+        <form class="tournament__create" on:submit=on_submit>
+            <h3>{t!(i18n, tournament_create)}</h3>
+            <label>
+                {t!(i18n, tournament_name)}
+                <input
+                    type="text"
+                    prop:value=name
+                    on:input=move |ev| set_name.set(event_target_value(&ev))
+                />
+            </label>
+            <select on:change=move |ev| set_kind.set(event_target_value(&ev))>
+                {option("swiss", t!(i18n, tournament_swiss).into_view(cx))}
+                {option("arena", t!(i18n, tournament_arena).into_view(cx))}
+                {option("round_robin", t!(i18n, tournament_round_robin).into_view(cx))}
+                {option("single_elimination", t!(i18n, tournament_single_elimination).into_view(cx))}
+                {option("double_elimination", t!(i18n, tournament_double_elimination).into_view(cx))}
+            </select>
+            <label>
+                {length_label}
+                <input
+                    type="number"
+                    min="1"
+                    prop:value=move || length.get().to_string()
+                    on:input=move |ev| {
+                        if let Ok(length) = event_target_value(&ev).parse() {
+                            set_length.set(length);
+                        }
+                    }
+                />
+            </label>
+            <button type="submit" class="small_button">{t!(i18n, tournament_create)}</button>
+            {error}
+        </form>
+    }
+}
+
+/// Running and upcoming tournaments.
+#[component]
+pub fn Tournaments(cx: Scope) -> impl IntoView {
+    let i18n = i18n_context(cx);
+
+    let refresh = use_interval(cx, TOURNAMENT_REFRESH);
+    let tournaments = create_resource(cx, move || refresh.get(), move |_| get_tournaments(cx));
+
+    let render_tournament = move |summary: TournamentSummary| {
+        view! { cx,
+            <li>
+                <a href=format!("/tournament/{}", summary.id) class="tournament__name">
+                    {summary.name.clone()}
+                </a>
+                <span>{format_text(cx, summary.format)}</span>
+                <span>{summary.players} " " {t!(i18n, tournament_players)}</span>
+                <span class="tournament__status">{status_text(cx, &summary)}</span>
+            </li>
+        }
+    };
+
+    let render = move || {
+        tournaments.read(cx).map(|list| match list {
+            Ok(list) if list.tournaments.is_empty() => {
+                view! { cx, <p>{t!(i18n, no_tournaments)}</p> }.into_view(cx)
+            }
+            Ok(list) => view! { cx,
+                <ul class="tournament__list">
+                    {list.tournaments.into_iter().map(render_tournament).collect_view(cx)}
+                </ul>
+            }
+            .into_view(cx),
+            Err(_) => ().into_view(cx),
+        })
+    };
+
+    // outside of the refreshed list so the form keeps what the admin typed
+    let is_admin = move || {
+        tournaments
+            .read(cx)
+            .is_some_and(|list| list.is_ok_and(|list| list.is_admin))
+    };
+
+    view! { cx,
+        <Title text="Hex Chess | Tournaments"/>
+        <h1 class="title">{t!(i18n, tournaments)}</h1>
+        <Transition fallback=|| ()>
+            {render}
+        </Transition>
+        <Show when=is_admin fallback=|_| ()>
+            {create_tournament_form(cx)}
+        </Show>
+    }
+}
+
+/// Keeps a registered player in the lobby and sends them to their game once they are paired.
+#[component]
+fn TournamentLobby(cx: Scope, id: Uuid) -> impl IntoView {
+    let i18n = i18n_context(cx);
+    let navigate = leptos_router::use_navigate(cx);
+    let events = GameEventStream::new(cx, &GameEventKind::Tournament(id));
+
+    events.listen(cx, move |event| {
+        if let GameEvent::GameStart { game_id, .. } = event {
+            let _ = navigate(&format!("/play/{}", game_id), Default::default());
+        }
+    });
+
+    view! { cx, <p class="tournament__lobby">{t!(i18n, tournament_waiting_pairing)}</p> }
+}
+
+fn standings_table(cx: Scope, standings: Vec<Standing>) -> impl IntoView {
+    let i18n = i18n_context(cx);
+    let rows = standings
+        .into_iter()
+        .map(|standing| {
+            view! { cx,
+                <tr class:tournament__withdrawn=standing.withdrawn>
+                    <td>{standing.rank}</td>
+                    <td>{standing.username}</td>
+                    <td>{display_points(standing.points)}</td>
+                    <td>{standing.games}</td>
+                    <td>{standing.wins}</td>
+                    <td>{display_points(standing.buchholz)}</td>
+                    <td>{display_points(standing.sonneborn_berger)}</td>
+                </tr>
+            }
+        })
+        .collect_view(cx);
+    view! { cx,
+        <h2>{t!(i18n, tournament_standings)}</h2>
+        <table class="tournament__table">
+            <tr>
+                <th>"#"</th>
+                <th>{t!(i18n, tournament_player)}</th>
+                <th>{t!(i18n, tournament_points)}</th>
+                <th>{t!(i18n, tournament_games)}</th>
+                <th>{t!(i18n, tournament_wins)}</th>
+                <th>{t!(i18n, tournament_buchholz)}</th>
+                <th>{t!(i18n, tournament_sonneborn_berger)}</th>
+            </tr>
+            {rows}
+        </table>
+    }
+}
+
+fn pairings_table(cx: Scope, pairings: Vec<PairingSummary>) -> impl IntoView {
+    let i18n = i18n_context(cx);
+    let rows = pairings
+        .into_iter()
+        .map(|pairing| {
+            let result = match (pairing.result, pairing.game_id) {
+                (Some(result), _) => result.display().into_view(cx),
+                (None, Some(game_id)) => view! { cx,
+                    <a href=format!("/play/{}", game_id)>{t!(i18n, spectate)}</a>
+                }
+                .into_view(cx),
+                (None, None) => pairing
+                    .join_deadline_in
+                    .map(|minutes| view! { cx,
+                        {t!(i18n, tournament_join_within)} " " {minutes} " " {t!(i18n, tournament_minutes)}
+                    })
+                    .into_view(cx),
+            };
+            view! { cx,
+                <tr>
+                    <td>{pairing.round}</td>
+                    <td>{pairing.white}</td>
+                    <td>{pairing.black}</td>
+                    <td>{result}</td>
+                </tr>
+            }
+        })
+        .collect_view(cx);
+    view! { cx,
+        <h2>{t!(i18n, tournament_pairings)}</h2>
+        <table class="tournament__table">
+            <tr>
+                <th>{t!(i18n, tournament_round)}</th>
+                <th>{t!(i18n, white)}</th>
+                <th>{t!(i18n, black)}</th>
+                <th>{t!(i18n, tournament_result)}</th>
+            </tr>
+            {rows}
+        </table>
+    }
+}
+
+/// Standings and pairings of a tournament, where players register and wait for their games.
+#[component]
+pub fn Tournament(cx: Scope) -> impl IntoView {
+    let params = use_params_map(cx);
+    let i18n = i18n_context(cx);
+
+    let register = create_server_action::<RegisterTournament>(cx);
+    let withdraw = create_server_action::<WithdrawTournament>(cx);
+    let start = create_server_action::<StartTournament>(cx);
+
+    let refresh = use_interval(cx, TOURNAMENT_REFRESH);
+    let id = move || params.with(|params| params.get("id")?.parse::<Uuid>().ok());
+    let details = create_resource(
+        cx,
+        move || {
+            (
+                id(),
+                refresh.get(),
+                register.version().get(),
+                withdraw.version().get(),
+                start.version().get(),
+            )
+        },
+        move |(id, ..)| async move {
+            match id {
+                Some(id) => get_tournament(cx, id).await.ok(),
+                None => None,
+            }
+        },
+    );
+
+    let in_lobby = move || {
+        details.read(cx).flatten().is_some_and(|details| {
+            details.is_registered && details.summary.status != TournamentStatus::Finished
+        })
+    };
+
+    let render_actions = move |details: &TournamentDetails| {
+        let id = details.summary.id;
+        let can_register = match (details.summary.status, details.summary.format) {
+            (TournamentStatus::Registration, _) => true,
+            (TournamentStatus::Running, TournamentFormat::Arena { .. }) => details
+                .summary
+                .minutes_left
+                .is_some_and(|minutes| minutes > 0),
+            _ => false,
+        };
+        let register_button = if details.is_registered {
+            (details.summary.status != TournamentStatus::Finished).then(|| {
+                view! { cx,
+                    <button class="small_button" on:click=move |_| withdraw.dispatch(WithdrawTournament { id })>
+                        {t!(i18n, tournament_withdraw)}
+                    </button>
+                }
+            })
+        } else {
+            can_register.then(|| {
+                view! { cx,
+                    <button class="small_button" on:click=move |_| register.dispatch(RegisterTournament { id })>
+                        {t!(i18n, tournament_register)}
+                    </button>
+                }
+            })
+        };
+        let start_button = (details.is_admin
+            && details.summary.status == TournamentStatus::Registration)
+            .then(|| {
+                view! { cx,
+                    <button class="small_button" on:click=move |_| start.dispatch(StartTournament { id })>
+                        {t!(i18n, tournament_start)}
+                    </button>
+                }
+            });
+        view! { cx,
+            <div class="tournament__actions">
+                <LoggedIn>{register_button.clone()}</LoggedIn>
+                {start_button}
+            </div>
+        }
+    };
+
+    let render_details = move |details: TournamentDetails| {
+        let actions = render_actions(&details);
+        view! { cx,
+            <h1 class="title">{details.summary.name.clone()}</h1>
+            <p class="tournament__info">
+                {format_text(cx, details.summary.format)} " · "
+                {details.summary.players} " " {t!(i18n, tournament_players)} " · "
+                {status_text(cx, &details.summary)}
+            </p>
+            {actions}
+            <div class="tournament__links">
+                {details.summary.format.is_bracket().then(|| view! { cx,
+                    <a href=format!("/tournament/{}/bracket", details.summary.id) class="small_button">
+                        {t!(i18n, tournament_bracket)}
+                    </a>
+                })}
+                <a href=format!("/api/tournaments/{}/results.csv", details.summary.id) class="small_button" download>
+                    {t!(i18n, tournament_export_results)}
+                </a>
+                <a href=format!("/api/tournaments/{}/standings.csv", details.summary.id) class="small_button" download>
+                    {t!(i18n, tournament_export_standings)}
+                </a>
+            </div>
+            {standings_table(cx, details.standings)}
+            {(!details.pairings.is_empty()).then(|| pairings_table(cx, details.pairings))}
+        }
+    };
+
+    let render = move || {
+        details.read(cx).map(|details| match details {
+            Some(details) => render_details(details).into_view(cx),
+            None => view! { cx, <p>{t!(i18n, tournament_not_found)}</p> }.into_view(cx),
+        })
+    };
+
+    view! { cx,
+        <Title text="Hex Chess | Tournament"/>
+        <Transition fallback=|| ()>
+            {render}
+        </Transition>
+        <Show when=in_lobby fallback=|_| ()>
+            {move || id().map(|id| view! { cx, <TournamentLobby id=id/> })}
+        </Show>
+    }
+}
+
+fn bracket_match(cx: Scope, bracket_match: BracketMatchSummary) -> impl IntoView {
+    let i18n = i18n_context(cx);
+    let results = bracket_match
+        .results
+        .iter()
+        .map(|result| result.display())
+        .collect::<Vec<_>>()
+        .join(", ");
+    let players = bracket_match
+        .players
+        .into_iter()
+        .enumerate()
+        .map(|(i, player)| {
+            view! { cx,
+                <li class:tournament__bracket-winner=bracket_match.winner == Some(i)>
+                    {player.unwrap_or_else(|| String::from("—"))}
+                </li>
+            }
+        })
+        .collect_view(cx);
+    view! { cx,
+        <div class="tournament__bracket-match">
+            <ol>{players}</ol>
+            <span>{results}</span>
+            {bracket_match.game_id.map(|game_id| view! { cx,
+                <a href=format!("/play/{}", game_id)>{t!(i18n, spectate)}</a>
+            })}
+        </div>
+    }
+}
+
+/// Matches of one side of a bracket in a column per round.
+fn bracket_side(cx: Scope, matches: Vec<BracketMatchSummary>) -> impl IntoView {
+    let rounds = matches.iter().map(|m| m.round).max().unwrap_or_default();
+    let columns = (1..=rounds)
+        .map(|round| {
+            let matches = matches
+                .iter()
+                .filter(|m| m.round == round)
+                .cloned()
+                .map(|m| bracket_match(cx, m))
+                .collect_view(cx);
+            view! { cx, <div class="tournament__bracket-round">{matches}</div> }
+        })
+        .collect_view(cx);
+    view! { cx, <div class="tournament__bracket">{columns}</div> }
+}
+
+/// Bracket of a single or double elimination.
+#[component]
+pub fn TournamentBracket(cx: Scope) -> impl IntoView {
+    let params = use_params_map(cx);
+    let i18n = i18n_context(cx);
+
+    let refresh = use_interval(cx, TOURNAMENT_REFRESH);
+    let id = move || params.with(|params| params.get("id")?.parse::<Uuid>().ok());
+    let details = create_resource(
+        cx,
+        move || (id(), refresh.get()),
+        move |(id, _)| async move {
+            match id {
+                Some(id) => get_tournament(cx, id).await.ok(),
+                None => None,
+            }
+        },
+    );
+
+    let render_bracket = move |details: TournamentDetails| {
+        let side = |side: BracketSide| -> Vec<BracketMatchSummary> {
+            details
+                .bracket
+                .iter()
+                .filter(|m| m.side == side)
+                .cloned()
+                .collect()
+        };
+        let (winners, losers, grand_final) = (
+            side(BracketSide::Winners),
+            side(BracketSide::Losers),
+            side(BracketSide::GrandFinal),
+        );
+        view! { cx,
+            <h1 class="title">
+                <a href=format!("/tournament/{}", details.summary.id)>{details.summary.name.clone()}</a>
+            </h1>
+            <p class="tournament__info">{status_text(cx, &details.summary)}</p>
+            {(!losers.is_empty()).then(|| view! { cx, <h2>{t!(i18n, tournament_winners_bracket)}</h2> })}
+            {bracket_side(cx, winners)}
+            {(!losers.is_empty()).then(|| view! { cx,
+                <h2>{t!(i18n, tournament_losers_bracket)}</h2>
+                {bracket_side(cx, losers)}
+            })}
+            {(!grand_final.is_empty()).then(|| view! { cx,
+                <h2>{t!(i18n, tournament_grand_final)}</h2>
+                {bracket_side(cx, grand_final)}
+            })}
+        }
+    };
+
+    let render = move || {
+        details.read(cx).map(|details| match details {
+            Some(details) if !details.bracket.is_empty() => render_bracket(details).into_view(cx),
+            Some(_) => view! { cx, <p>{t!(i18n, tournament_no_bracket)}</p> }.into_view(cx),
+            None => view! { cx, <p>{t!(i18n, tournament_not_found)}</p> }.into_view(cx),
+        })
+    };
+
+    view! { cx,
+        <Title text="Hex Chess | Bracket"/>
+        <Transition fallback=|| ()>
+            {render}
+        </Transition>
+    }
+}
+
+/// Link back to the tournament a finished game was played in, if any.
+#[component]
+pub fn BackToTournament(cx: Scope, game_id: Uuid) -> impl IntoView {
+    let i18n = i18n_context(cx);
+    let tournament = create_resource(
+        cx,
+        || (),
+        move |_| async move { get_game_tournament(cx, game_id).await.ok().flatten() },
+    );
+
+    let render = move || {
+        tournament.read(cx).flatten().map(|id| {
+            view! { cx,
+                <a href=format!("/tournament/{}", id) class="big_button">
+                    {t!(i18n, back_to_tournament)}
+                </a>
+            }
+        })
+    };
+
+    view! { cx,
+        <Suspense fallback=|| ()>
+            {render}
+        </Suspense>
+    }
+}
@@ -0,0 +1,123 @@
+use leptos::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::server::board::GameSummary;
+
+#[cfg(feature = "ssr")]
+pub mod server;
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct ReportSummary {
+    pub id: i32,
+    pub reporter_id: String,
+    pub reported_id: String,
+    pub game_id: Option<Uuid>,
+    pub reason: String,
+    pub comment: Option<String>,
+    pub created_at: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct AuditSummary {
+    pub id: i32,
+    pub admin_user_id: String,
+    pub action: String,
+    pub target: String,
+    pub details: Option<String>,
+    pub created_at: String,
+}
+
+/// Outcome of the triage of a report of the moderation queue.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ReportResolution {
+    /// Action was taken against the reported player.
+    Resolved,
+    /// The report did not need any action.
+    Dismissed,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct AdminOverview {
+    pub games: Vec<GameSummary>,
+    /// Players in a game or waiting for an opponent.
+    pub active_players: Vec<String>,
+    /// Open reports, oldest first.
+    pub reports: Vec<ReportSummary>,
+    pub audit_log: Vec<AuditSummary>,
+}
+
+#[server(GetAdminOverview, "/api")]
+pub async fn get_admin_overview(cx: Scope) -> Result<AdminOverview, ServerFnError> {
+    server::get_admin_id(cx).await?;
+    server::get_overview(cx).await
+}
+
+#[server(TerminateGame, "/api")]
+pub async fn terminate_game(cx: Scope, game_id: Uuid) -> Result<(), ServerFnError> {
+    let admin_id = server::get_admin_id(cx).await?;
+    use crate::server::db::correspondence_game::CorrespondenceGame;
+
+    let games = crate::server::board::server::get_games(cx).await?;
+    let pool = crate::server::get_pool(cx).await?;
+    games.load_correspondence_game(game_id, &pool).await?;
+    if let Some(record) = games.remove_game(game_id, true).await? {
+        CorrespondenceGame::delete(game_id, &pool).await?;
+        record.insert(&pool).await?;
+    }
+    server::audit(cx, &admin_id, "terminate_game", &game_id.to_string(), None).await
+}
+
+#[server(BanUser, "/api")]
+pub async fn ban_user(cx: Scope, user_id: String) -> Result<(), ServerFnError> {
+    use crate::server::db::user::User;
+
+    let admin_id = server::get_admin_id(cx).await?;
+    let pool = crate::server::get_pool(cx).await?;
+    if User::ban_from_user_id(&user_id, &pool).await? == 0 {
+        return Err(server::user_not_found(&user_id));
+    }
+    server::audit(cx, &admin_id, "ban_user", &user_id, None).await
+}
+
+#[server(TriageReport, "/api")]
+pub async fn triage_report(
+    cx: Scope,
+    report_id: i32,
+    resolution: ReportResolution,
+) -> Result<(), ServerFnError> {
+    use crate::server::db::report::Report;
+
+    let admin_id = server::get_admin_id(cx).await?;
+    let pool = crate::server::get_pool(cx).await?;
+    let status = server::report_status(resolution);
+    if Report::triage(report_id, status, &admin_id, &pool).await? == 0 {
+        return Err(ServerFnError::ServerError(format!(
+            "report {} is not open.",
+            report_id
+        )));
+    }
+    server::audit(
+        cx,
+        &admin_id,
+        "triage_report",
+        &report_id.to_string(),
+        Some(status),
+    )
+    .await
+}
+
+#[server(ResetUsername, "/api")]
+pub async fn reset_username(cx: Scope, user_id: String) -> Result<String, ServerFnError> {
+    use crate::server::db::user::User;
+
+    let admin_id = server::get_admin_id(cx).await?;
+    let pool = crate::server::get_pool(cx).await?;
+    let username = User::generate_username();
+    if User::update_username_from_user_id(&user_id, &username, &pool).await? == 0 {
+        return Err(server::user_not_found(&user_id));
+    }
+    server::audit(cx, &admin_id, "reset_username", &user_id, Some(&username)).await?;
+    Ok(username)
+}
@@ -0,0 +1,98 @@
+use std::collections::BTreeSet;
+
+use leptos::ServerFnError;
+use leptos_actix::extract;
+use tracing::info;
+
+use super::{AdminOverview, AuditSummary, ReportResolution, ReportSummary};
+use crate::server::{
+    auth::user_id::AdminOnly,
+    board::server::get_games,
+    db::{
+        audit::AuditEntry,
+        report::{Report, STATUS_DISMISSED, STATUS_RESOLVED},
+    },
+    get_pool,
+};
+
+const RECENT_ENTRIES: i64 = 50;
+
+pub async fn get_admin_id(cx: leptos::Scope) -> Result<String, ServerFnError> {
+    extract(cx, |admin: AdminOnly| async move { admin.0 }).await
+}
+
+/// Write an admin action to the audit log.
+pub async fn audit(
+    cx: leptos::Scope,
+    admin_id: &str,
+    action: &str,
+    target: &str,
+    details: Option<&str>,
+) -> Result<(), ServerFnError> {
+    let pool = get_pool(cx).await?;
+    AuditEntry::record(admin_id, action, target, details, &pool).await?;
+    info!(admin_id, action, target, details, "admin action");
+    Ok(())
+}
+
+/// Error of the moderation actions given an unknown user id, checked before they are audited.
+pub fn user_not_found(user_id: &str) -> ServerFnError {
+    ServerFnError::ServerError(format!("no user with id {}.", user_id))
+}
+
+pub fn report_status(resolution: ReportResolution) -> &'static str {
+    match resolution {
+        ReportResolution::Resolved => STATUS_RESOLVED,
+        ReportResolution::Dismissed => STATUS_DISMISSED,
+    }
+}
+
+impl From<Report> for ReportSummary {
+    fn from(report: Report) -> Self {
+        ReportSummary {
+            id: report.id,
+            reporter_id: report.reporter_id,
+            reported_id: report.reported_id,
+            game_id: report.game_id,
+            reason: report.reason,
+            comment: report.comment,
+            created_at: report.created_at.to_rfc3339(),
+        }
+    }
+}
+
+impl From<AuditEntry> for AuditSummary {
+    fn from(entry: AuditEntry) -> Self {
+        AuditSummary {
+            id: entry.id,
+            admin_user_id: entry.admin_user_id,
+            action: entry.action,
+            target: entry.target,
+            details: entry.details,
+            created_at: entry.created_at.to_rfc3339(),
+        }
+    }
+}
+
+pub async fn get_overview(cx: leptos::Scope) -> Result<AdminOverview, ServerFnError> {
+    let games = get_games(cx).await?;
+    let pool = get_pool(cx).await?;
+
+    let games = games.summaries().await;
+    let active_players = games
+        .iter()
+        .flat_map(|game| game.players.iter().cloned())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect();
+
+    let reports = Report::get_queue(RECENT_ENTRIES, &pool).await?;
+    let audit_log = AuditEntry::get_recent(RECENT_ENTRIES, &pool).await?;
+
+    Ok(AdminOverview {
+        games,
+        active_players,
+        reports: reports.into_iter().map(Into::into).collect(),
+        audit_log: audit_log.into_iter().map(Into::into).collect(),
+    })
+}
@@ -0,0 +1,142 @@
+use actix_web::{delete, get, post, web, HttpResponse};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use sqlx::PgPool;
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::server::{
+    auth::admin_token::AdminToken,
+    board::server::{GameError, Games},
+    db::{
+        audit::{AuditEntry, ADMIN_API_ACTOR},
+        correspondence_game::CorrespondenceGame,
+        game_record::ArchivedGame,
+        game_snapshot::GameSnapshot,
+    },
+};
+
+async fn audit(pool: &PgPool, action: &str, game_id: Uuid) {
+    let target = game_id.to_string();
+    if let Err(err) = AuditEntry::record(ADMIN_API_ACTOR, action, &target, None, pool).await {
+        warn!(error = %err, action, target, "could not write audit log");
+    }
+}
+
+/// Correspondence games both players left are only in the database, load them to end them.
+async fn load_correspondence_game(games: &Games, pool: &PgPool, game_id: Uuid) {
+    if let Err(err) = games.load_correspondence_game(game_id, pool).await {
+        warn!(%game_id, error = %err, "could not load correspondence game");
+    }
+}
+
+async fn delete_correspondence_game(pool: &PgPool, game_id: Uuid) {
+    if let Err(err) = CorrespondenceGame::delete(game_id, pool).await {
+        warn!(%game_id, error = %err, "could not delete correspondence game");
+    }
+}
+
+#[get("/games")]
+async fn list_games(_admin: AdminToken, games: web::Data<Games>) -> HttpResponse {
+    HttpResponse::Ok().json(games.summaries().await)
+}
+
+#[get("/games/export")]
+async fn export_games(_admin: AdminToken, games: web::Data<Games>) -> HttpResponse {
+    HttpResponse::Ok().json(games.snapshot_games().await)
+}
+
+/// Bounds of the archive export, on the time the games ended.
+#[derive(Debug, Deserialize)]
+struct ArchiveRange {
+    from: Option<DateTime<Utc>>,
+    to: Option<DateTime<Utc>>,
+}
+
+#[get("/games/archive")]
+async fn export_archived_games(
+    _admin: AdminToken,
+    pool: web::Data<PgPool>,
+    range: web::Query<ArchiveRange>,
+) -> actix_web::Result<HttpResponse> {
+    let games = ArchivedGame::get_ended_between(range.from, range.to, &pool)
+        .await
+        .map_err(|err| {
+            warn!(error = %err, "could not load game records");
+            actix_web::error::ErrorInternalServerError(err)
+        })?;
+    Ok(HttpResponse::Ok().json(games))
+}
+
+#[get("/games/archive/{game_id}")]
+async fn export_archived_game(
+    _admin: AdminToken,
+    pool: web::Data<PgPool>,
+    game_id: web::Path<Uuid>,
+) -> actix_web::Result<HttpResponse> {
+    let game_id = game_id.into_inner();
+    let game = ArchivedGame::get(game_id, &pool).await.map_err(|err| {
+        warn!(%game_id, error = %err, "could not load game record");
+        actix_web::error::ErrorInternalServerError(err)
+    })?;
+    let game = game.ok_or(GameError::InvalidGameId(game_id))?;
+    Ok(HttpResponse::Ok().json(game))
+}
+
+#[get("/games/{game_id}")]
+async fn export_game(
+    _admin: AdminToken,
+    games: web::Data<Games>,
+    game_id: web::Path<Uuid>,
+) -> Result<HttpResponse, GameError> {
+    let snapshot = games.snapshot_game(game_id.into_inner()).await?;
+    Ok(HttpResponse::Ok().json(snapshot))
+}
+
+#[post("/games/{game_id}/end")]
+async fn end_game(
+    _admin: AdminToken,
+    games: web::Data<Games>,
+    pool: web::Data<PgPool>,
+    game_id: web::Path<Uuid>,
+) -> Result<HttpResponse, GameError> {
+    let game_id = game_id.into_inner();
+    load_correspondence_game(&games, &pool, game_id).await;
+    if let Some(record) = games.remove_game(game_id, true).await? {
+        delete_correspondence_game(&pool, game_id).await;
+        if let Err(err) = record.insert(&pool).await {
+            warn!(%game_id, error = %err, "could not save game record");
+        }
+    }
+    audit(&pool, "terminate_game", game_id).await;
+    Ok(HttpResponse::NoContent().finish())
+}
+
+#[delete("/games/{game_id}")]
+async fn delete_game(
+    _admin: AdminToken,
+    games: web::Data<Games>,
+    pool: web::Data<PgPool>,
+    game_id: web::Path<Uuid>,
+) -> Result<HttpResponse, GameError> {
+    let game_id = game_id.into_inner();
+    load_correspondence_game(&games, &pool, game_id).await;
+    games.remove_game(game_id, false).await?;
+    delete_correspondence_game(&pool, game_id).await;
+    if let Err(err) = GameSnapshot::delete(game_id, &pool).await {
+        warn!(%game_id, error = %err, "could not delete game snapshot");
+    }
+    audit(&pool, "delete_game", game_id).await;
+    Ok(HttpResponse::NoContent().finish())
+}
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(list_games)
+        .service(export_games)
+        // before `export_game`, whose path would take `archive` as a game id
+        .service(export_archived_games)
+        .service(export_archived_game)
+        .service(export_game)
+        .service(end_game)
+        .service(delete_game);
+}
@@ -0,0 +1,78 @@
+use std::fmt::Display;
+
+use actix_web::{http::header, web, FromRequest, HttpRequest, ResponseError};
+use futures::future::{ready, Ready};
+use reqwest::StatusCode;
+use tracing::warn;
+
+use crate::server::config::Config;
+
+/// Extractor for operator endpoints, checks the `Authorization: Bearer` header
+/// against the admin token of the config.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AdminToken;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AdminTokenError {
+    Disabled,
+    Invalid,
+}
+
+impl Display for AdminTokenError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AdminTokenError::Disabled => f.write_str("not found."),
+            AdminTokenError::Invalid => f.write_str("invalid admin token."),
+        }
+    }
+}
+
+impl std::error::Error for AdminTokenError {}
+
+impl ResponseError for AdminTokenError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            AdminTokenError::Disabled => StatusCode::NOT_FOUND,
+            AdminTokenError::Invalid => StatusCode::UNAUTHORIZED,
+        }
+    }
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (a, b)| acc | (a ^ b)) == 0
+}
+
+fn check_token(req: &HttpRequest) -> Result<AdminToken, AdminTokenError> {
+    let expected = req
+        .app_data::<web::Data<Config>>()
+        .and_then(|config| config.server.admin_token.clone())
+        .ok_or(AdminTokenError::Disabled)?;
+
+    let token = req
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .ok_or(AdminTokenError::Invalid)?;
+
+    if constant_time_eq(token.as_bytes(), expected.as_bytes()) {
+        Ok(AdminToken)
+    } else {
+        warn!(
+            path = req.path(),
+            "rejected request with invalid admin token"
+        );
+        Err(AdminTokenError::Invalid)
+    }
+}
+
+impl FromRequest for AdminToken {
+    type Error = AdminTokenError;
+
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    #[inline]
+    fn from_request(req: &HttpRequest, _payload: &mut actix_web::dev::Payload) -> Self::Future {
+        ready(check_token(req))
+    }
+}
@@ -4,19 +4,20 @@ use oauth2::{
     basic::BasicClient, url::ParseError, AuthUrl, ClientId, ClientSecret, CsrfToken,
     PkceCodeChallenge, PkceCodeVerifier, RedirectUrl, Scope, TokenUrl,
 };
-use reqwest::header::{HeaderMap, InvalidHeaderValue};
+use reqwest::header::InvalidHeaderValue;
 use reqwest::Url;
-use std::{
-    env::{self, VarError},
-    fmt::Display,
-    ops::Deref,
-};
+use std::{fmt::Display, ops::Deref};
 
 use serde::Deserialize;
+use tracing::instrument;
+
+use crate::server::config::AuthConfig;
 
 #[derive(Debug, Clone)]
 pub struct AuthClient {
     client: BasicClient,
+    /// Shared by the userinfo and health check requests, with the configured timeout.
+    http_client: reqwest::Client,
     authority: String,
 }
 
@@ -26,9 +27,7 @@ pub enum AuthClientInitError {
     InvalidTokenUrl(ParseError),
     InvalidRedirectUrl(ParseError),
     InvalidRevocationUrl(ParseError),
-    MissingClientId(VarError),
-    MissingClientSecret(VarError),
-    MissingAuthority(VarError),
+    HttpClient(String),
 }
 
 impl Display for AuthClientInitError {
@@ -42,11 +41,7 @@ impl Display for AuthClientInitError {
             AuthClientInitError::InvalidRevocationUrl(err) => {
                 write!(f, "invalid revocation url: {}", err)
             }
-            AuthClientInitError::MissingClientId(err) => write!(f, "missing client id: {}", err),
-            AuthClientInitError::MissingClientSecret(err) => {
-                write!(f, "missing client secret: {}", err)
-            }
-            AuthClientInitError::MissingAuthority(err) => write!(f, "missing authority: {}", err),
+            AuthClientInitError::HttpClient(err) => write!(f, "invalid http client: {}", err),
         }
     }
 }
@@ -94,11 +89,18 @@ impl AuthClient {
         self.client
     }
 
-    pub fn new() -> Result<Self, AuthClientInitError> {
-        let authority = env::var("AUTHORITY").map_err(AuthClientInitError::MissingAuthority)?;
-        let client = create_client(&authority)?;
-
-        Ok(AuthClient { client, authority })
+    pub fn new(config: &AuthConfig) -> Result<Self, AuthClientInitError> {
+        let client = create_client(config)?;
+        let http_client = reqwest::Client::builder()
+            .timeout(config.request_timeout())
+            .build()
+            .map_err(|err| AuthClientInitError::HttpClient(err.to_string()))?;
+
+        Ok(AuthClient {
+            client,
+            http_client,
+            authority: config.authority.clone(),
+        })
     }
 
     pub fn revoke(&self, return_to: Option<&str>) -> HttpResponseBuilder {
@@ -121,23 +123,30 @@ impl AuthClient {
         res
     }
 
+    #[instrument(skip_all, err(Display))]
     pub async fn get_id(&self, token: &str) -> Result<String, UserIdError> {
-        let mut headers = HeaderMap::new();
-        headers.insert(
-            header::AUTHORIZATION,
-            header::HeaderValue::from_str(&format!("Bearer {}", token))?,
-        );
-        let client = reqwest::ClientBuilder::new()
-            .default_headers(headers)
-            .build()?;
-
+        let authorization = header::HeaderValue::from_str(&format!("Bearer {}", token))?;
         let url = format!("{}/userinfo", self.authority);
 
-        let res: UserInfoResponse = client.get(url).send().await?.json().await?;
+        let res: UserInfoResponse = self
+            .http_client
+            .get(url)
+            .header(header::AUTHORIZATION, authorization)
+            .send()
+            .await?
+            .json()
+            .await?;
 
         Ok(res.sub)
     }
 
+    /// Check that the auth provider answers, used by the readiness probe.
+    pub async fn check_reachable(&self) -> Result<(), reqwest::Error> {
+        let url = format!("{}/.well-known/openid-configuration", self.authority);
+        self.http_client.get(url).send().await?.error_for_status()?;
+        Ok(())
+    }
+
     pub fn get_auth_url(
         &self,
         redirect_url: RedirectUrl,
@@ -156,6 +165,7 @@ impl AuthClient {
         (authorize_url, csrf_token, pkce_verifier)
     }
 
+    #[instrument(skip_all, err(Display))]
     pub async fn get_token_from_code(
         &self,
         code: oauth2::AuthorizationCode,
@@ -192,12 +202,10 @@ impl Deref for AuthClient {
     }
 }
 
-fn create_client(authority: &str) -> Result<BasicClient, AuthClientInitError> {
-    let client_id =
-        ClientId::new(env::var("AUTH0_CLIENT_ID").map_err(AuthClientInitError::MissingClientId)?);
-    let client_secret = ClientSecret::new(
-        env::var("AUTH0_CLIENT_SECRET").map_err(AuthClientInitError::MissingClientSecret)?,
-    );
+fn create_client(config: &AuthConfig) -> Result<BasicClient, AuthClientInitError> {
+    let authority = &config.authority;
+    let client_id = ClientId::new(config.client_id.clone());
+    let client_secret = ClientSecret::new(config.client_secret.clone());
 
     let auth_url = AuthUrl::new(format!("{}/authorize", authority))
         .map_err(AuthClientInitError::InvalidAuthUrl)?;
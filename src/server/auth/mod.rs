@@ -10,13 +10,15 @@ use actix_web::{
     *,
 };
 use oauth2::{AuthorizationCode, CsrfToken, RedirectUrl, TokenResponse};
+pub mod admin_token;
 pub mod auth_client;
 pub mod user_id;
 use auth_client::AuthClient;
 use reqwest::header::HeaderValue;
 use serde::Deserialize;
+use tracing::{info, instrument, warn};
 
-use crate::server::BaseUrl;
+use crate::server::{db::user::User, BaseUrl};
 
 use self::user_id::MaybeUserId;
 
@@ -41,6 +43,7 @@ fn get_redirect_uri(origin: Option<&str>, base_url: &str) -> RedirectUrl {
 }
 
 #[get("/login")]
+#[instrument(skip_all, err(Display))]
 async fn login(
     client: web::Data<AuthClient>,
     base_url: web::Data<BaseUrl>,
@@ -91,6 +94,7 @@ fn append_session_token_cookie<'b>(
 }
 
 #[get("/logout")]
+#[instrument(skip_all, err(Display))]
 async fn logout(
     client: web::Data<AuthClient>,
     base_url: web::Data<BaseUrl>,
@@ -131,10 +135,12 @@ impl Display for CallbackError {
 impl std::error::Error for CallbackError {}
 
 #[get("/callback")]
+#[instrument(skip_all, err(Display))]
 async fn login_callback(
     session: Session,
     client: web::Data<AuthClient>,
     base_url: web::Data<BaseUrl>,
+    pool: web::Data<sqlx::PgPool>,
     params: web::Query<AuthRequest>,
     origin: web::Query<LoginParams>,
 ) -> Result<HttpResponse, Box<dyn std::error::Error>> {
@@ -161,7 +167,16 @@ async fn login_callback(
         .get_token_from_code(code, pkce_verifier, redirect_url)
         .await?;
 
-    // let id = client.get_id(token.access_token().secret()).await?;
+    // creating the user row is best effort, an unreachable userinfo endpoint or database
+    // must not prevent the login
+    match client.get_id(token.access_token().secret()).await {
+        Ok(id) => {
+            if let Err(err) = User::ensure_exists(&id, &pool).await {
+                warn!(error = %err, "could not create user");
+            }
+        }
+        Err(err) => warn!(error = %err, "could not get user id"),
+    }
 
     let max_age = token
         .expires_in()
@@ -171,6 +186,8 @@ async fn login_callback(
 
     let return_to = origin.origin.as_deref().unwrap_or("/");
 
+    info!("user logged in");
+
     let mut builder = HttpResponse::Found();
 
     Ok(
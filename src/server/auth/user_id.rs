@@ -6,19 +6,31 @@ use std::task::Poll;
 
 use actix_session::Session;
 use actix_web::{http::header, web, FromRequest, HttpResponse, ResponseError};
+use tracing::warn;
 
 use crate::server::auth::COOKIE_TOKEN_KEY;
+use crate::server::db::user::User;
 
 use super::auth_client::AuthClient;
 
 const SESSION_USER_ID: &str = "session_user_id_key";
 
+/// Id of the user cached in the session by a previous `MaybeUserId` extraction, without asking
+/// the auth server.
+pub fn session_user_id(session: &Session) -> Option<String> {
+    session.get::<String>(SESSION_USER_ID).ok().flatten()
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct MaybeUserId(pub Option<String>);
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct UserId(pub String);
 
+/// Id of a logged in user with the admin role.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AdminOnly(pub String);
+
 #[derive(Debug)]
 pub enum Impossible {}
 
@@ -84,7 +96,7 @@ impl FromRequest for MaybeUserId {
 
         let session = Session::extract(req).into_inner().unwrap();
 
-        if let Some(user_id) = session.get::<String>(SESSION_USER_ID).ok().flatten() {
+        if let Some(user_id) = session_user_id(&session) {
             return MaybeFuture::new_value(Ok(MaybeUserId(Some(user_id))));
         }
 
@@ -97,7 +109,9 @@ impl FromRequest for MaybeUserId {
 
         let fut = async move {
             let id = client.get_id(&token).await?;
-            session.insert(SESSION_USER_ID, &id).ok();
+            if let Err(err) = session.insert(SESSION_USER_ID, &id) {
+                warn!(error = %err, "could not store user id in session");
+            }
             Ok(MaybeUserId(Some(id)))
         };
 
@@ -139,6 +153,56 @@ impl FromRequest for UserId {
     }
 }
 
+impl FromRequest for AdminOnly {
+    type Error = actix_web::Error;
+
+    type Future = MaybeFuture<Result<Self, Self::Error>>;
+
+    #[inline]
+    fn from_request(
+        req: &actix_web::HttpRequest,
+        payload: &mut actix_web::dev::Payload,
+    ) -> Self::Future {
+        let user_id = MaybeUserId::from_request(req, payload);
+
+        let pool = match web::Data::<sqlx::PgPool>::from_request(req, payload).into_inner() {
+            Ok(pool) => pool,
+            Err(err) => return MaybeFuture::new_value(Err(err)),
+        };
+
+        let fut = async move {
+            let Some(user_id) = user_id.await?.0 else {
+                return Err(NotAdmin.into());
+            };
+            match User::is_admin_from_user_id(&user_id, &pool).await {
+                Ok(true) => Ok(AdminOnly(user_id)),
+                Ok(false) => {
+                    warn!(%user_id, "non admin user tried to access an admin resource");
+                    Err(NotAdmin.into())
+                }
+                Err(err) => Err(actix_web::error::ErrorInternalServerError(err)),
+            }
+        };
+
+        MaybeFuture::new_future(fut)
+    }
+}
+
+#[derive(Debug)]
+pub struct NotAdmin;
+
+impl Display for NotAdmin {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "admin access required.")
+    }
+}
+
+impl ResponseError for NotAdmin {
+    fn status_code(&self) -> reqwest::StatusCode {
+        reqwest::StatusCode::FORBIDDEN
+    }
+}
+
 #[derive(Debug)]
 pub struct RedirectToLogin {
     redirect_to: String,
@@ -0,0 +1,151 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::time::Instant;
+
+use crate::server::config::ChatConfig;
+
+use super::{ChatError, ChatMessage};
+
+/// Hook deciding what happens to a chat message before it is broadcast.
+pub trait ChatFilter: Send + Sync {
+    /// Return the text to broadcast, or `None` to reject the message.
+    fn filter(&self, text: &str) -> Option<String>;
+}
+
+/// Default filter, masks the configured words with `*`.
+pub struct WordListFilter {
+    words: HashSet<String>,
+}
+
+impl WordListFilter {
+    pub fn new(words: &[String]) -> Self {
+        WordListFilter {
+            words: words.iter().map(|word| word.to_lowercase()).collect(),
+        }
+    }
+
+    fn push_word(&self, word: &mut String, out: &mut String) {
+        if self.words.contains(&word.to_lowercase()) {
+            out.extend(std::iter::repeat('*').take(word.chars().count()));
+        } else {
+            out.push_str(word);
+        }
+        word.clear();
+    }
+}
+
+impl ChatFilter for WordListFilter {
+    fn filter(&self, text: &str) -> Option<String> {
+        if self.words.is_empty() {
+            return Some(text.to_owned());
+        }
+        let mut out = String::with_capacity(text.len());
+        let mut word = String::new();
+        for c in text.chars() {
+            if c.is_alphanumeric() {
+                word.push(c);
+            } else {
+                self.push_word(&mut word, &mut out);
+                out.push(c);
+            }
+        }
+        self.push_word(&mut word, &mut out);
+        Some(out)
+    }
+}
+
+/// Limits and filter shared by the chat of every game.
+pub struct ChatSettings {
+    config: ChatConfig,
+    filter: Box<dyn ChatFilter>,
+}
+
+impl ChatSettings {
+    pub fn new(config: ChatConfig) -> Self {
+        let filter = Box::new(WordListFilter::new(&config.banned_words));
+        ChatSettings { config, filter }
+    }
+
+    /// Replace the default word list filter.
+    pub fn with_filter(mut self, filter: impl ChatFilter + 'static) -> Self {
+        self.filter = Box::new(filter);
+        self
+    }
+
+    fn prepare(&self, text: &str) -> Result<String, ChatError> {
+        let text = text.trim();
+        if text.is_empty() {
+            return Err(ChatError::Empty);
+        }
+        if text.chars().count() > self.config.max_message_len {
+            return Err(ChatError::TooLong {
+                max: self.config.max_message_len,
+            });
+        }
+        self.filter.filter(text).ok_or(ChatError::Rejected)
+    }
+}
+
+/// Chat state of a game: the transcript, the players who muted it and the recent messages of
+/// each user for rate limiting.
+#[derive(Default)]
+pub struct Chat {
+    transcript: Vec<ChatMessage>,
+    muted: HashSet<String>,
+    recent: HashMap<String, VecDeque<Instant>>,
+}
+
+impl Chat {
+    pub fn from_transcript(transcript: Vec<ChatMessage>) -> Self {
+        Chat {
+            transcript,
+            ..Default::default()
+        }
+    }
+
+    pub fn transcript(&self) -> &[ChatMessage] {
+        &self.transcript
+    }
+
+    pub fn is_muted(&self, user_id: &str) -> bool {
+        self.muted.contains(user_id)
+    }
+
+    pub fn set_muted(&mut self, user_id: String, muted: bool) {
+        if muted {
+            self.muted.insert(user_id);
+        } else {
+            self.muted.remove(&user_id);
+        }
+    }
+
+    fn check_rate(&mut self, user_id: &str, config: &ChatConfig) -> Result<(), ChatError> {
+        let now = Instant::now();
+        let window = config.rate_limit_window();
+        let recent = self.recent.entry(user_id.to_owned()).or_default();
+        while recent
+            .front()
+            .is_some_and(|sent_at| now.duration_since(*sent_at) >= window)
+        {
+            recent.pop_front();
+        }
+        if recent.len() >= config.rate_limit_messages {
+            return Err(ChatError::RateLimited);
+        }
+        recent.push_back(now);
+        Ok(())
+    }
+
+    /// Validate a message from `user_id` and add it to the transcript.
+    pub fn post(
+        &mut self,
+        user_id: &str,
+        message: ChatMessage,
+        settings: &ChatSettings,
+    ) -> Result<ChatMessage, ChatError> {
+        let text = settings.prepare(&message.text)?;
+        self.check_rate(user_id, &settings.config)?;
+        let message = ChatMessage { text, ..message };
+        self.transcript.push(message.clone());
+        Ok(message)
+    }
+}
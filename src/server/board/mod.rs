@@ -8,9 +8,50 @@ use leptos::*;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+#[cfg(feature = "ssr")]
+pub mod chat;
 #[cfg(feature = "ssr")]
 pub mod server;
 
+/// Who wrote a chat message, players talk in their own room and spectators in another one.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ChatAuthor {
+    Player(Color),
+    Spectator(String),
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct ChatMessage {
+    pub author: ChatAuthor,
+    pub text: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum ChatError {
+    Empty,
+    TooLong { max: usize },
+    RateLimited,
+    Rejected,
+    Banned,
+}
+
+impl std::fmt::Display for ChatError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ChatError::Empty => f.write_str("message is empty."),
+            ChatError::TooLong { max } => {
+                write!(f, "message is longer than {} characters.", max)
+            }
+            ChatError::RateLimited => f.write_str("too many messages, slow down."),
+            ChatError::Rejected => f.write_str("message was rejected by the chat filter."),
+            ChatError::Banned => f.write_str("you are banned from the chat."),
+        }
+    }
+}
+
+impl std::error::Error for ChatError {}
+
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
 pub enum GameEvent {
     WaitingForOpponent,
@@ -25,6 +66,7 @@ pub enum GameEvent {
         game_id: Uuid,
         player_color: Color,
         board: Option<Board>,
+        chat: Vec<ChatMessage>,
     },
     OpponentPlayedMove {
         from: HexVector,
@@ -32,6 +74,31 @@ pub enum GameEvent {
         promote_to: Option<PieceKind>,
     },
     OpponentDisconnected,
+    /// Sent to a user who joined a game they don't play in, they receive the moves of both
+    /// players as `OpponentPlayedMove`.
+    Spectating {
+        game_id: Uuid,
+        board: Option<Board>,
+    },
+    /// The server is restarting, the game is saved and can be rejoined once it is back up.
+    ServerShuttingDown,
+    /// The game was ended by an operator.
+    GameTerminated,
+    /// The correspondence game ended, `loser` did not move before the deadline.
+    TimedOut {
+        loser: Color,
+    },
+    /// Nobody joined in time: the custom game link or the wait for a random opponent expired.
+    InvitationExpired,
+    /// The challenged player turned the challenge down.
+    ChallengeDeclined,
+    ChatMessage(ChatMessage),
+    /// Event of one board of a simul, the host receives the events of all their boards on a
+    /// single stream.
+    SimulBoard {
+        game_id: Uuid,
+        event: Box<GameEvent>,
+    },
 }
 
 impl GameEvent {
@@ -43,6 +110,44 @@ impl GameEvent {
     }
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum GameSummaryKind {
+    Started,
+    Custom,
+    Random,
+}
+
+/// Overview of a live game, or of a player waiting for one, as shown to operators.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct GameSummary {
+    pub game_id: Option<Uuid>,
+    pub kind: GameSummaryKind,
+    pub players: Vec<String>,
+    pub player_turn: Option<Color>,
+    pub spectators: usize,
+}
+
+/// How often clients poll the list of their games.
+pub const MY_GAMES_REFRESH: std::time::Duration = std::time::Duration::from_secs(10);
+
+/// A live game of the current user, as listed on the games page.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub struct PlayerGame {
+    pub game_id: Uuid,
+    pub color: Color,
+    pub player_turn: Color,
+    pub is_ended: bool,
+    /// Set for correspondence games.
+    pub days_per_move: Option<u32>,
+}
+
+impl PlayerGame {
+    pub fn is_my_turn(&self) -> bool {
+        !self.is_ended && self.color == self.player_turn
+    }
+}
+
 #[cfg(feature = "ssr")]
 impl From<&GameEvent> for actix_web_lab::sse::Event {
     fn from(val: &GameEvent) -> Self {
@@ -58,12 +163,101 @@ pub async fn play_move(
     to: HexVector,
     promote_to: Option<PieceKind>,
 ) -> Result<Result<Option<CanPromoteMove>, IllegalMove>, ServerFnError> {
-    let game = server::GAMES.get_game_with_id(game_id).await?;
+    let games = server::get_games(cx).await?;
+    let game = games.get_game_with_id(game_id).await?;
+
+    let player_id = server::get_unbanned_player_id(cx).await?;
+
+    let mut game = game.lock().await;
+    let res = game.play_move(player_id, from, to, promote_to).await?;
+    if matches!(res, Ok(None)) {
+        let follow_up = game.move_follow_up();
+        drop(game);
+        server::follow_up_move(cx, follow_up).await;
+    }
+    Ok(res)
+}
+
+/// The live and correspondence games of the current user, the ones waiting for their move
+/// first.
+#[server(GetMyGames, "/api")]
+pub async fn get_my_games(cx: Scope) -> Result<Vec<PlayerGame>, ServerFnError> {
+    let player_id = server::get_player_id(cx).await?;
+    server::get_player_games(cx, &player_id).await
+}
+
+/// A custom game waiting for the current user to accept or decline it.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct Challenge {
+    pub game_id: Uuid,
+    pub challenger: String,
+    /// Set for correspondence games.
+    pub days_per_move: Option<u32>,
+}
+
+#[server(GetChallenge, "/api")]
+pub async fn get_challenge(cx: Scope, game_id: Uuid) -> Result<Challenge, ServerFnError> {
+    let player_id = server::get_player_id(cx).await?;
+    server::get_challenge(cx, game_id, &player_id).await
+}
+
+/// A game that is over, as archived once it left the game server.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GameReplay {
+    pub game_id: Uuid,
+    pub white: String,
+    pub black: String,
+    /// Final position, with every move of the game in its history.
+    pub board: Board,
+    /// Same as the outcome of the game record.
+    pub outcome: String,
+}
+
+/// Archived game, `None` while it is played or if it does not exist. Anyone can see it.
+#[server(GetGameReplay, "/api")]
+pub async fn get_game_replay(
+    cx: Scope,
+    game_id: Uuid,
+) -> Result<Option<GameReplay>, ServerFnError> {
+    server::get_game_replay(cx, game_id).await
+}
+
+#[server(DeclineChallenge, "/api")]
+pub async fn decline_challenge(cx: Scope, game_id: Uuid) -> Result<(), ServerFnError> {
+    let games = server::get_games(cx).await?;
+    let player_id = server::get_player_id(cx).await?;
+    games.decline_challenge(game_id, &player_id).await?;
+    Ok(())
+}
+
+#[server(SendChatMessage, "/api")]
+pub async fn send_chat_message(
+    cx: Scope,
+    game_id: Uuid,
+    text: String,
+) -> Result<Result<(), ChatError>, ServerFnError> {
+    let games = server::get_games(cx).await?;
+    let game = games.get_game_with_id(game_id).await?;
+
+    let player_id = server::get_player_id(cx).await?;
+    let author = match server::get_chat_author(cx, &game, &player_id).await? {
+        Ok(author) => author,
+        Err(err) => return Ok(Err(err)),
+    };
+
+    let mut game = game.lock().await;
+    Ok(game
+        .post_chat_message(&player_id, author, text, games.chat_settings())
+        .await)
+}
+
+#[server(SetChatMuted, "/api")]
+pub async fn set_chat_muted(cx: Scope, game_id: Uuid, muted: bool) -> Result<(), ServerFnError> {
+    let games = server::get_games(cx).await?;
+    let game = games.get_game_with_id(game_id).await?;
 
     let player_id = server::get_player_id(cx).await?;
 
     let mut game = game.lock().await;
-    game.play_move(player_id, from, to, promote_to)
-        .await
-        .map_err(Into::into)
+    game.set_chat_muted(player_id, muted).map_err(Into::into)
 }
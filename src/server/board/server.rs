@@ -1,8 +1,9 @@
 use actix_web::*;
+use chrono::{DateTime, Utc};
 
 use futures::lock::Mutex;
 
-use hex_chess_core::board::Board;
+use hex_chess_core::board::{Board, GameEnd};
 use hex_chess_core::{
     hex_coord::HexVector,
     mov::{CanPromoteMove, IllegalMove},
@@ -13,34 +14,74 @@ use rand::seq::SliceRandom;
 
 use std::collections::{HashMap, HashSet};
 use std::fmt::Display;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
+use tracing::{debug, info, instrument, warn, Span};
 use uuid::Uuid;
 
 use crate::server::auth::user_id::MaybeUserId;
-
-use super::GameEvent;
+use crate::server::config::{ChatConfig, GamesConfig};
+use crate::server::db::block::Block;
+use crate::server::db::correspondence_game::CorrespondenceGame;
+use crate::server::db::game_record::GameRecord;
+use crate::server::db::game_snapshot::GameSnapshot;
+use crate::server::db::user::{User, DEFAULT_USERNAME};
+use crate::server::get_pool;
+use crate::server::metrics::{game_end_kind, Metrics};
+use crate::server::notifications::server::{get_notifier, spawn_notify, Notifier};
+use crate::server::notifications::NotificationKind;
+use crate::server::tournaments::server::{end_result, record_result};
+use crate::server::tournaments::PairingResult;
+
+use super::chat::{Chat, ChatSettings};
+use super::{
+    Challenge, ChatAuthor, ChatError, ChatMessage, GameEvent, GameReplay, GameSummary,
+    GameSummaryKind, PlayerGame,
+};
 
 use actix_web_lab::sse::{self, SendError};
 
-const TIMEOUT_DURATION: Duration = Duration::from_secs(2);
-
 pub struct Player {
     sender: sse::Sender,
     player_id: String,
+    /// Set for the host of a simul, their stream is shared by all their boards and the events
+    /// are wrapped in [`GameEvent::SimulBoard`].
+    simul_game: Option<Uuid>,
 }
 
-async fn test_connection(sender: &sse::Sender) -> bool {
+pub async fn test_connection(sender: &sse::Sender, timeout: Duration, metrics: &Metrics) -> bool {
     let future = async { sender.send(sse::Event::Comment("ping".into())).await };
 
-    actix_web::rt::time::timeout(TIMEOUT_DURATION, future)
-        .await
-        .is_ok_and(|r| r.is_ok())
+    let start = Instant::now();
+    let res = actix_web::rt::time::timeout(timeout, future).await;
+    metrics.ping_duration.observe(start.elapsed().as_secs_f64());
+
+    match res {
+        Ok(res) => res.is_ok(),
+        Err(_) => {
+            metrics.ping_timeouts.inc();
+            false
+        }
+    }
 }
 
 impl Player {
     pub fn new(sender: sse::Sender, player_id: String) -> Self {
-        Player { sender, player_id }
+        Player {
+            sender,
+            player_id,
+            simul_game: None,
+        }
+    }
+
+    /// Host of the simul board `game_id`, sharing the stream of their other boards.
+    pub fn simul_host(sender: sse::Sender, player_id: String, game_id: Uuid) -> Self {
+        Player {
+            sender,
+            player_id,
+            simul_game: Some(game_id),
+        }
     }
 
     pub fn new_with_stream(player_id: String) -> (Self, sse::Sse<sse::ChannelStream>) {
@@ -48,12 +89,30 @@ impl Player {
         (Self::new(sender, player_id), stream)
     }
 
-    pub async fn send<E: Into<sse::Event>>(&self, event: E) -> Result<(), SendError> {
-        self.sender.send(event).await
+    /// Create a player without any open stream, waiting for them to rejoin the game.
+    pub fn new_disconnected(player_id: String) -> Self {
+        Self::new_with_stream(player_id).0
+    }
+
+    pub async fn send(&self, event: &GameEvent) -> Result<(), SendError> {
+        match self.simul_game {
+            Some(game_id) => {
+                let event = GameEvent::SimulBoard {
+                    game_id,
+                    event: Box::new(event.clone()),
+                };
+                self.sender.send(&event).await
+            }
+            None => self.sender.send(event).await,
+        }
+    }
+
+    pub async fn is_connected(&self, timeout: Duration, metrics: &Metrics) -> bool {
+        test_connection(&self.sender, timeout, metrics).await
     }
 
-    pub async fn is_connected(&self) -> bool {
-        test_connection(&self.sender).await
+    pub fn player_id(&self) -> &str {
+        &self.player_id
     }
 
     pub fn has_id(&self, id: &str) -> bool {
@@ -61,12 +120,49 @@ impl Player {
     }
 }
 
+/// Time control of a correspondence game.
+#[derive(Debug, Clone, Copy)]
+struct Correspondence {
+    days_per_move: u32,
+    move_deadline: DateTime<Utc>,
+}
+
+impl Correspondence {
+    fn new(days_per_move: u32) -> Self {
+        let mut correspondence = Correspondence {
+            days_per_move,
+            move_deadline: Utc::now(),
+        };
+        correspondence.restart_clock();
+        correspondence
+    }
+
+    fn restart_clock(&mut self) {
+        self.move_deadline = Utc::now() + chrono::Duration::days(self.days_per_move.into());
+    }
+
+    fn is_timed_out(&self) -> bool {
+        self.move_deadline <= Utc::now()
+    }
+}
+
 pub struct Game {
     white_player: Player,
     black_player: Player,
     game_id: Uuid,
     board: Board,
     spectators: Vec<sse::Sender>,
+    ping_timeout: Duration,
+    metrics: web::Data<Metrics>,
+    connected_players: usize,
+    /// Restored games are kept alive until this deadline even if no player is connected.
+    resumable_until: Option<Instant>,
+    chat: Chat,
+    /// Set for correspondence games, they leave memory when both players disconnect and are
+    /// loaded back from the database when one of them rejoins.
+    correspondence: Option<Correspondence>,
+    /// Id of the opponent whose event stream missed the last move, they get a notification.
+    away_opponent: Option<String>,
 }
 
 impl Game {
@@ -91,27 +187,35 @@ impl Game {
     ) -> Result<Option<CanPromoteMove>, IllegalMove> {
         let res = self.board.play_move(from, to, promote_to)?;
         if res.is_none() {
+            self.metrics.moves_played.inc();
+            if let Some(correspondence) = &mut self.correspondence {
+                correspondence.restart_clock();
+            }
+            if let Some(end) = self.board.is_end() {
+                self.metrics.game_ended(game_end_kind(&end));
+            }
+
             let event = GameEvent::OpponentPlayedMove {
                 from,
                 to,
                 promote_to,
             };
             let opponent = match color {
-                Color::Black => &self.white_player.sender,
-                Color::White => &self.black_player.sender,
+                Color::Black => &self.white_player,
+                Color::White => &self.black_player,
             };
 
-            let futs = self
-                .spectators
-                .iter()
-                .chain(Some(opponent))
-                .map(|sender| sender.send(&event));
-
-            let _ = futures::future::join_all(futs).await; // don't care about result
+            let futs = self.spectators.iter().map(|sender| sender.send(&event));
+            let (opponent_res, _) = futures::join!(
+                opponent.send(&event),
+                futures::future::join_all(futs) // don't care about result
+            );
+            self.away_opponent = opponent_res.is_err().then(|| opponent.player_id.clone());
         }
         Ok(res)
     }
 
+    #[instrument(skip_all, fields(game_id = %self.game_id, player_id = %player_id))]
     pub async fn play_move(
         &mut self,
         player_id: String,
@@ -121,34 +225,64 @@ impl Game {
     ) -> Result<Result<Option<CanPromoteMove>, IllegalMove>, GameError> {
         let color = self.get_player_color(player_id)?;
         if color != self.board.get_player_turn() {
+            self.metrics.illegal_moves.inc();
+            debug!("move played out of turn");
             return Ok(Err(IllegalMove::NotYourTurn));
         }
-        Ok(self.play_move_inner(color, from, to, promote_to).await)
+        if self
+            .correspondence
+            .is_some_and(|correspondence| correspondence.is_timed_out())
+        {
+            debug!("move played after the deadline");
+            return Err(GameError::MoveTimedOut);
+        }
+        let start = Instant::now();
+        let res = self.play_move_inner(color, from, to, promote_to).await;
+        match &res {
+            Ok(_) => {
+                self.metrics
+                    .move_latency
+                    .observe(start.elapsed().as_secs_f64());
+                debug!(?color, ?from, ?to, "move played");
+            }
+            Err(err) => {
+                self.metrics.illegal_moves.inc();
+                debug!(?color, ?from, ?to, ?err, "illegal move rejected");
+            }
+        }
+        Ok(res)
     }
 
+    /// Start a game where `player1` plays white, if both players are still connected.
     pub async fn new(
         player1: Player,
         player2: Player,
         game_id: Uuid,
+        ping_timeout: Duration,
+        metrics: web::Data<Metrics>,
     ) -> Result<Self, Option<Player>> {
-        let mut players = [player1, player2];
-
-        let mut rng = rand::thread_rng();
-
-        players.shuffle(&mut rng);
-
-        let [player1, player2] = players;
-
         let player1_color = Color::White;
         let player2_color = Color::Black;
 
-        let res = futures::join!(player1.is_connected(), player2.is_connected());
+        let res = futures::join!(
+            player1.is_connected(ping_timeout, &metrics),
+            player2.is_connected(ping_timeout, &metrics)
+        );
 
         match res {
             (true, true) => {}
-            (true, false) => return Err(Some(player1)),
-            (false, true) => return Err(Some(player2)),
-            (false, false) => return Err(None),
+            (true, false) => {
+                debug!(player_id = %player2.player_id, "player disconnected before game start");
+                return Err(Some(player1));
+            }
+            (false, true) => {
+                debug!(player_id = %player1.player_id, "player disconnected before game start");
+                return Err(Some(player2));
+            }
+            (false, false) => {
+                debug!("both players disconnected before game start");
+                return Err(None);
+            }
         }
 
         let event_p1 = GameEvent::GameStart {
@@ -168,20 +302,227 @@ impl Game {
             game_id,
             board: Board::new(),
             spectators: vec![],
+            ping_timeout,
+            metrics,
+            connected_players: 2,
+            resumable_until: None,
+            chat: Chat::default(),
+            correspondence: None,
+            away_opponent: None,
         })
     }
 
+    /// Give each side `days_per_move` days per move, starting now.
+    pub fn with_correspondence(mut self, days_per_move: u32) -> Self {
+        self.correspondence = Some(Correspondence::new(days_per_move));
+        self
+    }
+
+    pub fn restore(
+        snapshot: GameSnapshot,
+        ping_timeout: Duration,
+        resume_grace: Duration,
+        metrics: web::Data<Metrics>,
+    ) -> Self {
+        Game {
+            white_player: Player::new_disconnected(snapshot.white_player_id),
+            black_player: Player::new_disconnected(snapshot.black_player_id),
+            game_id: snapshot.game_id,
+            board: snapshot.board.0,
+            spectators: vec![],
+            ping_timeout,
+            metrics,
+            connected_players: 0,
+            resumable_until: Some(Instant::now() + resume_grace),
+            chat: Chat::from_transcript(snapshot.chat.0),
+            correspondence: None,
+            away_opponent: None,
+        }
+    }
+
+    pub fn from_correspondence(
+        game: CorrespondenceGame,
+        ping_timeout: Duration,
+        metrics: web::Data<Metrics>,
+    ) -> Self {
+        Game {
+            white_player: Player::new_disconnected(game.white_player_id),
+            black_player: Player::new_disconnected(game.black_player_id),
+            game_id: game.game_id,
+            board: game.board.0,
+            spectators: vec![],
+            ping_timeout,
+            metrics,
+            connected_players: 0,
+            resumable_until: None,
+            chat: Chat::from_transcript(game.chat.0),
+            correspondence: Some(Correspondence {
+                days_per_move: game.days_per_move as u32,
+                move_deadline: game.move_deadline,
+            }),
+            away_opponent: None,
+        }
+    }
+
+    /// Saves and notifications left once a move is played.
+    pub fn move_follow_up(&self) -> MoveFollowUp {
+        let kind = if self.is_ended() {
+            NotificationKind::GameEnded
+        } else if self.correspondence.is_some() {
+            NotificationKind::CorrespondenceMove
+        } else {
+            NotificationKind::OpponentMoved
+        };
+        MoveFollowUp {
+            game_id: self.game_id,
+            is_ended: self.is_ended(),
+            correspondence: self.correspondence_game(),
+            away_opponent: self
+                .away_opponent
+                .clone()
+                .map(|opponent_id| (opponent_id, kind)),
+        }
+    }
+
+    /// The state to save in the database, for correspondence games only.
+    pub fn correspondence_game(&self) -> Option<CorrespondenceGame> {
+        self.correspondence
+            .map(|correspondence| CorrespondenceGame {
+                game_id: self.game_id,
+                white_player_id: self.white_player.player_id.clone(),
+                black_player_id: self.black_player.player_id.clone(),
+                board: sqlx::types::Json(self.board.clone()),
+                chat: sqlx::types::Json(self.chat.transcript().to_vec()),
+                days_per_move: correspondence.days_per_move as i32,
+                move_deadline: correspondence.move_deadline,
+            })
+    }
+
+    pub fn snapshot(&self) -> GameSnapshot {
+        GameSnapshot {
+            game_id: self.game_id,
+            white_player_id: self.white_player.player_id.clone(),
+            black_player_id: self.black_player.player_id.clone(),
+            board: sqlx::types::Json(self.board.clone()),
+            chat: sqlx::types::Json(self.chat.transcript().to_vec()),
+        }
+    }
+
+    /// How the game ended, for games removed from the server without a winner it is `abandoned`.
+    fn outcome(&self) -> &'static str {
+        self.board
+            .is_end()
+            .map_or("abandoned", |end| game_end_kind(&end))
+    }
+
+    pub fn record(&self, outcome: &str) -> GameRecord {
+        GameRecord {
+            game_id: self.game_id,
+            white_player_id: self.white_player.player_id.clone(),
+            black_player_id: self.black_player.player_id.clone(),
+            board: sqlx::types::Json(self.board.clone()),
+            chat: sqlx::types::Json(self.chat.transcript().to_vec()),
+            outcome: outcome.to_owned(),
+        }
+    }
+
+    pub fn summary(&self) -> GameSummary {
+        GameSummary {
+            game_id: Some(self.game_id),
+            kind: GameSummaryKind::Started,
+            players: vec![
+                self.white_player.player_id.clone(),
+                self.black_player.player_id.clone(),
+            ],
+            player_turn: Some(self.board.get_player_turn()),
+            spectators: self.spectators.len(),
+        }
+    }
+
+    pub fn player_game(&self, color: Color) -> PlayerGame {
+        PlayerGame {
+            game_id: self.game_id,
+            color,
+            player_turn: self.board.get_player_turn(),
+            is_ended: self.is_ended(),
+            days_per_move: self
+                .correspondence
+                .map(|correspondence| correspondence.days_per_move),
+        }
+    }
+
+    pub fn is_ended(&self) -> bool {
+        self.board.is_end().is_some()
+    }
+
+    async fn broadcast(&self, event: &GameEvent) {
+        let futs = self.spectators.iter().map(|sender| sender.send(event));
+
+        // don't care about result
+        let _ = futures::join!(
+            self.white_player.send(event),
+            self.black_player.send(event),
+            futures::future::join_all(futs)
+        );
+    }
+
+    /// Check and broadcast a chat message, player messages go to the players who did not mute
+    /// the chat and spectator messages to the spectators.
+    #[instrument(skip_all, fields(game_id = %self.game_id, player_id = %sender_id))]
+    pub async fn post_chat_message(
+        &mut self,
+        sender_id: &str,
+        author: ChatAuthor,
+        text: String,
+        settings: &ChatSettings,
+    ) -> Result<(), ChatError> {
+        let message = ChatMessage { author, text };
+        let message = match self.chat.post(sender_id, message, settings) {
+            Ok(message) => message,
+            Err(err) => {
+                debug!(%err, "chat message rejected");
+                return Err(err);
+            }
+        };
+        self.metrics.chat_messages.inc();
+
+        let is_player = matches!(message.author, ChatAuthor::Player(_));
+        let event = GameEvent::ChatMessage(message);
+        if is_player {
+            let futs = [&self.white_player, &self.black_player]
+                .into_iter()
+                .filter(|player| !self.chat.is_muted(&player.player_id))
+                .map(|player| player.send(&event));
+            let _ = futures::future::join_all(futs).await; // don't care about result
+        } else {
+            let futs = self.spectators.iter().map(|sender| sender.send(&event));
+            let _ = futures::future::join_all(futs).await; // don't care about result
+        }
+        Ok(())
+    }
+
+    pub fn set_chat_muted(&mut self, player_id: String, muted: bool) -> Result<(), GameError> {
+        self.get_player_color(player_id.clone())?;
+        self.chat.set_muted(player_id, muted);
+        Ok(())
+    }
+
     async fn remove_stale_specs(&mut self) {
-        let futs = self.spectators.iter().map(test_connection);
+        let futs = self
+            .spectators
+            .iter()
+            .map(|sender| test_connection(sender, self.ping_timeout, &self.metrics));
         let res = futures::future::join_all(futs).await;
 
         let mut iter = res.into_iter();
         self.spectators.retain(|_| iter.next().unwrap());
     }
 
+    #[instrument(skip_all, fields(game_id = %self.game_id))]
     pub async fn is_stale(&mut self) -> bool {
         // check if ended
-        if self.board.is_end().is_some() {
+        if let Some(end) = self.board.is_end() {
+            debug!(?end, "game ended");
             return true;
         }
 
@@ -190,12 +531,17 @@ impl Game {
 
         // check if all players are connected
         let res = futures::join!(
-            self.white_player.is_connected(),
-            self.black_player.is_connected()
+            self.white_player
+                .is_connected(self.ping_timeout, &self.metrics),
+            self.black_player
+                .is_connected(self.ping_timeout, &self.metrics)
         );
 
+        self.connected_players = usize::from(res.0) + usize::from(res.1);
+
         match res {
             (true, false) => {
+                debug!(player_id = %self.black_player.player_id, "player disconnected");
                 let _ = self
                     .white_player
                     .send(&GameEvent::OpponentDisconnected)
@@ -203,6 +549,7 @@ impl Game {
                 false
             }
             (false, true) => {
+                debug!(player_id = %self.white_player.player_id, "player disconnected");
                 let _ = self
                     .black_player
                     .send(&GameEvent::OpponentDisconnected)
@@ -210,9 +557,28 @@ impl Game {
                 false
             }
             (true, true) => false,
-            (false, false) => true,
+            (false, false) if self.correspondence.is_some() => {
+                debug!("both players left the correspondence game");
+                true
+            }
+            (false, false)
+                if self
+                    .resumable_until
+                    .is_some_and(|deadline| deadline > Instant::now()) =>
+            {
+                false
+            }
+            (false, false) => {
+                self.metrics.game_ended("abandoned");
+                true
+            }
         }
     }
+
+    /// Number of open event streams of this game, as of the last call to `is_stale`.
+    pub fn connection_count(&self) -> usize {
+        self.connected_players + self.spectators.len()
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -221,6 +587,16 @@ pub enum GameError {
     InvalidPlayerId { game_id: Uuid },
     PlayerNotLoggedIn,
     AllPlayerDisconnected,
+    ServerShuttingDown,
+    PlayerBanned,
+    BanCheckFailed,
+    BlockCheckFailed,
+    PlayerBlocked,
+    TooManyCustomGames,
+    InvalidDaysPerMove { max: u32 },
+    MoveTimedOut,
+    InvalidOpponent,
+    NotChallenged,
 }
 
 impl Display for GameError {
@@ -232,13 +608,42 @@ impl Display for GameError {
                 write!(f, "you are not a player of game {}", game_id)
             }
             GameError::AllPlayerDisconnected => f.write_str("All player disconnected."),
+            GameError::ServerShuttingDown => {
+                f.write_str("server is shutting down, try again in a few moments.")
+            }
+            GameError::PlayerBanned => f.write_str("you are banned from playing."),
+            GameError::BanCheckFailed => {
+                f.write_str("could not check your account, try again in a few moments.")
+            }
+            GameError::BlockCheckFailed => {
+                f.write_str("could not check your blocked players, try again in a few moments.")
+            }
+            GameError::PlayerBlocked => f.write_str("you can't play with this player."),
+            GameError::TooManyCustomGames => {
+                f.write_str("you have too many custom games waiting for an opponent.")
+            }
+            GameError::InvalidDaysPerMove { max } => {
+                write!(f, "days per move must be between 1 and {}.", max)
+            }
+            GameError::MoveTimedOut => f.write_str("the time to play this move ran out."),
+            GameError::InvalidOpponent => f.write_str("this player can't be challenged."),
+            GameError::NotChallenged => f.write_str("this game is reserved for another player."),
         }
     }
 }
 
 impl actix_web::ResponseError for GameError {
     fn status_code(&self) -> http::StatusCode {
-        http::StatusCode::BAD_REQUEST
+        match self {
+            GameError::ServerShuttingDown
+            | GameError::BanCheckFailed
+            | GameError::BlockCheckFailed => http::StatusCode::SERVICE_UNAVAILABLE,
+            GameError::PlayerBanned | GameError::PlayerBlocked | GameError::NotChallenged => {
+                http::StatusCode::FORBIDDEN
+            }
+            GameError::TooManyCustomGames => http::StatusCode::TOO_MANY_REQUESTS,
+            _ => http::StatusCode::BAD_REQUEST,
+        }
     }
 }
 
@@ -246,77 +651,539 @@ impl std::error::Error for GameError {}
 
 pub struct Games {
     games: Mutex<HashMap<Uuid, Arc<Mutex<Game>>>>,
-    custom_games: Mutex<HashMap<Uuid, Player>>,
-    waiting_room: Mutex<Vec<Player>>,
+    custom_games: Mutex<HashMap<Uuid, WaitingPlayer>>,
+    waiting_room: Mutex<Vec<WaitingPlayer>>,
+    /// Ids of the started games of each player.
+    player_games: Mutex<HashMap<String, HashSet<Uuid>>>,
+    config: GamesConfig,
+    chat: ChatSettings,
+    metrics: web::Data<Metrics>,
+    shutting_down: AtomicBool,
 }
 
-impl Default for Games {
-    fn default() -> Self {
-        Self::new()
-    }
+/// Games removed from memory by a sweep.
+#[derive(Default)]
+pub struct RemovedGames {
+    /// Records of the games that are over or abandoned.
+    pub records: Vec<GameRecord>,
+    /// Correspondence games both players left, to save until one of them comes back.
+    pub correspondence: Vec<CorrespondenceGame>,
 }
 
 pub enum GameState {
     Started(Arc<Mutex<Game>>),
-    Waiting(Player),
+    Waiting(WaitingPlayer),
 }
 
-impl Games {
-    pub fn new() -> Self {
-        actix_web::rt::spawn(remove_stale_games());
+/// A player waiting for an opponent, either as the creator of a custom game or in the waiting
+/// room.
+pub struct WaitingPlayer {
+    player: Player,
+    /// Kept when a custom game creator reconnects, the entry expires after its TTL regardless.
+    created_at: Instant,
+    /// Set for custom games that start as correspondence games.
+    days_per_move: Option<u32>,
+    /// Set for challenges, only this player can join the custom game.
+    opponent_id: Option<String>,
+}
+
+impl WaitingPlayer {
+    fn new(player: Player) -> Self {
+        WaitingPlayer {
+            player,
+            created_at: Instant::now(),
+            days_per_move: None,
+            opponent_id: None,
+        }
+    }
+
+    fn is_expired(&self, ttl: Duration) -> bool {
+        self.created_at.elapsed() >= ttl
+    }
+}
 
+impl Games {
+    pub fn new(config: GamesConfig, chat: ChatConfig, metrics: web::Data<Metrics>) -> Self {
         Games {
             games: Mutex::new(HashMap::new()),
             custom_games: Mutex::new(HashMap::new()),
             waiting_room: Mutex::new(vec![]),
+            player_games: Mutex::new(HashMap::new()),
+            config,
+            chat: ChatSettings::new(chat),
+            metrics,
+            shutting_down: AtomicBool::new(false),
         }
     }
 
-    async fn find_waiting_game(&self) -> Option<Player> {
-        self.waiting_room.lock().await.pop()
+    pub fn chat_settings(&self) -> &ChatSettings {
+        &self.chat
+    }
+
+    pub fn is_shutting_down(&self) -> bool {
+        self.shutting_down.load(Ordering::Acquire)
+    }
+
+    fn check_accepting_games(&self) -> Result<(), GameError> {
+        if self.is_shutting_down() {
+            Err(GameError::ServerShuttingDown)
+        } else {
+            Ok(())
+        }
     }
 
+    /// Load the games saved by the last shutdown back in memory.
+    #[instrument(skip_all)]
+    pub async fn restore(&self, pool: &sqlx::PgPool) -> sqlx::Result<usize> {
+        let snapshots = GameSnapshot::get_all(pool).await?;
+        let count = snapshots.len();
+        for snapshot in snapshots {
+            let game_id = snapshot.game_id;
+            let game = Game::restore(
+                snapshot,
+                self.config.ping_timeout(),
+                self.config.resume_grace(),
+                self.metrics.clone(),
+            );
+            self.insert_game(game_id, game).await;
+        }
+        if count > 0 {
+            info!(count, "restored games from last shutdown");
+        }
+        Ok(count)
+    }
+
+    /// List the started games, the custom games waiting for an opponent and the waiting room.
+    pub async fn summaries(&self) -> Vec<GameSummary> {
+        let games: Vec<_> = self.games.lock().await.values().cloned().collect();
+        let mut summaries = Vec::with_capacity(games.len());
+        for game in games {
+            summaries.push(game.lock().await.summary());
+        }
+
+        let custom_games = self.custom_games.lock().await;
+        summaries.extend(custom_games.iter().map(|(game_id, custom)| GameSummary {
+            game_id: Some(*game_id),
+            kind: GameSummaryKind::Custom,
+            players: vec![custom.player.player_id.clone()],
+            player_turn: None,
+            spectators: 0,
+        }));
+        drop(custom_games);
+
+        let waiting_room = self.waiting_room.lock().await;
+        summaries.extend(waiting_room.iter().map(|waiting| GameSummary {
+            game_id: None,
+            kind: GameSummaryKind::Random,
+            players: vec![waiting.player.player_id.clone()],
+            player_turn: None,
+            spectators: 0,
+        }));
+
+        summaries
+    }
+
+    /// Remove a started or waiting game, notifying its players if `notify` is set.
+    ///
+    /// Return the record of the game if it had started.
+    #[instrument(skip(self))]
+    pub async fn remove_game(
+        &self,
+        game_id: Uuid,
+        notify: bool,
+    ) -> Result<Option<GameRecord>, GameError> {
+        let event = GameEvent::GameTerminated;
+
+        let mut games = self.games.lock().await;
+        let game = games.remove(&game_id);
+        self.metrics.games.set(games.len() as i64);
+        drop(games);
+
+        if let Some(game) = game {
+            let game = game.lock().await;
+            self.unindex_game(
+                game_id,
+                &game.white_player.player_id,
+                &game.black_player.player_id,
+            )
+            .await;
+            if notify {
+                game.broadcast(&event).await;
+            }
+            self.metrics.game_ended("terminated");
+            info!("removed game");
+            return Ok(Some(game.record("terminated")));
+        }
+
+        let mut custom_games = self.custom_games.lock().await;
+        let custom = custom_games.remove(&game_id);
+        self.metrics.custom_games.set(custom_games.len() as i64);
+        drop(custom_games);
+
+        match custom {
+            Some(custom) => {
+                if notify {
+                    let _ = custom.player.send(&event).await;
+                }
+                info!("removed custom game");
+                Ok(None)
+            }
+            None => Err(GameError::InvalidGameId(game_id)),
+        }
+    }
+
+    pub async fn snapshot_game(&self, game_id: Uuid) -> Result<GameSnapshot, GameError> {
+        let game = self.get_game_with_id(game_id).await?;
+        let snapshot = game.lock().await.snapshot();
+        Ok(snapshot)
+    }
+
+    pub async fn snapshot_games(&self) -> Vec<GameSnapshot> {
+        let games: Vec<_> = self.games.lock().await.values().cloned().collect();
+        let mut snapshots = Vec::with_capacity(games.len());
+        for game in games {
+            snapshots.push(game.lock().await.snapshot());
+        }
+        snapshots
+    }
+
+    /// Stop accepting new games, warn every connected player and save the in-progress games.
+    #[instrument(skip_all)]
+    pub async fn shutdown(&self, pool: &sqlx::PgPool) -> sqlx::Result<usize> {
+        self.shutting_down.store(true, Ordering::Release);
+
+        let event = GameEvent::ServerShuttingDown;
+
+        let waiting: Vec<Player> = self
+            .waiting_room
+            .lock()
+            .await
+            .drain(..)
+            .map(|waiting| waiting.player)
+            .collect();
+        let custom: Vec<Player> = self
+            .custom_games
+            .lock()
+            .await
+            .drain()
+            .map(|(_, custom)| custom.player)
+            .collect();
+        let futs = waiting
+            .iter()
+            .chain(&custom)
+            .map(|player| player.send(&event));
+        let _ = futures::future::join_all(futs).await;
+
+        let games: Vec<_> = self.games.lock().await.drain().collect();
+        self.player_games.lock().await.clear();
+        let mut saved = 0;
+        for (game_id, game) in games {
+            let game = game.lock().await;
+            game.broadcast(&event).await;
+            if game.is_ended() {
+                if let Err(err) = game.record(game.outcome()).insert(pool).await {
+                    warn!(%game_id, error = %err, "could not save game record");
+                }
+                continue;
+            }
+            if let Some(correspondence) = game.correspondence_game() {
+                if let Err(err) = correspondence.upsert(pool).await {
+                    warn!(%game_id, error = %err, "could not save correspondence game");
+                }
+                continue;
+            }
+            match game.snapshot().insert(pool).await {
+                Ok(_) => saved += 1,
+                Err(err) => warn!(%game_id, error = %err, "could not save game"),
+            }
+        }
+
+        self.metrics.games.set(0);
+        self.metrics.custom_games.set(0);
+        self.metrics.waiting_room.set(0);
+        info!(saved, "saved in-progress games");
+        Ok(saved)
+    }
+
+    /// Take the most recent waiting player that is not in `blocked`.
+    async fn find_waiting_game(&self, blocked: &HashSet<String>) -> Option<Player> {
+        let mut waiting_room = self.waiting_room.lock().await;
+        let index = waiting_room
+            .iter()
+            .rposition(|waiting| !blocked.contains(&waiting.player.player_id))?;
+        let waiting = waiting_room.remove(index);
+        self.metrics.waiting_room.set(waiting_room.len() as i64);
+        Some(waiting.player)
+    }
+
+    async fn push_waiting_player(&self, player: Player) {
+        let mut waiting_room = self.waiting_room.lock().await;
+        waiting_room.push(WaitingPlayer::new(player));
+        self.metrics.waiting_room.set(waiting_room.len() as i64);
+    }
+
+    async fn insert_game(&self, game_id: Uuid, game: Game) {
+        let mut player_games = self.player_games.lock().await;
+        for player in [&game.white_player, &game.black_player] {
+            player_games
+                .entry(player.player_id.clone())
+                .or_default()
+                .insert(game_id);
+        }
+        drop(player_games);
+
+        let mut games = self.games.lock().await;
+        games
+            .entry(game_id)
+            .or_insert_with(|| Arc::new(Mutex::new(game)));
+        self.metrics.games.set(games.len() as i64);
+    }
+
+    async fn unindex_game(&self, game_id: Uuid, white_player_id: &str, black_player_id: &str) {
+        let mut player_games = self.player_games.lock().await;
+        for player_id in [white_player_id, black_player_id] {
+            if let Some(game_ids) = player_games.get_mut(player_id) {
+                game_ids.remove(&game_id);
+                if game_ids.is_empty() {
+                    player_games.remove(player_id);
+                }
+            }
+        }
+    }
+
+    /// The started games of `player_id`, the ones waiting for their move first.
+    pub async fn games_of_player(&self, player_id: &str) -> Vec<PlayerGame> {
+        let game_ids: Vec<Uuid> = self
+            .player_games
+            .lock()
+            .await
+            .get(player_id)
+            .map(|game_ids| game_ids.iter().copied().collect())
+            .unwrap_or_default();
+
+        let mut player_games = Vec::with_capacity(game_ids.len());
+        for game_id in game_ids {
+            let Ok(game) = self.get_game_with_id(game_id).await else {
+                continue;
+            };
+            let game = game.lock().await;
+            if let Ok(color) = game.get_player_color(player_id.to_owned()) {
+                player_games.push(game.player_game(color));
+            }
+        }
+        sort_player_games(&mut player_games);
+        player_games
+    }
+
+    /// The live game `player_id` is playing, correspondence games are played over days and
+    /// don't count.
+    pub async fn playing_game(&self, player_id: &str) -> Option<Uuid> {
+        let game_ids: Vec<Uuid> = self
+            .player_games
+            .lock()
+            .await
+            .get(player_id)
+            .map(|game_ids| game_ids.iter().copied().collect())
+            .unwrap_or_default();
+
+        for game_id in game_ids {
+            let Ok(game) = self.get_game_with_id(game_id).await else {
+                continue;
+            };
+            let game = game.lock().await;
+            if !game.is_ended() && game.correspondence.is_none() {
+                return Some(game_id);
+            }
+        }
+        None
+    }
+
+    /// Whether `player_id` has a stream open on the game server: to one of their games, to a
+    /// custom game they created or to the waiting room. The streams are pinged together once
+    /// every lock is released.
+    pub async fn is_online(&self, player_id: &str) -> bool {
+        let game_ids: Vec<Uuid> = self
+            .player_games
+            .lock()
+            .await
+            .get(player_id)
+            .map(|game_ids| game_ids.iter().copied().collect())
+            .unwrap_or_default();
+
+        let mut senders = vec![];
+        for game_id in game_ids {
+            let Ok(game) = self.get_game_with_id(game_id).await else {
+                continue;
+            };
+            let game = game.lock().await;
+            let player = if game.white_player.has_id(player_id) {
+                &game.white_player
+            } else {
+                &game.black_player
+            };
+            senders.push(player.sender.clone());
+        }
+        senders.extend(
+            self.custom_games
+                .lock()
+                .await
+                .values()
+                .filter(|custom| custom.player.has_id(player_id))
+                .map(|custom| custom.player.sender.clone()),
+        );
+        senders.extend(
+            self.waiting_room
+                .lock()
+                .await
+                .iter()
+                .filter(|waiting| waiting.player.has_id(player_id))
+                .map(|waiting| waiting.player.sender.clone()),
+        );
+
+        let ping_timeout = self.config.ping_timeout();
+        let pings = senders
+            .iter()
+            .map(|sender| test_connection(sender, ping_timeout, &self.metrics));
+        futures::future::join_all(pings)
+            .await
+            .into_iter()
+            .any(|connected| connected)
+    }
+
+    async fn insert_custom_game(&self, game_id: Uuid, custom: WaitingPlayer) {
+        let mut custom_games = self.custom_games.lock().await;
+        custom_games.insert(game_id, custom);
+        self.metrics.custom_games.set(custom_games.len() as i64);
+    }
+
+    /// Start a game with the given colours, unlike random and custom games where they are
+    /// drawn at random. Gives back the player still connected if the other one left.
     pub async fn start_new_game(
         &self,
-        player1: Player,
-        player2: Player,
+        white_player: Player,
+        black_player: Player,
         game_id: Uuid,
     ) -> Result<(), Option<Player>> {
-        let game = Game::new(player1, player2, game_id).await?;
-        println!("start game {}", game_id);
-        let game = Arc::new(Mutex::new(game));
-        self.games.lock().await.insert(game_id, game);
+        let ping_timeout = self.config.ping_timeout();
+        let game = Game::new(
+            white_player,
+            black_player,
+            game_id,
+            ping_timeout,
+            self.metrics.clone(),
+        )
+        .await?;
+        info!(%game_id, "started game");
+        self.insert_game(game_id, game).await;
         Ok(())
     }
 
-    pub async fn start_new_random_game(&self, player_id: String) -> sse::Sse<sse::ChannelStream> {
-        let (player1, stream) = Player::new_with_stream(player_id.clone());
-        if let Some(player2) = self.find_waiting_game().await {
+    async fn new_game(
+        &self,
+        player1: Player,
+        player2: Player,
+        game_id: Uuid,
+    ) -> Result<Game, Option<Player>> {
+        let mut players = [player1, player2];
+
+        let mut rng = rand::thread_rng();
+
+        players.shuffle(&mut rng);
+
+        let [player1, player2] = players;
+
+        let ping_timeout = self.config.ping_timeout();
+        Game::new(
+            player1,
+            player2,
+            game_id,
+            ping_timeout,
+            self.metrics.clone(),
+        )
+        .await
+    }
+
+    /// Pair the player with someone from the waiting room, never with a player from `blocked`.
+    #[instrument(skip(self, blocked))]
+    pub async fn start_new_random_game(
+        &self,
+        player_id: String,
+        blocked: &HashSet<String>,
+    ) -> Result<sse::Sse<sse::ChannelStream>, GameError> {
+        self.check_accepting_games()?;
+        let (player1, stream) = Player::new_with_stream(player_id);
+        if let Some(player2) = self.find_waiting_game(blocked).await {
             if player2.player_id == player1.player_id {
-                self.waiting_room.lock().await.push(player1);
+                self.push_waiting_player(player1).await;
             } else {
                 let player2_id = player2.player_id.clone();
                 let game_id = Uuid::new_v4();
-                match Game::new(player1, player2, game_id).await {
+                match self.new_game(player1, player2, game_id).await {
                     Ok(game) => {
-                        println!(
-                            "Started random game {} with player {} and player {}",
-                            game_id, player_id, player2_id
-                        );
-                        let game = Arc::new(Mutex::new(game));
-                        self.games.lock().await.insert(game_id, game);
+                        info!(%game_id, opponent_id = %player2_id, "started random game");
+                        self.insert_game(game_id, game).await;
                     }
                     Err(Some(player)) => {
-                        self.waiting_room.lock().await.push(player);
+                        self.push_waiting_player(player).await;
                     }
                     Err(None) => {}
                 }
             }
         } else {
-            println!("Player with id {} is waiting for opponent", player_id);
-            self.waiting_room.lock().await.push(player1);
+            info!("player is waiting for opponent");
+            self.push_waiting_player(player1).await;
         }
-        stream
+        Ok(stream)
+    }
+
+    /// How a started game ended, `None` while it is still played.
+    pub async fn game_end(&self, game_id: Uuid) -> Result<Option<GameEnd>, GameError> {
+        let game = self.get_game_with_id(game_id).await?;
+        let game = game.lock().await;
+        Ok(game.board.is_end())
+    }
+
+    /// Result of a tournament or simul game, `None` while it is played. Ended games leave the
+    /// game server at the next stale games sweep, their result is then read from their record.
+    pub async fn game_result(&self, game_id: Uuid, pool: &sqlx::PgPool) -> Option<PairingResult> {
+        match self.game_end(game_id).await {
+            Ok(end) => end.map(end_result),
+            Err(_) => match GameRecord::get(game_id, pool).await {
+                // `None` while the record is not saved yet
+                Ok(record) => record.as_ref().map(record_result),
+                Err(err) => {
+                    warn!(%game_id, error = %err, "could not get game record");
+                    None
+                }
+            },
+        }
+    }
+
+    /// Give a simul board to the new stream of its host, after they reconnected.
+    pub async fn rejoin_simul_board(
+        &self,
+        game_id: Uuid,
+        host_id: &str,
+        sender: &sse::Sender,
+    ) -> Result<(), GameError> {
+        let game = self.get_game_with_id(game_id).await?;
+        let mut game = game.lock().await;
+        let player_color = game.get_player_color(host_id.to_owned())?;
+
+        let host = Player::simul_host(sender.clone(), host_id.to_owned(), game_id);
+        let event = GameEvent::RejoinedGame {
+            game_id,
+            player_color,
+            board: game.board.has_started().then(|| game.board.clone()),
+            chat: game.chat.transcript().to_vec(),
+        };
+        let _ = host.send(&event).await;
+
+        match player_color {
+            Color::Black => game.black_player = host,
+            Color::White => game.white_player = host,
+        }
+        Ok(())
     }
 
     pub async fn get_game_with_id(&self, id: Uuid) -> Result<Arc<Mutex<Game>>, GameError> {
@@ -327,46 +1194,268 @@ impl Games {
         Err(GameError::InvalidGameId(id))
     }
 
+    /// Players of a game that is over but still in memory, as `(white, black)`.
+    pub async fn get_ended_game_players(&self, id: Uuid) -> Option<(String, String)> {
+        let game = self.get_game_with_id(id).await.ok()?;
+        let game = game.lock().await;
+        game.is_ended().then(|| {
+            (
+                game.white_player.player_id.clone(),
+                game.black_player.player_id.clone(),
+            )
+        })
+    }
+
     pub async fn get_all_game_with_id(&self, id: Uuid) -> Result<GameState, GameError> {
         if let Ok(game) = self.get_game_with_id(id).await {
             return Ok(GameState::Started(game));
         }
         let mut custom_games = self.custom_games.lock().await;
 
-        if let Some(player) = custom_games.remove(&id) {
-            return Ok(GameState::Waiting(player));
+        if let Some(custom) = custom_games.remove(&id) {
+            self.metrics.custom_games.set(custom_games.len() as i64);
+            return Ok(GameState::Waiting(custom));
         }
 
         Err(GameError::InvalidGameId(id))
     }
 
-    pub async fn remove_stale_games(&self) {
+    /// Remove the ended and abandoned games, and the correspondence games both players left.
+    #[instrument(skip_all)]
+    pub async fn remove_stale_games(&self) -> RemovedGames {
         let mut games = self.games.lock().await;
 
-        let mut to_remove = HashSet::new();
+        let mut to_remove = HashMap::new();
+        let mut removed = RemovedGames::default();
+        let mut connections = 0;
+        let mut spectators = 0;
 
         for (game_id, game) in games.iter() {
             let mut game = game.lock().await;
             if game.is_stale().await {
-                to_remove.insert(*game_id);
+                to_remove.insert(
+                    *game_id,
+                    (
+                        game.white_player.player_id.clone(),
+                        game.black_player.player_id.clone(),
+                    ),
+                );
+                match game.correspondence_game() {
+                    Some(correspondence) if !game.is_ended() => {
+                        removed.correspondence.push(correspondence)
+                    }
+                    _ => removed.records.push(game.record(game.outcome())),
+                }
+            } else {
+                connections += game.connection_count();
+                spectators += game.spectators.len();
             }
         }
 
         if !to_remove.is_empty() {
-            println!("removed games {:?}", to_remove);
+            info!(removed = ?to_remove.keys(), "removed stale games");
+        }
+
+        games.retain(|id, _| !to_remove.contains_key(id));
+        self.metrics.games.set(games.len() as i64);
+        self.metrics.spectators.set(spectators as i64);
+        drop(games);
+
+        for (game_id, (white_player_id, black_player_id)) in &to_remove {
+            self.unindex_game(*game_id, white_player_id, black_player_id)
+                .await;
+        }
+
+        connections += self.sweep_custom_games().await;
+        connections += self.sweep_waiting_room().await;
+        self.metrics.sse_connections.set(connections as i64);
+
+        removed
+    }
+
+    /// Ping every custom game creator, dropping the games whose creator disconnected and telling
+    /// the creators of games older than `custom_game_ttl` that their link expired.
+    ///
+    /// Returns the number of creators still waiting.
+    async fn sweep_custom_games(&self) -> usize {
+        let ttl = self.config.custom_game_ttl();
+        let ping_timeout = self.config.ping_timeout();
+        let mut custom_games = self.custom_games.lock().await;
+
+        let checks = custom_games.iter().map(|(game_id, custom)| async move {
+            let connected = custom
+                .player
+                .is_connected(ping_timeout, &self.metrics)
+                .await;
+            (*game_id, connected, custom.is_expired(ttl))
+        });
+        let checks = futures::future::join_all(checks).await;
+
+        let mut disconnected = vec![];
+        let mut expired = vec![];
+        for (game_id, connected, is_expired) in checks {
+            if !connected {
+                custom_games.remove(&game_id);
+                disconnected.push(game_id);
+            } else if is_expired {
+                if let Some(custom) = custom_games.remove(&game_id) {
+                    expired.push((game_id, custom.player));
+                }
+            }
+        }
+        self.metrics.custom_games.set(custom_games.len() as i64);
+        let remaining = custom_games.len();
+        drop(custom_games);
+
+        if !disconnected.is_empty() {
+            info!(removed = ?disconnected, "removed abandoned custom games");
         }
+        if !expired.is_empty() {
+            let game_ids: Vec<Uuid> = expired.iter().map(|(game_id, _)| *game_id).collect();
+            info!(removed = ?game_ids, "removed expired custom games");
+            let event = GameEvent::InvitationExpired;
+            let futs = expired.iter().map(|(_, player)| player.send(&event));
+            let _ = futures::future::join_all(futs).await;
+        }
+
+        remaining
+    }
+
+    /// Ping every player in the waiting room, dropping the ones who disconnected and sending
+    /// back the ones who waited longer than `waiting_room_ttl`.
+    ///
+    /// Returns the number of players still waiting.
+    async fn sweep_waiting_room(&self) -> usize {
+        let ttl = self.config.waiting_room_ttl();
+        let ping_timeout = self.config.ping_timeout();
+        let mut waiting_room = self.waiting_room.lock().await;
+
+        let checks = waiting_room.iter().map(|waiting| async move {
+            let connected = waiting
+                .player
+                .is_connected(ping_timeout, &self.metrics)
+                .await;
+            (connected, waiting.is_expired(ttl))
+        });
+        let checks = futures::future::join_all(checks).await;
+
+        let mut disconnected = 0;
+        let mut expired = vec![];
+        let mut remaining = Vec::with_capacity(waiting_room.len());
+        for (waiting, (connected, is_expired)) in waiting_room.drain(..).zip(checks) {
+            if !connected {
+                disconnected += 1;
+            } else if is_expired {
+                expired.push(waiting.player);
+            } else {
+                remaining.push(waiting);
+            }
+        }
+        *waiting_room = remaining;
+        self.metrics.waiting_room.set(waiting_room.len() as i64);
+        let remaining = waiting_room.len();
+        drop(waiting_room);
+
+        if disconnected > 0 || !expired.is_empty() {
+            info!(disconnected, expired = expired.len(), "swept waiting room");
+        }
+        let event = GameEvent::InvitationExpired;
+        let futs = expired.iter().map(|player| player.send(&event));
+        let _ = futures::future::join_all(futs).await;
+
+        remaining
+    }
+
+    fn custom_game_limit_reached(
+        &self,
+        custom_games: &HashMap<Uuid, WaitingPlayer>,
+        player_id: &str,
+    ) -> bool {
+        let open = custom_games
+            .values()
+            .filter(|custom| custom.player.has_id(player_id))
+            .count();
+        open >= self.config.max_custom_games_per_user
+    }
 
-        games.retain(|id, _| !to_remove.contains(id));
+    pub async fn check_custom_game_limit(&self, player_id: &str) -> Result<(), GameError> {
+        let custom_games = self.custom_games.lock().await;
+        if self.custom_game_limit_reached(&custom_games, player_id) {
+            Err(GameError::TooManyCustomGames)
+        } else {
+            Ok(())
+        }
     }
 
-    pub async fn create_custom_game(&self, player_id: String) -> sse::Sse<sse::ChannelStream> {
+    #[instrument(skip(self))]
+    /// Create a custom game, played as a correspondence game if `days_per_move` is set and only
+    /// joinable by `opponent_id` if it is set.
+    pub async fn create_custom_game(
+        &self,
+        player_id: String,
+        days_per_move: Option<u32>,
+        opponent_id: Option<String>,
+    ) -> Result<(Uuid, sse::Sse<sse::ChannelStream>), GameError> {
+        self.check_accepting_games()?;
+        let max = self.config.correspondence_max_days_per_move;
+        if days_per_move.is_some_and(|days| days == 0 || days > max) {
+            return Err(GameError::InvalidDaysPerMove { max });
+        }
+
+        let mut custom_games = self.custom_games.lock().await;
+        if self.custom_game_limit_reached(&custom_games, &player_id) {
+            info!("player has too many custom games");
+            return Err(GameError::TooManyCustomGames);
+        }
+
         let game_id = Uuid::new_v4();
         let (player, stream) = Player::new_with_stream(player_id);
         let _ = player.send(&GameEvent::CustomCreated { game_id }).await;
-        let mut games = self.custom_games.lock().await;
-        games.insert(game_id, player);
-        println!("created custom game {}", game_id);
-        stream
+        let custom = WaitingPlayer {
+            days_per_move,
+            opponent_id,
+            ..WaitingPlayer::new(player)
+        };
+        custom_games.insert(game_id, custom);
+        self.metrics.custom_games.set(custom_games.len() as i64);
+        info!(%game_id, ?days_per_move, "created custom game");
+        Ok((game_id, stream))
+    }
+
+    /// The challenge `player_id` received for the custom game `game_id`, as the id of the
+    /// challenger and the days per move.
+    pub async fn get_challenge(
+        &self,
+        game_id: Uuid,
+        player_id: &str,
+    ) -> Result<(String, Option<u32>), GameError> {
+        let custom_games = self.custom_games.lock().await;
+        let custom = custom_games
+            .get(&game_id)
+            .ok_or(GameError::InvalidGameId(game_id))?;
+        if custom.opponent_id.as_deref() != Some(player_id) {
+            return Err(GameError::NotChallenged);
+        }
+        Ok((custom.player.player_id.clone(), custom.days_per_move))
+    }
+
+    /// Turn a challenge down, the challenger is told and the custom game removed.
+    #[instrument(skip(self))]
+    pub async fn decline_challenge(&self, game_id: Uuid, player_id: &str) -> Result<(), GameError> {
+        let mut custom_games = self.custom_games.lock().await;
+        let custom = custom_games
+            .get(&game_id)
+            .ok_or(GameError::InvalidGameId(game_id))?;
+        if custom.opponent_id.as_deref() != Some(player_id) {
+            return Err(GameError::NotChallenged);
+        }
+        let custom = custom_games.remove(&game_id).unwrap();
+        self.metrics.custom_games.set(custom_games.len() as i64);
+        drop(custom_games);
+
+        info!("challenge declined");
+        let _ = custom.player.send(&GameEvent::ChallengeDeclined).await;
+        Ok(())
     }
 
     async fn join_started_game_inner(
@@ -381,20 +1470,25 @@ impl Games {
             game_id: game.game_id,
             player_color,
             board,
+            chat: game.chat.transcript().to_vec(),
         };
 
         let _ = player.send(&event).await;
 
-        match player_color {
-            Color::Black => game.black_player.sender = player,
-            Color::White => game.white_player.sender = player,
-        }
+        // a simul host rejoining a single board gets its events on this stream alone
+        let slot = match player_color {
+            Color::Black => &mut game.black_player,
+            Color::White => &mut game.white_player,
+        };
+        *slot = Player::new(player, slot.player_id.clone());
 
         stream
     }
+    /// Rejoin a started game, or spectate it unless one of its players is in `blocked`.
     async fn join_started_game(
         game: Arc<Mutex<Game>>,
         player_id: &str,
+        blocked: &HashSet<String>,
     ) -> Result<sse::Sse<sse::ChannelStream>, GameError> {
         let mut game = game.lock().await;
 
@@ -403,35 +1497,57 @@ impl Games {
 
         match (is_black, is_white) {
             (true, false) => {
-                println!(
-                    "Player {} successfully rejoined game {}",
-                    player_id, game.game_id
-                );
+                info!(game_id = %game.game_id, "player rejoined game");
                 Ok(Self::join_started_game_inner(&mut game, Color::Black).await)
             }
             (false, true) => {
-                println!(
-                    "Player {} successfully rejoined game {}",
-                    player_id, game.game_id
-                );
+                info!(game_id = %game.game_id, "player rejoined game");
                 Ok(Self::join_started_game_inner(&mut game, Color::White).await)
             }
-            _ => Err(GameError::InvalidPlayerId {
-                game_id: game.game_id,
-            }),
+            _ if blocked.contains(&game.white_player.player_id)
+                || blocked.contains(&game.black_player.player_id) =>
+            {
+                info!(game_id = %game.game_id, "user tried to spectate a game of a blocked player");
+                Err(GameError::PlayerBlocked)
+            }
+            _ => {
+                info!(game_id = %game.game_id, "user is spectating game");
+                Ok(Self::spectate_game(&mut game).await)
+            }
         }
     }
 
+    async fn spectate_game(game: &mut Game) -> sse::Sse<sse::ChannelStream> {
+        let (spectator, stream) = sse::channel(10);
+
+        let event = GameEvent::Spectating {
+            game_id: game.game_id,
+            board: game.board.has_started().then(|| game.board.clone()),
+        };
+        let _ = spectator.send(&event).await;
+        game.spectators.push(spectator);
+
+        stream
+    }
+
     async fn join_waiting_game(
         &self,
         game_id: Uuid,
-        player1: Player,
+        custom: WaitingPlayer,
         player2_id: String,
     ) -> Option<sse::Sse<sse::ChannelStream>> {
         let (player2, stream) = Player::new_with_stream(player2_id);
-        let res = self.start_new_game(player1, player2, game_id).await;
+        let res = self.new_game(custom.player, player2, game_id).await;
         match res {
-            Ok(()) => Some(stream),
+            Ok(game) => {
+                let game = match custom.days_per_move {
+                    Some(days_per_move) => game.with_correspondence(days_per_move),
+                    None => game,
+                };
+                info!(%game_id, days_per_move = ?custom.days_per_move, "started game");
+                self.insert_game(game_id, game).await;
+                Some(stream)
+            }
             Err(Some(player)) => {
                 let _ = player.send(&GameEvent::OpponentDisconnected).await;
                 Some(stream)
@@ -440,53 +1556,225 @@ impl Games {
         }
     }
 
+    /// Join or rejoin a game, or spectate it for users who don't play in it. A custom game can't
+    /// be joined, nor a started game spectated, if one of its players is in `blocked`.
+    #[instrument(skip(self, blocked))]
     pub async fn join_game(
         &self,
         game_id: Uuid,
         player_id: String,
+        blocked: &HashSet<String>,
     ) -> Result<sse::Sse<sse::ChannelStream>, GameError> {
+        self.check_accepting_games()?;
         let game = self.get_all_game_with_id(game_id).await?;
         match game {
-            GameState::Started(game) => Self::join_started_game(game, &player_id).await,
-            GameState::Waiting(player1) => {
-                if player1.has_id(&player_id) {
-                    println!(
-                        "Player with id {} rejoined witing custom game with id {}",
-                        player_id, game_id
-                    );
+            GameState::Started(game) => Self::join_started_game(game, &player_id, blocked).await,
+            GameState::Waiting(custom) => {
+                if custom.player.has_id(&player_id) {
+                    info!("player rejoined waiting custom game");
                     let (player, stream) = Player::new_with_stream(player_id);
                     let _ = player.send(&GameEvent::WaitingForOpponent).await;
-                    let mut custom_games = self.custom_games.lock().await;
-                    custom_games.insert(game_id, player);
+                    let custom = WaitingPlayer { player, ..custom };
+                    self.insert_custom_game(game_id, custom).await;
                     Ok(stream)
-                } else if let Some(stream) = self
-                    .join_waiting_game(game_id, player1, player_id.clone())
-                    .await
+                } else if blocked.contains(&custom.player.player_id) {
+                    info!("player tried to join a custom game of a blocked player");
+                    self.insert_custom_game(game_id, custom).await;
+                    Err(GameError::PlayerBlocked)
+                } else if custom
+                    .opponent_id
+                    .as_ref()
+                    .is_some_and(|opponent_id| *opponent_id != player_id)
+                {
+                    info!("player tried to join a challenge sent to someone else");
+                    self.insert_custom_game(game_id, custom).await;
+                    Err(GameError::NotChallenged)
+                } else if let Some(stream) =
+                    self.join_waiting_game(game_id, custom, player_id).await
                 {
-                    println!(
-                        "Player with id {} joined custom game {} and started it",
-                        player_id, game_id
-                    );
+                    info!("player joined custom game and started it");
                     Ok(stream)
                 } else {
-                    println!("All player disconnected from game {}", game_id);
+                    info!("all players disconnected from custom game");
                     Err(GameError::AllPlayerDisconnected)
                 }
             }
         }
     }
+
+    /// Load a correspondence game both players left back in memory, so it can be joined.
+    pub async fn load_correspondence_game(
+        &self,
+        game_id: Uuid,
+        pool: &sqlx::PgPool,
+    ) -> sqlx::Result<()> {
+        if self.get_game_with_id(game_id).await.is_ok() {
+            return Ok(());
+        }
+        if let Some(correspondence) = CorrespondenceGame::get(game_id, pool).await? {
+            let game = Game::from_correspondence(
+                correspondence,
+                self.config.ping_timeout(),
+                self.metrics.clone(),
+            );
+            debug!(%game_id, "loaded correspondence game");
+            self.insert_game(game_id, game).await;
+        }
+        Ok(())
+    }
+
+    /// Remove the correspondence games in memory whose side to move ran out of time, telling
+    /// their players, and return their records.
+    #[instrument(skip_all)]
+    pub async fn remove_timed_out_games(&self) -> Vec<GameRecord> {
+        let mut games = self.games.lock().await;
+
+        let mut records = vec![];
+        for game in games.values() {
+            let game = game.lock().await;
+            let timed_out = game
+                .correspondence
+                .is_some_and(|correspondence| correspondence.is_timed_out());
+            if timed_out && !game.is_ended() {
+                let loser = game.board.get_player_turn();
+                game.broadcast(&GameEvent::TimedOut { loser }).await;
+                records.push(game.record("timeout"));
+            }
+        }
+
+        games.retain(|game_id, _| records.iter().all(|record| record.game_id != *game_id));
+        self.metrics.games.set(games.len() as i64);
+        drop(games);
+
+        for record in &records {
+            self.unindex_game(
+                record.game_id,
+                &record.white_player_id,
+                &record.black_player_id,
+            )
+            .await;
+            self.metrics.game_ended("timeout");
+            info!(game_id = %record.game_id, "correspondence game timed out");
+        }
+        records
+    }
+
+    /// End a correspondence game whose deadline passed in the database, the side to move loses.
+    ///
+    /// If the game is in memory a move was played since it was saved, its latest state is
+    /// returned to be saved again instead.
+    pub async fn adjudicate_timeout(
+        &self,
+        correspondence: CorrespondenceGame,
+    ) -> Result<GameRecord, CorrespondenceGame> {
+        let game_id = correspondence.game_id;
+        if let Ok(game) = self.get_game_with_id(game_id).await {
+            if let Some(latest) = game.lock().await.correspondence_game() {
+                return Err(latest);
+            }
+        }
+        self.metrics.game_ended("timeout");
+        info!(%game_id, "correspondence game timed out");
+        Ok(correspondence.into_record("timeout"))
+    }
+
+    /// End a live game that ran out of time, the side to move loses. Its record is returned to
+    /// be saved, `None` if the game is over or left the game server.
+    #[instrument(skip(self))]
+    pub async fn adjudicate_game(&self, game_id: Uuid) -> Option<GameRecord> {
+        let mut games = self.games.lock().await;
+        let game = games.get(&game_id)?.clone();
+        let game = game.lock().await;
+        if game.is_ended() {
+            return None;
+        }
+        games.remove(&game_id);
+        self.metrics.games.set(games.len() as i64);
+        drop(games);
+
+        let loser = game.board.get_player_turn();
+        game.broadcast(&GameEvent::TimedOut { loser }).await;
+        self.unindex_game(
+            game_id,
+            &game.white_player.player_id,
+            &game.black_player.player_id,
+        )
+        .await;
+        self.metrics.game_ended("timeout");
+        info!("game adjudicated");
+        Some(game.record("timeout"))
+    }
 }
 
-lazy_static::lazy_static! {
-    pub static ref GAMES: Games = Games::new();
+/// Spawn the background task that periodically removes stale games and saves their records.
+pub fn spawn_stale_games_sweep(games: web::Data<Games>, pool: web::Data<sqlx::PgPool>) {
+    actix_web::rt::spawn(async move {
+        let mut interval = actix_web::rt::time::interval(games.config.stale_sweep_interval());
+        loop {
+            interval.tick().await;
+            let removed = games.remove_stale_games().await;
+            for record in removed.records {
+                if let Err(err) = record.insert(&pool).await {
+                    warn!(game_id = %record.game_id, error = %err, "could not save game record");
+                }
+            }
+            for correspondence in removed.correspondence {
+                if let Err(err) = correspondence.upsert(&pool).await {
+                    warn!(game_id = %correspondence.game_id, error = %err, "could not save correspondence game");
+                }
+            }
+        }
+    });
 }
 
-async fn remove_stale_games() {
-    let mut interval = actix_web::rt::time::interval(Duration::from_secs(10));
-    loop {
-        interval.tick().await;
-        GAMES.remove_stale_games().await;
-    }
+/// Spawn the background task that periodically ends the correspondence games whose side to
+/// move ran out of time.
+pub fn spawn_correspondence_timeouts(
+    games: web::Data<Games>,
+    pool: web::Data<sqlx::PgPool>,
+    notifier: web::Data<Notifier>,
+) {
+    actix_web::rt::spawn(async move {
+        let mut interval =
+            actix_web::rt::time::interval(games.config.correspondence_sweep_interval());
+        loop {
+            interval.tick().await;
+            let mut records = games.remove_timed_out_games().await;
+            for record in &records {
+                if let Err(err) = CorrespondenceGame::delete(record.game_id, &pool).await {
+                    warn!(game_id = %record.game_id, error = %err, "could not delete correspondence game");
+                }
+            }
+
+            let timed_out = match CorrespondenceGame::take_timed_out(&pool).await {
+                Ok(timed_out) => timed_out,
+                Err(err) => {
+                    warn!(error = %err, "could not load timed out correspondence games");
+                    vec![]
+                }
+            };
+            for correspondence in timed_out {
+                match games.adjudicate_timeout(correspondence).await {
+                    Ok(record) => records.push(record),
+                    Err(latest) => {
+                        if let Err(err) = latest.upsert(&pool).await {
+                            warn!(game_id = %latest.game_id, error = %err, "could not save correspondence game");
+                        }
+                    }
+                }
+            }
+
+            for record in records {
+                if let Err(err) = record.insert(&pool).await {
+                    warn!(game_id = %record.game_id, error = %err, "could not save game record");
+                }
+                for player_id in [&record.white_player_id, &record.black_player_id] {
+                    let kind = NotificationKind::GameEnded;
+                    notifier.notify(player_id, kind, Some(record.game_id)).await;
+                }
+            }
+        }
+    });
 }
 
 pub fn check_id(id: MaybeUserId) -> Result<String, GameError> {
@@ -502,30 +1790,287 @@ pub async fn get_player_id(cx: leptos::Scope) -> Result<String, ServerFnError> {
     result.map_err(|_| ServerFnError::ServerError("not logged in.".into()))
 }
 
+pub async fn get_games(cx: leptos::Scope) -> Result<web::Data<Games>, ServerFnError> {
+    use leptos_actix::extract;
+    extract(cx, |games: web::Data<Games>| async move { games }).await
+}
+
+fn sort_player_games(player_games: &mut [PlayerGame]) {
+    player_games.sort_by_key(|game| (!game.is_my_turn(), game.is_ended));
+}
+
+/// The games of a player in memory, and the correspondence games both players left.
+pub async fn get_player_games(
+    cx: leptos::Scope,
+    player_id: &str,
+) -> Result<Vec<PlayerGame>, ServerFnError> {
+    let games = get_games(cx).await?;
+    let pool = get_pool(cx).await?;
+
+    let mut player_games = games.games_of_player(player_id).await;
+    for correspondence in CorrespondenceGame::get_for_player(player_id, &pool).await? {
+        if player_games
+            .iter()
+            .any(|game| game.game_id == correspondence.game_id)
+        {
+            continue;
+        }
+        let color = if correspondence.white_player_id == player_id {
+            Color::White
+        } else {
+            Color::Black
+        };
+        player_games.push(PlayerGame {
+            game_id: correspondence.game_id,
+            color,
+            player_turn: correspondence.board.get_player_turn(),
+            is_ended: correspondence.board.is_end().is_some(),
+            days_per_move: Some(correspondence.days_per_move as u32),
+        });
+    }
+    sort_player_games(&mut player_games);
+    Ok(player_games)
+}
+
+/// The challenge sent to `player_id` for `game_id`, with the username of the challenger.
+pub async fn get_challenge(
+    cx: leptos::Scope,
+    game_id: Uuid,
+    player_id: &str,
+) -> Result<Challenge, ServerFnError> {
+    let games = get_games(cx).await?;
+    let (challenger_id, days_per_move) = games.get_challenge(game_id, player_id).await?;
+    let pool = get_pool(cx).await?;
+    let challenger = User::get_from_user_id(&challenger_id, &pool).await?;
+    Ok(Challenge {
+        game_id,
+        challenger: challenger.username,
+        days_per_move,
+    })
+}
+
+/// Archived game with the usernames of its players.
+pub async fn get_game_replay(
+    cx: leptos::Scope,
+    game_id: Uuid,
+) -> Result<Option<GameReplay>, ServerFnError> {
+    let pool = get_pool(cx).await?;
+    let Some(record) = GameRecord::get(game_id, &pool).await? else {
+        return Ok(None);
+    };
+    let white = User::get_from_user_id(&record.white_player_id, &pool).await?;
+    let black = User::get_from_user_id(&record.black_player_id, &pool).await?;
+    Ok(Some(GameReplay {
+        game_id,
+        white: white.username,
+        black: black.username,
+        board: record.board.0,
+        outcome: record.outcome,
+    }))
+}
+
+/// What is left to do once a move is played, taken out of the game so its lock is released
+/// before the database write.
+pub struct MoveFollowUp {
+    game_id: Uuid,
+    is_ended: bool,
+    correspondence: Option<CorrespondenceGame>,
+    /// Opponent who was not on the game page, with the notification they get.
+    away_opponent: Option<(String, NotificationKind)>,
+}
+
+/// Save a correspondence game after a move, or delete it from the database once it is over,
+/// and notify the opponent if they were not on the game page. The move is already played, so
+/// failures are only logged.
+pub async fn follow_up_move(cx: leptos::Scope, follow_up: MoveFollowUp) {
+    let game_id = follow_up.game_id;
+    if let Some(correspondence) = follow_up.correspondence {
+        let is_ended = follow_up.is_ended;
+        if let Err(err) = save_correspondence_game(cx, correspondence, is_ended).await {
+            warn!(%game_id, error = %err, "could not save correspondence game");
+        }
+    }
+
+    if let Some((opponent_id, kind)) = follow_up.away_opponent {
+        match get_notifier(cx).await {
+            Ok(notifier) => spawn_notify(notifier, opponent_id, kind, Some(game_id)),
+            Err(err) => warn!(%game_id, error = %err, "could not notify opponent"),
+        }
+    }
+}
+
+async fn save_correspondence_game(
+    cx: leptos::Scope,
+    correspondence: CorrespondenceGame,
+    is_ended: bool,
+) -> Result<(), ServerFnError> {
+    let pool = get_pool(cx).await?;
+    if is_ended {
+        CorrespondenceGame::delete(correspondence.game_id, &pool).await?;
+    } else {
+        correspondence.upsert(&pool).await?;
+    }
+    Ok(())
+}
+
+/// Players chat as their colour, other users chat in the spectator room under their username.
+pub async fn get_chat_author(
+    cx: leptos::Scope,
+    game: &Mutex<Game>,
+    player_id: &str,
+) -> Result<Result<ChatAuthor, ChatError>, ServerFnError> {
+    let pool = get_pool(cx).await?;
+
+    if User::is_banned(player_id, &pool).await? {
+        return Ok(Err(ChatError::Banned));
+    }
+
+    let color = game.lock().await.get_player_color(player_id.to_owned());
+    let author = match color {
+        Ok(color) => ChatAuthor::Player(color),
+        Err(_) => {
+            let username = User::get_username(player_id, &pool).await?;
+            ChatAuthor::Spectator(username.unwrap_or_else(|| DEFAULT_USERNAME.to_owned()))
+        }
+    };
+    Ok(Ok(author))
+}
+
+fn record_player_id(id: &str) {
+    Span::current().record("player_id", id);
+}
+
+/// Refuse banned players, and every player when the ban can't be checked.
+async fn check_not_banned(id: &str, pool: &sqlx::PgPool) -> Result<(), GameError> {
+    match User::is_banned(id, pool).await {
+        Ok(true) => {
+            info!("banned player was refused");
+            Err(GameError::PlayerBanned)
+        }
+        Ok(false) => Ok(()),
+        Err(err) => {
+            warn!(error = %err, "could not check if player is banned");
+            Err(GameError::BanCheckFailed)
+        }
+    }
+}
+
+/// Check that the user is logged in and not banned.
+async fn check_player(id: MaybeUserId, pool: &sqlx::PgPool) -> Result<String, GameError> {
+    let id = check_id(id)?;
+    record_player_id(&id);
+    check_not_banned(&id, pool).await?;
+    Ok(id)
+}
+
+/// Id of the current user if they are logged in and not banned.
+pub async fn get_unbanned_player_id(cx: leptos::Scope) -> Result<String, ServerFnError> {
+    let player_id = get_player_id(cx).await?;
+    let pool = get_pool(cx).await?;
+    check_not_banned(&player_id, &pool).await?;
+    Ok(player_id)
+}
+
+/// Load the block relations of a player, failing closed so a database failure can't pair
+/// them with a blocked player.
+async fn get_blocked_ids(id: &str, pool: &sqlx::PgPool) -> Result<HashSet<String>, GameError> {
+    Block::get_related_ids(id, pool).await.map_err(|err| {
+        warn!(error = %err, "could not load blocked players");
+        GameError::BlockCheckFailed
+    })
+}
+
 #[get("new_random_game")]
-async fn random_game(player_id: MaybeUserId) -> Result<sse::Sse<sse::ChannelStream>, GameError> {
-    let id = check_id(player_id)?;
+#[instrument(skip_all, fields(player_id = tracing::field::Empty))]
+async fn random_game(
+    games: web::Data<Games>,
+    pool: web::Data<sqlx::PgPool>,
+    player_id: MaybeUserId,
+) -> Result<sse::Sse<sse::ChannelStream>, GameError> {
+    let id = check_player(player_id, &pool).await?;
+    let blocked = get_blocked_ids(&id, &pool).await?;
+
+    info!("player is waiting for random game");
+    games.start_new_random_game(id, &blocked).await
+}
 
-    println!("Player with id {} is waiting for random game", id);
-    Ok(GAMES.start_new_random_game(id).await)
+#[derive(Debug, serde::Deserialize)]
+struct CustomGameOptions {
+    /// Create a correspondence game with this many days per move.
+    days_per_move: Option<u32>,
+    /// Challenge the user with this public id, they are the only one who can join.
+    opponent: Option<i32>,
+}
+
+/// Find the user `player_id` challenges, they must exist and not be blocked.
+async fn get_challenged_player(
+    player_id: &str,
+    opponent: i32,
+    pool: &sqlx::PgPool,
+) -> Result<String, GameError> {
+    let opponent = match User::get_from_id(opponent, pool).await {
+        Ok(Some(user)) if user.user_id != player_id && user.banned_at.is_none() => user,
+        Ok(_) => return Err(GameError::InvalidOpponent),
+        Err(err) => {
+            warn!(error = %err, "could not load challenged player");
+            return Err(GameError::InvalidOpponent);
+        }
+    };
+    if get_blocked_ids(player_id, pool)
+        .await?
+        .contains(&opponent.user_id)
+    {
+        info!("player tried to challenge a blocked player");
+        return Err(GameError::PlayerBlocked);
+    }
+    Ok(opponent.user_id)
 }
 
 #[get("new_custom_game")]
-async fn custom_game(player_id: MaybeUserId) -> Result<sse::Sse<sse::ChannelStream>, GameError> {
-    let id = check_id(player_id)?;
-    println!("Player with id {} asked to create custom game", id);
-    Ok(GAMES.create_custom_game(id).await)
+#[instrument(skip_all, fields(player_id = tracing::field::Empty))]
+async fn custom_game(
+    games: web::Data<Games>,
+    pool: web::Data<sqlx::PgPool>,
+    notifier: web::Data<Notifier>,
+    player_id: MaybeUserId,
+    options: web::Query<CustomGameOptions>,
+) -> Result<sse::Sse<sse::ChannelStream>, GameError> {
+    let id = check_player(player_id, &pool).await?;
+    let opponent_id = match options.opponent {
+        Some(opponent) => Some(get_challenged_player(&id, opponent, &pool).await?),
+        None => None,
+    };
+    info!("player asked to create custom game");
+    let (game_id, stream) = games
+        .create_custom_game(id, options.days_per_move, opponent_id.clone())
+        .await?;
+    if let Some(opponent_id) = opponent_id {
+        spawn_notify(
+            notifier,
+            opponent_id,
+            NotificationKind::Challenge,
+            Some(game_id),
+        );
+    }
+    Ok(stream)
 }
 
 #[get("join_game/{game_id}")]
+#[instrument(skip_all, fields(game_id = %game_id, player_id = tracing::field::Empty))]
 async fn join_game(
+    games: web::Data<Games>,
+    pool: web::Data<sqlx::PgPool>,
     game_id: web::Path<Uuid>,
     player_id: MaybeUserId,
 ) -> Result<sse::Sse<sse::ChannelStream>, GameError> {
-    let id = check_id(player_id)?;
+    let id = check_player(player_id, &pool).await?;
+    let blocked = get_blocked_ids(&id, &pool).await?;
     let game_id = game_id.into_inner();
-    println!("User with id {} try joining game {}", id, game_id);
-    GAMES.join_game(game_id, id).await
+    info!("player is trying to join game");
+    if let Err(err) = games.load_correspondence_game(game_id, &pool).await {
+        warn!(error = %err, "could not load correspondence game");
+    }
+    games.join_game(game_id, id, &blocked).await
 }
 
 pub fn config(cfg: &mut web::ServiceConfig) {
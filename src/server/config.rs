@@ -0,0 +1,983 @@
+use serde::Deserialize;
+use std::{
+    fmt::Display,
+    net::IpAddr,
+    path::{Path, PathBuf},
+    str::FromStr,
+    time::Duration,
+};
+
+const CONFIG_PATH_ENV: &str = "HEX_CHESS_CONFIG";
+const DEFAULT_CONFIG_PATH: &str = "config.toml";
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub server: ServerConfig,
+    pub database: DatabaseConfig,
+    pub auth: AuthConfig,
+    pub games: GamesConfig,
+    pub chat: ChatConfig,
+    pub rate_limit: RateLimitConfig,
+    pub notifications: NotificationsConfig,
+    pub tournaments: TournamentsConfig,
+    pub simuls: SimulsConfig,
+    pub logging: LoggingConfig,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServerConfig {
+    pub base_url: String,
+    /// Bearer token giving access to the operator endpoints, they are disabled when unset.
+    pub admin_token: Option<String>,
+    /// Time given to open connections to close once the server received a shutdown signal.
+    pub shutdown_timeout_secs: u64,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        ServerConfig {
+            base_url: String::new(),
+            admin_token: None,
+            shutdown_timeout_secs: 30,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DatabaseConfig {
+    pub url: String,
+    /// Apply the pending migrations when the server starts.
+    pub run_migrations: bool,
+}
+
+impl DatabaseConfig {
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        require(&self.url, "database.url", "DATABASE_URL")
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AuthConfig {
+    pub authority: String,
+    pub client_id: String,
+    pub client_secret: String,
+    /// Time given to the auth provider to answer the userinfo and health check requests.
+    pub request_timeout_secs: u64,
+}
+
+impl Default for AuthConfig {
+    fn default() -> Self {
+        AuthConfig {
+            authority: String::new(),
+            client_id: String::new(),
+            client_secret: String::new(),
+            request_timeout_secs: 5,
+        }
+    }
+}
+
+impl AuthConfig {
+    pub fn request_timeout(&self) -> Duration {
+        Duration::from_secs(self.request_timeout_secs)
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct GamesConfig {
+    pub ping_timeout_secs: u64,
+    pub stale_sweep_interval_secs: u64,
+    /// How long games restored after a restart wait for their players to reconnect.
+    pub resume_grace_secs: u64,
+    /// Number of custom games a player can have waiting for an opponent at the same time.
+    pub max_custom_games_per_user: usize,
+    /// Custom games nobody joined are removed after this delay.
+    pub custom_game_ttl_secs: u64,
+    /// Players waiting for a random opponent are sent back after this delay.
+    pub waiting_room_ttl_secs: u64,
+    /// Longest time per move a correspondence game can be created with.
+    pub correspondence_max_days_per_move: u32,
+    /// How often correspondence games past their move deadline are adjudicated.
+    pub correspondence_sweep_interval_secs: u64,
+}
+
+impl Default for GamesConfig {
+    fn default() -> Self {
+        GamesConfig {
+            ping_timeout_secs: 2,
+            stale_sweep_interval_secs: 10,
+            resume_grace_secs: 300,
+            max_custom_games_per_user: 3,
+            custom_game_ttl_secs: 900,
+            waiting_room_ttl_secs: 600,
+            correspondence_max_days_per_move: 14,
+            correspondence_sweep_interval_secs: 60,
+        }
+    }
+}
+
+impl GamesConfig {
+    pub fn ping_timeout(&self) -> Duration {
+        Duration::from_secs(self.ping_timeout_secs)
+    }
+
+    pub fn stale_sweep_interval(&self) -> Duration {
+        Duration::from_secs(self.stale_sweep_interval_secs)
+    }
+
+    pub fn resume_grace(&self) -> Duration {
+        Duration::from_secs(self.resume_grace_secs)
+    }
+
+    pub fn custom_game_ttl(&self) -> Duration {
+        Duration::from_secs(self.custom_game_ttl_secs)
+    }
+
+    pub fn waiting_room_ttl(&self) -> Duration {
+        Duration::from_secs(self.waiting_room_ttl_secs)
+    }
+
+    pub fn correspondence_sweep_interval(&self) -> Duration {
+        Duration::from_secs(self.correspondence_sweep_interval_secs)
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ChatConfig {
+    /// Maximum length of a message, in characters.
+    pub max_message_len: usize,
+    /// Number of messages a user can send in each rate limit window.
+    pub rate_limit_messages: usize,
+    pub rate_limit_window_secs: u64,
+    /// Words masked by the default chat filter, case insensitive.
+    pub banned_words: Vec<String>,
+}
+
+impl Default for ChatConfig {
+    fn default() -> Self {
+        ChatConfig {
+            max_message_len: 280,
+            rate_limit_messages: 5,
+            rate_limit_window_secs: 10,
+            banned_words: vec![],
+        }
+    }
+}
+
+impl ChatConfig {
+    pub fn rate_limit_window(&self) -> Duration {
+        Duration::from_secs(self.rate_limit_window_secs)
+    }
+}
+
+/// Token buckets applied to every `/api` request, one per client address and one per user.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RateLimitConfig {
+    pub enabled: bool,
+    /// Number of requests an address can make in a burst.
+    pub ip_burst: u32,
+    /// Requests per second given back to each address.
+    pub ip_per_sec: f64,
+    pub user_burst: u32,
+    pub user_per_sec: f64,
+    /// Reverse proxies whose `Forwarded` and `X-Forwarded-For` headers give the client address,
+    /// the address of the connection is used for any other peer.
+    pub trusted_proxies: Vec<IpAddr>,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        RateLimitConfig {
+            enabled: true,
+            ip_burst: 60,
+            ip_per_sec: 2.0,
+            user_burst: 30,
+            user_per_sec: 1.0,
+            trusted_proxies: vec![],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct NotificationsConfig {
+    /// Number of notifications listed in the notification centre.
+    pub history_len: u32,
+    /// Send Web Push messages for correspondence moves and challenges.
+    pub push_enabled: bool,
+    /// VAPID public key given to browsers subscribing, base64url encoded.
+    pub vapid_public_key: String,
+    /// PEM file holding the VAPID private key matching `vapid_public_key`.
+    pub vapid_private_key_file: String,
+    /// Contact sent to the push services, a `mailto:` or `https:` URL.
+    pub vapid_subject: String,
+    /// How long push services keep a message for an offline browser.
+    pub push_ttl_secs: u32,
+    /// Push services a subscription endpoint can point to, a host matches itself and its
+    /// subdomains. Endpoints must use https.
+    pub push_hosts: Vec<String>,
+    /// Also accept `http` endpoints on a loopback address, for the local push sink only.
+    pub push_allow_loopback: bool,
+    /// Notifications older than this are deleted.
+    pub retention_days: u32,
+}
+
+impl Default for NotificationsConfig {
+    fn default() -> Self {
+        NotificationsConfig {
+            history_len: 20,
+            push_enabled: false,
+            vapid_public_key: String::new(),
+            vapid_private_key_file: String::new(),
+            vapid_subject: String::new(),
+            push_ttl_secs: 86400,
+            push_hosts: [
+                "fcm.googleapis.com",
+                "push.services.mozilla.com",
+                "notify.windows.com",
+                "push.apple.com",
+            ]
+            .map(String::from)
+            .to_vec(),
+            push_allow_loopback: false,
+            retention_days: 90,
+        }
+    }
+}
+
+impl NotificationsConfig {
+    fn validate(&self) -> Result<(), ConfigError> {
+        require_non_zero(self.history_len.into(), "notifications.history_len")?;
+        require_non_zero(self.retention_days.into(), "notifications.retention_days")?;
+        if !self.push_enabled {
+            return Ok(());
+        }
+        require(
+            &self.vapid_public_key,
+            "notifications.vapid_public_key",
+            "HEX_CHESS_VAPID_PUBLIC_KEY",
+        )?;
+        require(
+            &self.vapid_private_key_file,
+            "notifications.vapid_private_key_file",
+            "HEX_CHESS_VAPID_PRIVATE_KEY_FILE",
+        )?;
+        require_url(
+            &self.vapid_subject,
+            "notifications.vapid_subject",
+            "HEX_CHESS_VAPID_SUBJECT",
+        )
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TournamentsConfig {
+    /// How often finished tournament games are scored and new pairings are made.
+    pub tick_interval_secs: u64,
+    pub max_players: usize,
+    pub max_swiss_rounds: u32,
+    pub max_arena_minutes: u32,
+    /// Longest time between two scheduled rounds of a round-robin or a bracket.
+    pub max_round_minutes: u32,
+    /// Players who are not in the lobby this long after their game was paired lose by
+    /// forfeit.
+    pub join_deadline_secs: u64,
+    /// Games still running this long after their round was paired are adjudicated, the side
+    /// to move loses.
+    pub round_time_limit_mins: u32,
+    /// Finished tournaments are removed from the list after this delay.
+    pub finished_ttl_secs: u64,
+}
+
+impl Default for TournamentsConfig {
+    fn default() -> Self {
+        TournamentsConfig {
+            tick_interval_secs: 5,
+            max_players: 128,
+            max_swiss_rounds: 15,
+            max_arena_minutes: 180,
+            max_round_minutes: 10080,
+            join_deadline_secs: 300,
+            round_time_limit_mins: 120,
+            finished_ttl_secs: 86400,
+        }
+    }
+}
+
+impl TournamentsConfig {
+    pub fn tick_interval(&self) -> Duration {
+        Duration::from_secs(self.tick_interval_secs)
+    }
+
+    pub fn join_deadline(&self) -> Duration {
+        Duration::from_secs(self.join_deadline_secs)
+    }
+
+    pub fn round_time_limit(&self) -> Duration {
+        Duration::from_secs(u64::from(self.round_time_limit_mins) * 60)
+    }
+
+    pub fn finished_ttl(&self) -> Duration {
+        Duration::from_secs(self.finished_ttl_secs)
+    }
+
+    fn validate(&self) -> Result<(), ConfigError> {
+        require_non_zero(self.tick_interval_secs, "tournaments.tick_interval_secs")?;
+        require_non_zero(self.max_players as u64, "tournaments.max_players")?;
+        require_non_zero(self.max_swiss_rounds.into(), "tournaments.max_swiss_rounds")?;
+        require_non_zero(
+            self.max_arena_minutes.into(),
+            "tournaments.max_arena_minutes",
+        )?;
+        require_non_zero(
+            self.max_round_minutes.into(),
+            "tournaments.max_round_minutes",
+        )?;
+        require_non_zero(self.join_deadline_secs, "tournaments.join_deadline_secs")?;
+        require_non_zero(
+            self.round_time_limit_mins.into(),
+            "tournaments.round_time_limit_mins",
+        )
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SimulsConfig {
+    /// How often finished simul games are scored.
+    pub tick_interval_secs: u64,
+    /// Most challengers a host can play at once.
+    pub max_boards: usize,
+    /// Finished simuls are removed from the list after this delay.
+    pub finished_ttl_secs: u64,
+}
+
+impl Default for SimulsConfig {
+    fn default() -> Self {
+        SimulsConfig {
+            tick_interval_secs: 5,
+            max_boards: 20,
+            finished_ttl_secs: 86400,
+        }
+    }
+}
+
+impl SimulsConfig {
+    pub fn tick_interval(&self) -> Duration {
+        Duration::from_secs(self.tick_interval_secs)
+    }
+
+    pub fn finished_ttl(&self) -> Duration {
+        Duration::from_secs(self.finished_ttl_secs)
+    }
+
+    fn validate(&self) -> Result<(), ConfigError> {
+        require_non_zero(self.tick_interval_secs, "simuls.tick_interval_secs")?;
+        require_non_zero(self.max_boards as u64, "simuls.max_boards")
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LoggingConfig {
+    /// Filter directives, in the `RUST_LOG` format.
+    pub level: String,
+    pub json: bool,
+}
+
+impl Default for LoggingConfig {
+    fn default() -> Self {
+        LoggingConfig {
+            level: "info".into(),
+            json: false,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Read {
+        path: PathBuf,
+        err: std::io::Error,
+    },
+    Parse {
+        path: PathBuf,
+        err: toml::de::Error,
+    },
+    Missing {
+        key: &'static str,
+        env: &'static str,
+    },
+    InvalidEnv {
+        env: &'static str,
+        reason: String,
+    },
+    Invalid {
+        key: &'static str,
+        reason: String,
+    },
+}
+
+impl Display for ConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConfigError::Read { path, err } => {
+                write!(f, "could not read config file {}: {}", path.display(), err)
+            }
+            ConfigError::Parse { path, err } => {
+                write!(f, "invalid config file {}: {}", path.display(), err)
+            }
+            ConfigError::Missing { key, env } => write!(
+                f,
+                "missing `{}`, set it in the config file or with the {} environment variable.",
+                key, env
+            ),
+            ConfigError::InvalidEnv { env, reason } => {
+                write!(
+                    f,
+                    "invalid value for environment variable {}: {}",
+                    env, reason
+                )
+            }
+            ConfigError::Invalid { key, reason } => write!(f, "invalid `{}`: {}", key, reason),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+fn override_from_env<T>(target: &mut T, env: &'static str) -> Result<(), ConfigError>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    let Ok(value) = std::env::var(env) else {
+        return Ok(());
+    };
+    *target = value
+        .parse()
+        .map_err(|err: T::Err| ConfigError::InvalidEnv {
+            env,
+            reason: err.to_string(),
+        })?;
+    Ok(())
+}
+
+fn override_option_from_env(target: &mut Option<String>, env: &'static str) {
+    if let Ok(value) = std::env::var(env) {
+        *target = Some(value);
+    }
+}
+
+fn require(value: &str, key: &'static str, env: &'static str) -> Result<(), ConfigError> {
+    if value.trim().is_empty() {
+        Err(ConfigError::Missing { key, env })
+    } else {
+        Ok(())
+    }
+}
+
+fn require_url(value: &str, key: &'static str, env: &'static str) -> Result<(), ConfigError> {
+    require(value, key, env)?;
+    reqwest::Url::parse(value)
+        .map(|_| ())
+        .map_err(|err| ConfigError::Invalid {
+            key,
+            reason: err.to_string(),
+        })
+}
+
+fn require_non_zero(value: u64, key: &'static str) -> Result<(), ConfigError> {
+    if value == 0 {
+        Err(ConfigError::Invalid {
+            key,
+            reason: "must be greater than 0.".into(),
+        })
+    } else {
+        Ok(())
+    }
+}
+
+fn require_positive(value: f64, key: &'static str) -> Result<(), ConfigError> {
+    if value > 0.0 {
+        Ok(())
+    } else {
+        Err(ConfigError::Invalid {
+            key,
+            reason: "must be greater than 0.".into(),
+        })
+    }
+}
+
+impl Config {
+    /// Load the configuration from the file pointed by `HEX_CHESS_CONFIG` (or `config.toml`),
+    /// apply the environment overrides and validate the result.
+    pub fn load() -> Result<Self, ConfigError> {
+        let config = Self::load_unvalidated()?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Same as `load` but skip the validation, for tools that only need part of the config
+    /// and validate the sections they use themselves.
+    pub fn load_unvalidated() -> Result<Self, ConfigError> {
+        let path = std::env::var(CONFIG_PATH_ENV).ok();
+        let must_exist = path.is_some();
+        let path = PathBuf::from(path.as_deref().unwrap_or(DEFAULT_CONFIG_PATH));
+
+        let mut config = Self::from_file(&path, must_exist)?;
+        config.apply_env_overrides()?;
+        Ok(config)
+    }
+
+    fn from_file(path: &Path, must_exist: bool) -> Result<Self, ConfigError> {
+        let content = match std::fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound && !must_exist => {
+                return Ok(Config::default())
+            }
+            Err(err) => {
+                return Err(ConfigError::Read {
+                    path: path.to_owned(),
+                    err,
+                })
+            }
+        };
+
+        toml::from_str(&content).map_err(|err| ConfigError::Parse {
+            path: path.to_owned(),
+            err,
+        })
+    }
+
+    fn apply_env_overrides(&mut self) -> Result<(), ConfigError> {
+        override_from_env(&mut self.server.base_url, "BASE_URL")?;
+        override_option_from_env(&mut self.server.admin_token, "HEX_CHESS_ADMIN_TOKEN");
+        override_from_env(
+            &mut self.server.shutdown_timeout_secs,
+            "HEX_CHESS_SHUTDOWN_TIMEOUT_SECS",
+        )?;
+        override_from_env(&mut self.database.url, "DATABASE_URL")?;
+        override_from_env(
+            &mut self.database.run_migrations,
+            "HEX_CHESS_RUN_MIGRATIONS",
+        )?;
+        override_from_env(&mut self.auth.authority, "AUTHORITY")?;
+        override_from_env(&mut self.auth.client_id, "AUTH0_CLIENT_ID")?;
+        override_from_env(&mut self.auth.client_secret, "AUTH0_CLIENT_SECRET")?;
+        override_from_env(
+            &mut self.auth.request_timeout_secs,
+            "HEX_CHESS_AUTH_REQUEST_TIMEOUT_SECS",
+        )?;
+        override_from_env(
+            &mut self.games.ping_timeout_secs,
+            "HEX_CHESS_PING_TIMEOUT_SECS",
+        )?;
+        override_from_env(
+            &mut self.games.stale_sweep_interval_secs,
+            "HEX_CHESS_STALE_SWEEP_INTERVAL_SECS",
+        )?;
+        override_from_env(
+            &mut self.games.resume_grace_secs,
+            "HEX_CHESS_RESUME_GRACE_SECS",
+        )?;
+        override_from_env(
+            &mut self.games.max_custom_games_per_user,
+            "HEX_CHESS_MAX_CUSTOM_GAMES_PER_USER",
+        )?;
+        override_from_env(
+            &mut self.games.custom_game_ttl_secs,
+            "HEX_CHESS_CUSTOM_GAME_TTL_SECS",
+        )?;
+        override_from_env(
+            &mut self.games.waiting_room_ttl_secs,
+            "HEX_CHESS_WAITING_ROOM_TTL_SECS",
+        )?;
+        override_from_env(
+            &mut self.games.correspondence_max_days_per_move,
+            "HEX_CHESS_CORRESPONDENCE_MAX_DAYS_PER_MOVE",
+        )?;
+        override_from_env(
+            &mut self.games.correspondence_sweep_interval_secs,
+            "HEX_CHESS_CORRESPONDENCE_SWEEP_INTERVAL_SECS",
+        )?;
+        override_from_env(
+            &mut self.chat.max_message_len,
+            "HEX_CHESS_CHAT_MAX_MESSAGE_LEN",
+        )?;
+        override_from_env(
+            &mut self.chat.rate_limit_messages,
+            "HEX_CHESS_CHAT_RATE_LIMIT_MESSAGES",
+        )?;
+        override_from_env(
+            &mut self.chat.rate_limit_window_secs,
+            "HEX_CHESS_CHAT_RATE_LIMIT_WINDOW_SECS",
+        )?;
+        override_from_env(&mut self.rate_limit.enabled, "HEX_CHESS_RATE_LIMIT")?;
+        override_from_env(
+            &mut self.rate_limit.ip_burst,
+            "HEX_CHESS_RATE_LIMIT_IP_BURST",
+        )?;
+        override_from_env(
+            &mut self.rate_limit.ip_per_sec,
+            "HEX_CHESS_RATE_LIMIT_IP_PER_SEC",
+        )?;
+        override_from_env(
+            &mut self.rate_limit.user_burst,
+            "HEX_CHESS_RATE_LIMIT_USER_BURST",
+        )?;
+        override_from_env(
+            &mut self.rate_limit.user_per_sec,
+            "HEX_CHESS_RATE_LIMIT_USER_PER_SEC",
+        )?;
+        override_from_env(
+            &mut self.notifications.history_len,
+            "HEX_CHESS_NOTIFICATIONS_HISTORY_LEN",
+        )?;
+        override_from_env(&mut self.notifications.push_enabled, "HEX_CHESS_PUSH")?;
+        override_from_env(
+            &mut self.notifications.vapid_public_key,
+            "HEX_CHESS_VAPID_PUBLIC_KEY",
+        )?;
+        override_from_env(
+            &mut self.notifications.vapid_private_key_file,
+            "HEX_CHESS_VAPID_PRIVATE_KEY_FILE",
+        )?;
+        override_from_env(
+            &mut self.notifications.vapid_subject,
+            "HEX_CHESS_VAPID_SUBJECT",
+        )?;
+        override_from_env(
+            &mut self.notifications.push_ttl_secs,
+            "HEX_CHESS_PUSH_TTL_SECS",
+        )?;
+        override_from_env(
+            &mut self.notifications.push_allow_loopback,
+            "HEX_CHESS_PUSH_ALLOW_LOOPBACK",
+        )?;
+        override_from_env(
+            &mut self.notifications.retention_days,
+            "HEX_CHESS_NOTIFICATIONS_RETENTION_DAYS",
+        )?;
+        override_from_env(
+            &mut self.tournaments.tick_interval_secs,
+            "HEX_CHESS_TOURNAMENTS_TICK_INTERVAL_SECS",
+        )?;
+        override_from_env(
+            &mut self.tournaments.max_players,
+            "HEX_CHESS_TOURNAMENTS_MAX_PLAYERS",
+        )?;
+        override_from_env(
+            &mut self.tournaments.max_swiss_rounds,
+            "HEX_CHESS_TOURNAMENTS_MAX_SWISS_ROUNDS",
+        )?;
+        override_from_env(
+            &mut self.tournaments.max_arena_minutes,
+            "HEX_CHESS_TOURNAMENTS_MAX_ARENA_MINUTES",
+        )?;
+        override_from_env(
+            &mut self.tournaments.max_round_minutes,
+            "HEX_CHESS_TOURNAMENTS_MAX_ROUND_MINUTES",
+        )?;
+        override_from_env(
+            &mut self.tournaments.join_deadline_secs,
+            "HEX_CHESS_TOURNAMENTS_JOIN_DEADLINE_SECS",
+        )?;
+        override_from_env(
+            &mut self.tournaments.round_time_limit_mins,
+            "HEX_CHESS_TOURNAMENTS_ROUND_TIME_LIMIT_MINS",
+        )?;
+        override_from_env(
+            &mut self.tournaments.finished_ttl_secs,
+            "HEX_CHESS_TOURNAMENTS_FINISHED_TTL_SECS",
+        )?;
+        override_from_env(
+            &mut self.simuls.tick_interval_secs,
+            "HEX_CHESS_SIMULS_TICK_INTERVAL_SECS",
+        )?;
+        override_from_env(&mut self.simuls.max_boards, "HEX_CHESS_SIMULS_MAX_BOARDS")?;
+        override_from_env(
+            &mut self.simuls.finished_ttl_secs,
+            "HEX_CHESS_SIMULS_FINISHED_TTL_SECS",
+        )?;
+        override_from_env(&mut self.logging.level, "RUST_LOG")?;
+        override_from_env(&mut self.logging.json, "HEX_CHESS_LOG_JSON")?;
+        Ok(())
+    }
+
+    fn validate(&self) -> Result<(), ConfigError> {
+        require_url(&self.server.base_url, "server.base_url", "BASE_URL")?;
+        self.database.validate()?;
+        require_url(&self.auth.authority, "auth.authority", "AUTHORITY")?;
+        require(&self.auth.client_id, "auth.client_id", "AUTH0_CLIENT_ID")?;
+        require(
+            &self.auth.client_secret,
+            "auth.client_secret",
+            "AUTH0_CLIENT_SECRET",
+        )?;
+        require_non_zero(self.auth.request_timeout_secs, "auth.request_timeout_secs")?;
+        require_non_zero(self.games.ping_timeout_secs, "games.ping_timeout_secs")?;
+        require_non_zero(
+            self.games.stale_sweep_interval_secs,
+            "games.stale_sweep_interval_secs",
+        )?;
+        require_non_zero(
+            self.games.max_custom_games_per_user as u64,
+            "games.max_custom_games_per_user",
+        )?;
+        require_non_zero(
+            self.games.custom_game_ttl_secs,
+            "games.custom_game_ttl_secs",
+        )?;
+        require_non_zero(
+            self.games.waiting_room_ttl_secs,
+            "games.waiting_room_ttl_secs",
+        )?;
+        require_non_zero(
+            self.games.correspondence_max_days_per_move as u64,
+            "games.correspondence_max_days_per_move",
+        )?;
+        require_non_zero(
+            self.games.correspondence_sweep_interval_secs,
+            "games.correspondence_sweep_interval_secs",
+        )?;
+        require_non_zero(self.chat.max_message_len as u64, "chat.max_message_len")?;
+        require_non_zero(
+            self.chat.rate_limit_messages as u64,
+            "chat.rate_limit_messages",
+        )?;
+        require_non_zero(
+            self.chat.rate_limit_window_secs,
+            "chat.rate_limit_window_secs",
+        )?;
+        if self.rate_limit.enabled {
+            require_non_zero(self.rate_limit.ip_burst.into(), "rate_limit.ip_burst")?;
+            require_non_zero(self.rate_limit.user_burst.into(), "rate_limit.user_burst")?;
+            require_positive(self.rate_limit.ip_per_sec, "rate_limit.ip_per_sec")?;
+            require_positive(self.rate_limit.user_per_sec, "rate_limit.user_per_sec")?;
+        }
+        self.notifications.validate()?;
+        self.tournaments.validate()?;
+        self.simuls.validate()?;
+        if self
+            .server
+            .admin_token
+            .as_deref()
+            .is_some_and(str::is_empty)
+        {
+            return Err(ConfigError::Invalid {
+                key: "server.admin_token",
+                reason: "must not be empty, remove it to disable the operator endpoints.".into(),
+            });
+        }
+        tracing_subscriber::EnvFilter::try_new(&self.logging.level).map_err(|err| {
+            ConfigError::Invalid {
+                key: "logging.level",
+                reason: err.to_string(),
+            }
+        })?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    /// The environment is shared by the tests running in parallel.
+    static ENV: Mutex<()> = Mutex::new(());
+
+    fn with_env<T>(vars: &[(&str, &str)], f: impl FnOnce() -> T) -> T {
+        let _guard = ENV.lock().unwrap_or_else(|err| err.into_inner());
+        for (name, value) in vars {
+            std::env::set_var(name, value);
+        }
+        let res = f();
+        for (name, _) in vars {
+            std::env::remove_var(name);
+        }
+        res
+    }
+
+    fn valid_config() -> Config {
+        let mut config = Config::default();
+        config.server.base_url = "https://hexchess.example".into();
+        config.database.url = "postgres://localhost/hexchess".into();
+        config.auth.authority = "https://tenant.example".into();
+        config.auth.client_id = "client".into();
+        config.auth.client_secret = "secret".into();
+        config
+    }
+
+    /// Key named by the validation error of `config`.
+    fn invalid_key(config: Config) -> &'static str {
+        match config.validate() {
+            Err(ConfigError::Missing { key, .. } | ConfigError::Invalid { key, .. }) => key,
+            Err(err) => panic!("unexpected error: {}", err),
+            Ok(()) => panic!("config is valid"),
+        }
+    }
+
+    #[test]
+    fn env_overrides_config_file() {
+        let path = std::env::temp_dir().join(format!("hex-chess-{}.toml", std::process::id()));
+        std::fs::write(
+            &path,
+            "[server]\nbase_url = \"https://hexchess.example\"\n\n\
+             [games]\nping_timeout_secs = 5\n\n[simuls]\nmax_boards = 10\n",
+        )
+        .unwrap();
+        let config = with_env(
+            &[
+                ("HEX_CHESS_PING_TIMEOUT_SECS", "7"),
+                ("HEX_CHESS_ADMIN_TOKEN", "operator"),
+                ("HEX_CHESS_RATE_LIMIT", "false"),
+            ],
+            || {
+                let mut config = Config::from_file(&path, true)?;
+                config.apply_env_overrides()?;
+                Ok::<_, ConfigError>(config)
+            },
+        );
+        std::fs::remove_file(&path).unwrap();
+        let config = config.unwrap();
+
+        assert_eq!(config.server.base_url, "https://hexchess.example");
+        assert_eq!(config.games.ping_timeout_secs, 7);
+        assert_eq!(config.simuls.max_boards, 10);
+        assert_eq!(config.server.admin_token.as_deref(), Some("operator"));
+        assert!(!config.rate_limit.enabled);
+        // sections missing from the file keep their defaults
+        assert_eq!(config.tournaments.max_players, 128);
+        assert_eq!(config.games.stale_sweep_interval_secs, 10);
+    }
+
+    #[test]
+    fn missing_config_file() {
+        let path = std::env::temp_dir().join("hex-chess-missing-config.toml");
+        assert!(Config::from_file(&path, false).is_ok());
+        assert!(matches!(
+            Config::from_file(&path, true),
+            Err(ConfigError::Read { .. })
+        ));
+    }
+
+    #[test]
+    fn invalid_env_value() {
+        for (name, value) in [
+            ("HEX_CHESS_SIMULS_MAX_BOARDS", "many"),
+            ("HEX_CHESS_NOTIFICATIONS_HISTORY_LEN", "-5"),
+            ("HEX_CHESS_RATE_LIMIT", "maybe"),
+        ] {
+            let res = with_env(&[(name, value)], || Config::default().apply_env_overrides());
+            assert!(
+                matches!(res, Err(ConfigError::InvalidEnv { env, .. }) if env == name),
+                "{}",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn valid_config_passes() {
+        valid_config().validate().unwrap();
+    }
+
+    #[test]
+    fn invalid_server() {
+        let mut config = valid_config();
+        config.server.base_url = " ".into();
+        assert_eq!(invalid_key(config), "server.base_url");
+
+        let mut config = valid_config();
+        config.server.base_url = "not a url".into();
+        assert_eq!(invalid_key(config), "server.base_url");
+
+        let mut config = valid_config();
+        config.server.admin_token = Some(String::new());
+        assert_eq!(invalid_key(config), "server.admin_token");
+    }
+
+    #[test]
+    fn invalid_database() {
+        let mut config = valid_config();
+        config.database.url = String::new();
+        assert_eq!(invalid_key(config), "database.url");
+    }
+
+    #[test]
+    fn invalid_auth() {
+        let mut config = valid_config();
+        config.auth.client_id = String::new();
+        assert_eq!(invalid_key(config), "auth.client_id");
+
+        let mut config = valid_config();
+        config.auth.request_timeout_secs = 0;
+        assert_eq!(invalid_key(config), "auth.request_timeout_secs");
+    }
+
+    #[test]
+    fn invalid_games() {
+        let mut config = valid_config();
+        config.games.ping_timeout_secs = 0;
+        assert_eq!(invalid_key(config), "games.ping_timeout_secs");
+
+        let mut config = valid_config();
+        config.games.correspondence_max_days_per_move = 0;
+        assert_eq!(
+            invalid_key(config),
+            "games.correspondence_max_days_per_move"
+        );
+    }
+
+    #[test]
+    fn invalid_chat() {
+        let mut config = valid_config();
+        config.chat.max_message_len = 0;
+        assert_eq!(invalid_key(config), "chat.max_message_len");
+    }
+
+    #[test]
+    fn invalid_rate_limit() {
+        let mut config = valid_config();
+        config.rate_limit.ip_per_sec = 0.0;
+        assert_eq!(invalid_key(config.clone()), "rate_limit.ip_per_sec");
+
+        // the limits are not checked when rate limiting is disabled
+        config.rate_limit.enabled = false;
+        config.validate().unwrap();
+    }
+
+    #[test]
+    fn invalid_notifications() {
+        let mut config = valid_config();
+        config.notifications.history_len = 0;
+        assert_eq!(invalid_key(config), "notifications.history_len");
+
+        let mut config = valid_config();
+        config.notifications.push_enabled = true;
+        assert_eq!(invalid_key(config), "notifications.vapid_public_key");
+    }
+
+    #[test]
+    fn invalid_tournaments() {
+        let mut config = valid_config();
+        config.tournaments.round_time_limit_mins = 0;
+        assert_eq!(invalid_key(config), "tournaments.round_time_limit_mins");
+    }
+
+    #[test]
+    fn invalid_simuls() {
+        let mut config = valid_config();
+        config.simuls.max_boards = 0;
+        assert_eq!(invalid_key(config), "simuls.max_boards");
+    }
+}
@@ -0,0 +1,47 @@
+type Pool = sqlx::PgPool;
+
+/// Actor recorded for the actions done through the token protected admin API.
+pub const ADMIN_API_ACTOR: &str = "admin-api";
+/// Actor recorded for the actions done with the `hex-chess-admin` tool directly on the database.
+pub const ADMIN_CLI_ACTOR: &str = "hex-chess-admin";
+
+#[derive(Debug, sqlx::FromRow)]
+pub struct AuditEntry {
+    pub id: i32,
+    pub admin_user_id: String,
+    pub action: String,
+    pub target: String,
+    pub details: Option<String>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl AuditEntry {
+    pub async fn record(
+        admin_user_id: &str,
+        action: &str,
+        target: &str,
+        details: Option<&str>,
+        pool: &Pool,
+    ) -> sqlx::Result<u64> {
+        let res = sqlx::query!(
+            "insert into admin_audit_log (admin_user_id, action, target, details) values ($1, $2, $3, $4);",
+            admin_user_id,
+            action,
+            target,
+            details
+        )
+        .execute(pool)
+        .await?;
+        Ok(res.rows_affected())
+    }
+
+    pub async fn get_recent(limit: i64, pool: &Pool) -> sqlx::Result<Vec<Self>> {
+        sqlx::query_as!(
+            AuditEntry,
+            "select id, admin_user_id, action, target, details, created_at from admin_audit_log order by created_at desc limit $1",
+            limit
+        )
+        .fetch_all(pool)
+        .await
+    }
+}
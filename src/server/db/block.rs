@@ -0,0 +1,61 @@
+use std::collections::HashSet;
+
+type Pool = sqlx::PgPool;
+
+#[derive(Debug, sqlx::FromRow)]
+pub struct BlockedUser {
+    /// Database id of the blocked user, the auth id is not shared with other players.
+    pub id: i32,
+    pub username: String,
+}
+
+pub struct Block;
+
+impl Block {
+    pub async fn insert(blocker_id: &str, blocked_id: &str, pool: &Pool) -> sqlx::Result<u64> {
+        let res = sqlx::query!(
+            "insert into user_blocks (blocker_id, blocked_id) values ($1, $2) on conflict do nothing;",
+            blocker_id,
+            blocked_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(res.rows_affected())
+    }
+
+    pub async fn delete(blocker_id: &str, blocked_user_id: i32, pool: &Pool) -> sqlx::Result<u64> {
+        let res = sqlx::query!(
+            "delete from user_blocks where blocker_id = $1 and blocked_id = (select user_id from users where id = $2)",
+            blocker_id,
+            blocked_user_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(res.rows_affected())
+    }
+
+    pub async fn get_blocked_users(
+        blocker_id: &str,
+        pool: &Pool,
+    ) -> sqlx::Result<Vec<BlockedUser>> {
+        sqlx::query_as!(
+            BlockedUser,
+            "select users.id, users.username from user_blocks join users on users.user_id = user_blocks.blocked_id where user_blocks.blocker_id = $1 order by users.username",
+            blocker_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Users that must never be paired with `user_id`: the ones they blocked and the ones who
+    /// blocked them.
+    pub async fn get_related_ids(user_id: &str, pool: &Pool) -> sqlx::Result<HashSet<String>> {
+        let rows = sqlx::query!(
+            r#"select blocked_id as "user_id!" from user_blocks where blocker_id = $1 union select blocker_id from user_blocks where blocked_id = $1"#,
+            user_id
+        )
+        .fetch_all(pool)
+        .await?;
+        Ok(rows.into_iter().map(|row| row.user_id).collect())
+    }
+}
@@ -0,0 +1,92 @@
+use chrono::{DateTime, Utc};
+use hex_chess_core::board::Board;
+use serde::{Deserialize, Serialize};
+use sqlx::types::Json;
+use uuid::Uuid;
+
+use crate::server::board::ChatMessage;
+use crate::server::db::game_record::GameRecord;
+
+type Pool = sqlx::PgPool;
+
+/// A correspondence game, saved after every move so it outlives its players' connections.
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow)]
+pub struct CorrespondenceGame {
+    pub game_id: Uuid,
+    pub white_player_id: String,
+    pub black_player_id: String,
+    pub board: Json<Board>,
+    pub chat: Json<Vec<ChatMessage>>,
+    pub days_per_move: i32,
+    /// The side to move loses the game if it has not played by then.
+    pub move_deadline: DateTime<Utc>,
+}
+
+impl CorrespondenceGame {
+    pub fn into_record(self, outcome: &str) -> GameRecord {
+        GameRecord {
+            game_id: self.game_id,
+            white_player_id: self.white_player_id,
+            black_player_id: self.black_player_id,
+            board: self.board,
+            chat: self.chat,
+            outcome: outcome.to_owned(),
+        }
+    }
+
+    pub async fn upsert(&self, pool: &Pool) -> sqlx::Result<u64> {
+        let res = sqlx::query!(
+            "insert into correspondence_games (game_id, white_player_id, black_player_id, board, chat, days_per_move, move_deadline) values ($1, $2, $3, $4, $5, $6, $7) on conflict (game_id) do update set board = excluded.board, chat = excluded.chat, move_deadline = excluded.move_deadline;",
+            self.game_id,
+            self.white_player_id,
+            self.black_player_id,
+            &self.board as _,
+            &self.chat as _,
+            self.days_per_move,
+            self.move_deadline
+        )
+        .execute(pool)
+        .await?;
+        Ok(res.rows_affected())
+    }
+
+    pub async fn get(game_id: Uuid, pool: &Pool) -> sqlx::Result<Option<Self>> {
+        sqlx::query_as!(
+            CorrespondenceGame,
+            r#"select game_id, white_player_id, black_player_id, board as "board: Json<Board>", chat as "chat: Json<Vec<ChatMessage>>", days_per_move, move_deadline from correspondence_games where game_id = $1"#,
+            game_id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    pub async fn get_for_player(player_id: &str, pool: &Pool) -> sqlx::Result<Vec<Self>> {
+        sqlx::query_as!(
+            CorrespondenceGame,
+            r#"select game_id, white_player_id, black_player_id, board as "board: Json<Board>", chat as "chat: Json<Vec<ChatMessage>>", days_per_move, move_deadline from correspondence_games where white_player_id = $1 or black_player_id = $1 order by move_deadline"#,
+            player_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn delete(game_id: Uuid, pool: &Pool) -> sqlx::Result<u64> {
+        let res = sqlx::query!(
+            "delete from correspondence_games where game_id = $1",
+            game_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(res.rows_affected())
+    }
+
+    /// Remove the games whose move deadline passed from the database and return them.
+    pub async fn take_timed_out(pool: &Pool) -> sqlx::Result<Vec<Self>> {
+        sqlx::query_as!(
+            CorrespondenceGame,
+            r#"delete from correspondence_games where move_deadline < CURRENT_TIMESTAMP returning game_id, white_player_id, black_player_id, board as "board: Json<Board>", chat as "chat: Json<Vec<ChatMessage>>", days_per_move, move_deadline"#
+        )
+        .fetch_all(pool)
+        .await
+    }
+}
@@ -0,0 +1,76 @@
+type Pool = sqlx::PgPool;
+
+#[derive(Debug, sqlx::FromRow)]
+pub struct FollowedUser {
+    /// Database id of the followed user, the auth id is not shared with other players.
+    pub id: i32,
+    /// Auth id, only used by the server to look up the presence of the user.
+    pub user_id: String,
+    pub username: String,
+    /// Whether they follow the user back, which makes them friends.
+    pub follows_back: bool,
+}
+
+pub struct Follow;
+
+impl Follow {
+    /// Follow a user, unless either of them blocked the other.
+    pub async fn insert(
+        follower_id: &str,
+        followed_user_id: i32,
+        pool: &Pool,
+    ) -> sqlx::Result<u64> {
+        let res = sqlx::query!(
+            "insert into user_follows (follower_id, followed_id) select $1, user_id from users where id = $2 and user_id <> $1 and not exists(select 1 from user_blocks where (blocker_id = $1 and blocked_id = users.user_id) or (blocker_id = users.user_id and blocked_id = $1)) on conflict do nothing;",
+            follower_id,
+            followed_user_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(res.rows_affected())
+    }
+
+    pub async fn delete(
+        follower_id: &str,
+        followed_user_id: i32,
+        pool: &Pool,
+    ) -> sqlx::Result<u64> {
+        let res = sqlx::query!(
+            "delete from user_follows where follower_id = $1 and followed_id = (select user_id from users where id = $2)",
+            follower_id,
+            followed_user_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(res.rows_affected())
+    }
+
+    pub async fn is_following(
+        follower_id: &str,
+        followed_user_id: i32,
+        pool: &Pool,
+    ) -> sqlx::Result<bool> {
+        let record = sqlx::query!(
+            r#"select exists(select 1 from user_follows join users on users.user_id = user_follows.followed_id where user_follows.follower_id = $1 and users.id = $2) as "following!""#,
+            follower_id,
+            followed_user_id
+        )
+        .fetch_one(pool)
+        .await?;
+        Ok(record.following)
+    }
+
+    /// Users followed by `follower_id`, banned users are left out.
+    pub async fn get_followed_users(
+        follower_id: &str,
+        pool: &Pool,
+    ) -> sqlx::Result<Vec<FollowedUser>> {
+        sqlx::query_as!(
+            FollowedUser,
+            r#"select users.id, users.user_id, users.username, exists(select 1 from user_follows back where back.follower_id = users.user_id and back.followed_id = $1) as "follows_back!" from user_follows join users on users.user_id = user_follows.followed_id where user_follows.follower_id = $1 and users.banned_at is null order by users.username"#,
+            follower_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+}
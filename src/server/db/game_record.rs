@@ -0,0 +1,91 @@
+use chrono::{DateTime, Utc};
+use hex_chess_core::board::Board;
+use serde::{Deserialize, Serialize};
+use sqlx::types::Json;
+use uuid::Uuid;
+
+use crate::server::board::ChatMessage;
+
+type Pool = sqlx::PgPool;
+
+/// A game that is over, kept with its chat transcript once it leaves the game server.
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow)]
+pub struct GameRecord {
+    pub game_id: Uuid,
+    pub white_player_id: String,
+    pub black_player_id: String,
+    pub board: Json<Board>,
+    pub chat: Json<Vec<ChatMessage>>,
+    /// How the game ended, `win`, `draw`, `stalemate`, `abandoned`, `terminated` or `timeout`,
+    /// where the side to move on `board` lost on time.
+    pub outcome: String,
+}
+
+impl GameRecord {
+    /// Save the record, dropping the snapshot left by a restart in the same statement.
+    pub async fn insert(&self, pool: &Pool) -> sqlx::Result<u64> {
+        let res = sqlx::query!(
+            "with snapshot as (delete from game_snapshots where game_id = $1) insert into game_records (game_id, white_player_id, black_player_id, board, chat, outcome) values ($1, $2, $3, $4, $5, $6) on conflict (game_id) do nothing;",
+            self.game_id,
+            self.white_player_id,
+            self.black_player_id,
+            &self.board as _,
+            &self.chat as _,
+            self.outcome
+        )
+        .execute(pool)
+        .await?;
+        Ok(res.rows_affected())
+    }
+
+    pub async fn get(game_id: Uuid, pool: &Pool) -> sqlx::Result<Option<Self>> {
+        sqlx::query_as!(
+            GameRecord,
+            r#"select game_id, white_player_id, black_player_id, board as "board: Json<Board>", chat as "chat: Json<Vec<ChatMessage>>", outcome from game_records where game_id = $1"#,
+            game_id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+}
+
+/// A game record with the time it ended, as exported by the admin API.
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow)]
+pub struct ArchivedGame {
+    pub game_id: Uuid,
+    pub white_player_id: String,
+    pub black_player_id: String,
+    pub board: Json<Board>,
+    pub chat: Json<Vec<ChatMessage>>,
+    pub outcome: String,
+    pub ended_at: DateTime<Utc>,
+}
+
+impl ArchivedGame {
+    pub async fn get(game_id: Uuid, pool: &Pool) -> sqlx::Result<Option<Self>> {
+        sqlx::query_as!(
+            ArchivedGame,
+            r#"select game_id, white_player_id, black_player_id, board as "board: Json<Board>", chat as "chat: Json<Vec<ChatMessage>>", outcome, ended_at from game_records where game_id = $1"#,
+            game_id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// The games that ended from `from` included to `to` excluded, oldest first. A missing bound
+    /// leaves that side of the range open.
+    pub async fn get_ended_between(
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+        pool: &Pool,
+    ) -> sqlx::Result<Vec<Self>> {
+        sqlx::query_as!(
+            ArchivedGame,
+            r#"select game_id, white_player_id, black_player_id, board as "board: Json<Board>", chat as "chat: Json<Vec<ChatMessage>>", outcome, ended_at from game_records where ($1::timestamptz is null or ended_at >= $1) and ($2::timestamptz is null or ended_at < $2) order by ended_at"#,
+            from,
+            to
+        )
+        .fetch_all(pool)
+        .await
+    }
+}
@@ -0,0 +1,52 @@
+use hex_chess_core::board::Board;
+use serde::{Deserialize, Serialize};
+use sqlx::types::Json;
+use uuid::Uuid;
+
+use crate::server::board::ChatMessage;
+
+type Pool = sqlx::PgPool;
+
+/// State of an in-progress game saved on shutdown, so it can be resumed after a restart.
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow)]
+pub struct GameSnapshot {
+    pub game_id: Uuid,
+    pub white_player_id: String,
+    pub black_player_id: String,
+    pub board: Json<Board>,
+    pub chat: Json<Vec<ChatMessage>>,
+}
+
+impl GameSnapshot {
+    pub async fn insert(&self, pool: &Pool) -> sqlx::Result<u64> {
+        let res = sqlx::query!(
+            "insert into game_snapshots (game_id, white_player_id, black_player_id, board, chat) values ($1, $2, $3, $4, $5) on conflict (game_id) do update set board = excluded.board, chat = excluded.chat, created_at = CURRENT_TIMESTAMP;",
+            self.game_id,
+            self.white_player_id,
+            self.black_player_id,
+            &self.board as _,
+            &self.chat as _
+        )
+        .execute(pool)
+        .await?;
+        Ok(res.rows_affected())
+    }
+
+    /// Every saved snapshot. They stay in the database until their game is saved again on
+    /// shutdown or recorded, so a crash after a restart does not lose them.
+    pub async fn get_all(pool: &Pool) -> sqlx::Result<Vec<Self>> {
+        sqlx::query_as!(
+            GameSnapshot,
+            r#"select game_id, white_player_id, black_player_id, board as "board: Json<Board>", chat as "chat: Json<Vec<ChatMessage>>" from game_snapshots"#
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn delete(game_id: Uuid, pool: &Pool) -> sqlx::Result<u64> {
+        let res = sqlx::query!("delete from game_snapshots where game_id = $1;", game_id)
+            .execute(pool)
+            .await?;
+        Ok(res.rows_affected())
+    }
+}
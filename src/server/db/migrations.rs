@@ -0,0 +1,138 @@
+use std::fmt::Display;
+
+use sqlx::migrate::{Migrate, MigrateError, Migrator};
+use sqlx::PgPool;
+use tracing::info;
+
+/// Migrations of the `migrations` folder, embedded in the binary.
+pub static MIGRATOR: Migrator = sqlx::migrate!();
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchemaStatus {
+    pub applied: Vec<i64>,
+    pub pending: Vec<i64>,
+    pub dirty: Option<i64>,
+}
+
+impl SchemaStatus {
+    pub fn current_version(&self) -> Option<i64> {
+        self.applied.last().copied()
+    }
+
+    pub fn latest_version() -> Option<i64> {
+        up_migrations().map(|(version, _)| version).max()
+    }
+}
+
+#[derive(Debug)]
+pub enum SchemaError {
+    Migrate(MigrateError),
+    Dirty(i64),
+    Behind {
+        current: Option<i64>,
+        pending: Vec<i64>,
+    },
+}
+
+impl Display for SchemaError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SchemaError::Migrate(err) => write!(f, "could not check database schema: {}", err),
+            SchemaError::Dirty(version) => write!(
+                f,
+                "migration {} failed halfway, fix the database before starting the server.",
+                version
+            ),
+            SchemaError::Behind { current, pending } => {
+                let current = current.map_or_else(|| "none".to_owned(), |v| v.to_string());
+                write!(
+                    f,
+                    "database schema is out of date (current version: {}, pending migrations: {:?}), run `hex-chess-admin migrate run` or enable `database.run_migrations`.",
+                    current, pending
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for SchemaError {}
+
+impl From<MigrateError> for SchemaError {
+    fn from(value: MigrateError) -> Self {
+        SchemaError::Migrate(value)
+    }
+}
+
+fn up_migrations() -> impl Iterator<Item = (i64, &'static str)> {
+    MIGRATOR
+        .iter()
+        .filter(|migration| !migration.migration_type.is_down_migration())
+        .map(|migration| (migration.version, migration.description.as_ref()))
+}
+
+pub fn description(version: i64) -> Option<&'static str> {
+    up_migrations()
+        .find(|(v, _)| *v == version)
+        .map(|(_, description)| description)
+}
+
+pub async fn status(pool: &PgPool) -> Result<SchemaStatus, MigrateError> {
+    let mut conn = pool.acquire().await?;
+    conn.ensure_migrations_table().await?;
+    let dirty = conn.dirty_version().await?;
+    let mut applied: Vec<i64> = conn
+        .list_applied_migrations()
+        .await?
+        .into_iter()
+        .map(|migration| migration.version)
+        .collect();
+    applied.sort_unstable();
+
+    let pending = up_migrations()
+        .map(|(version, _)| version)
+        .filter(|version| applied.binary_search(version).is_err())
+        .collect();
+
+    Ok(SchemaStatus {
+        applied,
+        pending,
+        dirty,
+    })
+}
+
+pub async fn run(pool: &PgPool) -> Result<(), MigrateError> {
+    MIGRATOR.run(pool).await?;
+    info!(version = ?SchemaStatus::latest_version(), "database migrations applied");
+    Ok(())
+}
+
+/// Revert the applied migrations down to `target`, or the last applied one if `None`.
+pub async fn rollback(pool: &PgPool, target: Option<i64>) -> Result<i64, MigrateError> {
+    let target = match target {
+        Some(target) => target,
+        None => {
+            let status = status(pool).await?;
+            let mut applied = status.applied.into_iter().rev();
+            applied.next();
+            applied.next().unwrap_or(0)
+        }
+    };
+    MIGRATOR.undo(pool, target).await?;
+    info!(target, "database migrations reverted");
+    Ok(target)
+}
+
+/// Refuse to go further if the database is not at the latest schema version.
+pub async fn check_schema(pool: &PgPool) -> Result<(), SchemaError> {
+    let status = status(pool).await?;
+    if let Some(version) = status.dirty {
+        return Err(SchemaError::Dirty(version));
+    }
+    if !status.pending.is_empty() {
+        return Err(SchemaError::Behind {
+            current: status.current_version(),
+            pending: status.pending,
+        });
+    }
+    Ok(())
+}
@@ -1,11 +1,21 @@
 use actix_web::web;
 use sqlx::PgPool;
 
-pub mod user;
+use crate::server::config::DatabaseConfig;
 
-pub async fn create_db_pool() -> sqlx::Result<web::Data<PgPool>> {
-    let db_url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set.");
+pub mod audit;
+pub mod block;
+pub mod correspondence_game;
+pub mod follow;
+pub mod game_record;
+pub mod game_snapshot;
+pub mod migrations;
+pub mod notification;
+pub mod push_subscription;
+pub mod report;
+pub mod user;
 
-    let pool = PgPool::connect(&db_url).await?;
+pub async fn create_db_pool(config: &DatabaseConfig) -> sqlx::Result<web::Data<PgPool>> {
+    let pool = PgPool::connect(&config.url).await?;
     Ok(web::Data::new(pool))
 }
@@ -0,0 +1,74 @@
+use uuid::Uuid;
+
+type Pool = sqlx::PgPool;
+
+#[derive(Debug, sqlx::FromRow)]
+pub struct Notification {
+    pub id: i32,
+    pub user_id: String,
+    pub kind: String,
+    pub game_id: Option<Uuid>,
+    pub read: bool,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl Notification {
+    pub async fn insert(
+        user_id: &str,
+        kind: &str,
+        game_id: Option<Uuid>,
+        pool: &Pool,
+    ) -> sqlx::Result<Self> {
+        sqlx::query_as!(
+            Notification,
+            "insert into notifications (user_id, kind, game_id) values ($1, $2, $3) returning id, user_id, kind, game_id, read, created_at",
+            user_id,
+            kind,
+            game_id
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    /// Latest notifications of a user, newest first.
+    pub async fn get_recent(user_id: &str, limit: i64, pool: &Pool) -> sqlx::Result<Vec<Self>> {
+        sqlx::query_as!(
+            Notification,
+            "select id, user_id, kind, game_id, read, created_at from notifications where user_id = $1 order by created_at desc limit $2",
+            user_id,
+            limit
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn count_unread(user_id: &str, pool: &Pool) -> sqlx::Result<i64> {
+        let res = sqlx::query!(
+            r#"select count(*) as "count!" from notifications where user_id = $1 and not read"#,
+            user_id
+        )
+        .fetch_one(pool)
+        .await?;
+        Ok(res.count)
+    }
+
+    pub async fn delete_older_than(
+        cutoff: chrono::DateTime<chrono::Utc>,
+        pool: &Pool,
+    ) -> sqlx::Result<u64> {
+        let res = sqlx::query!("delete from notifications where created_at < $1", cutoff)
+            .execute(pool)
+            .await?;
+        Ok(res.rows_affected())
+    }
+
+    pub async fn mark_all_read(user_id: &str, pool: &Pool) -> sqlx::Result<u64> {
+        let res = sqlx::query!(
+            "update notifications set read = true where user_id = $1 and not read",
+            user_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(res.rows_affected())
+    }
+}
@@ -0,0 +1,50 @@
+type Pool = sqlx::PgPool;
+
+/// A browser registered for Web Push by a user, `p256dh` and `auth` are the keys used to
+/// encrypt the messages sent to `endpoint`.
+#[derive(Debug, sqlx::FromRow)]
+pub struct PushSubscription {
+    pub endpoint: String,
+    pub user_id: String,
+    pub p256dh: String,
+    pub auth: String,
+}
+
+impl PushSubscription {
+    /// Save the subscription or update the keys of the user's existing one, an endpoint
+    /// registered by another user is left untouched and 0 is returned.
+    pub async fn upsert(&self, pool: &Pool) -> sqlx::Result<u64> {
+        let res = sqlx::query!(
+            "insert into push_subscriptions (endpoint, user_id, p256dh, auth) values ($1, $2, $3, $4) on conflict (endpoint) do update set p256dh = excluded.p256dh, auth = excluded.auth where push_subscriptions.user_id = excluded.user_id;",
+            self.endpoint,
+            self.user_id,
+            self.p256dh,
+            self.auth
+        )
+        .execute(pool)
+        .await?;
+        Ok(res.rows_affected())
+    }
+
+    pub async fn get_for_user(user_id: &str, pool: &Pool) -> sqlx::Result<Vec<Self>> {
+        sqlx::query_as!(
+            PushSubscription,
+            "select endpoint, user_id, p256dh, auth from push_subscriptions where user_id = $1",
+            user_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Remove a subscription, only its owner can remove it when `user_id` is set.
+    pub async fn delete(endpoint: &str, user_id: Option<&str>, pool: &Pool) -> sqlx::Result<u64> {
+        let res = sqlx::query!(
+            "delete from push_subscriptions where endpoint = $1 and ($2::varchar is null or user_id = $2)",
+            endpoint,
+            user_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(res.rows_affected())
+    }
+}
@@ -0,0 +1,66 @@
+use uuid::Uuid;
+
+type Pool = sqlx::PgPool;
+
+pub const STATUS_RESOLVED: &str = "resolved";
+pub const STATUS_DISMISSED: &str = "dismissed";
+
+#[derive(Debug, sqlx::FromRow)]
+pub struct Report {
+    pub id: i32,
+    pub reporter_id: String,
+    pub reported_id: String,
+    pub game_id: Option<Uuid>,
+    pub reason: String,
+    pub comment: Option<String>,
+    pub status: String,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl Report {
+    /// Add a report to the moderation queue, a player can only report a game once.
+    pub async fn insert(
+        reporter_id: &str,
+        reported_id: &str,
+        game_id: Uuid,
+        reason: &str,
+        comment: Option<&str>,
+        pool: &Pool,
+    ) -> sqlx::Result<u64> {
+        let res = sqlx::query!(
+            "insert into reports (reporter_id, reported_id, game_id, reason, comment) values ($1, $2, $3, $4, $5) on conflict do nothing;",
+            reporter_id,
+            reported_id,
+            game_id,
+            reason,
+            comment
+        )
+        .execute(pool)
+        .await?;
+        Ok(res.rows_affected())
+    }
+
+    /// Open reports, oldest first.
+    pub async fn get_queue(limit: i64, pool: &Pool) -> sqlx::Result<Vec<Self>> {
+        sqlx::query_as!(
+            Report,
+            "select id, reporter_id, reported_id, game_id, reason, comment, status, created_at from reports where status = 'open' order by created_at limit $1",
+            limit
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Close an open report with `status`, recording the admin who triaged it.
+    pub async fn triage(id: i32, status: &str, admin_id: &str, pool: &Pool) -> sqlx::Result<u64> {
+        let res = sqlx::query!(
+            "update reports set status = $1, resolved_by = $2, resolved_at = CURRENT_TIMESTAMP where id = $3 and status = 'open'",
+            status,
+            admin_id,
+            id
+        )
+        .execute(pool)
+        .await?;
+        Ok(res.rows_affected())
+    }
+}
@@ -1,5 +1,10 @@
 type Pool = sqlx::PgPool;
 
+pub const ROLE_PLAYER: &str = "player";
+pub const ROLE_ADMIN: &str = "admin";
+/// Name shown for a user id with no row in `users`.
+pub const DEFAULT_USERNAME: &str = "player";
+
 #[derive(Debug, sqlx::FromRow)]
 pub struct User {
     pub id: i32,
@@ -7,6 +12,8 @@ pub struct User {
     pub username: String,
     pub created_at: chrono::DateTime<chrono::Utc>,
     pub updated_at: chrono::DateTime<chrono::Utc>,
+    pub banned_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub role: String,
 }
 
 impl User {
@@ -30,12 +37,50 @@ impl User {
         Ok(res.rows_affected())
     }
 
+    /// Generate a placeholder username, used for new accounts and username resets.
+    pub fn generate_username() -> String {
+        let id = uuid::Uuid::new_v4().simple().to_string();
+        format!("player-{}", &id[..8])
+    }
+
+    /// Create the user row on first login, does nothing if it already exists.
+    pub async fn ensure_exists(user_id: &str, pool: &Pool) -> sqlx::Result<u64> {
+        let res = sqlx::query!(
+            "insert into users (user_id, username) values ($1, $2) on conflict (user_id) do nothing;",
+            user_id,
+            Self::generate_username()
+        )
+        .execute(pool)
+        .await?;
+        Ok(res.rows_affected())
+    }
+
     pub async fn get_from_user_id(user_id: &str, pool: &Pool) -> sqlx::Result<Self> {
         sqlx::query_as!(User, "select * from users where user_id = $1", user_id)
             .fetch_one(pool)
             .await
     }
 
+    pub async fn get_username(user_id: &str, pool: &Pool) -> sqlx::Result<Option<String>> {
+        let record = sqlx::query!("select username from users where user_id = $1", user_id)
+            .fetch_optional(pool)
+            .await?;
+        Ok(record.map(|record| record.username))
+    }
+
+    /// Look a user up by the public id shown in urls.
+    pub async fn get_from_id(id: i32, pool: &Pool) -> sqlx::Result<Option<Self>> {
+        sqlx::query_as!(User, "select * from users where id = $1", id)
+            .fetch_optional(pool)
+            .await
+    }
+
+    pub async fn get_from_username(username: &str, pool: &Pool) -> sqlx::Result<Option<Self>> {
+        sqlx::query_as!(User, "select * from users where username = $1", username)
+            .fetch_optional(pool)
+            .await
+    }
+
     pub async fn get_id_from_user_id(user_id: &str, pool: &Pool) -> sqlx::Result<i32> {
         let record = sqlx::query!("select id from users where user_id = $1", user_id)
             .fetch_one(pool)
@@ -58,4 +103,65 @@ impl User {
 
         Ok(res.rows_affected())
     }
+
+    /// Ban a user, banning them again keeps the date of the first ban.
+    pub async fn ban_from_user_id(user_id: &str, pool: &Pool) -> sqlx::Result<u64> {
+        let res = sqlx::query!(
+            "update users set banned_at = coalesce(banned_at, CURRENT_TIMESTAMP) where user_id = $1",
+            user_id
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(res.rows_affected())
+    }
+
+    pub async fn unban_from_user_id(user_id: &str, pool: &Pool) -> sqlx::Result<u64> {
+        let res = sqlx::query!(
+            "update users set banned_at = null where user_id = $1",
+            user_id
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(res.rows_affected())
+    }
+
+    /// Users without a row in the table are not admins.
+    pub async fn is_admin_from_user_id(user_id: &str, pool: &Pool) -> sqlx::Result<bool> {
+        let record = sqlx::query!(
+            r#"select role = 'admin' as "is_admin!" from users where user_id = $1"#,
+            user_id
+        )
+        .fetch_optional(pool)
+        .await?;
+        Ok(record.is_some_and(|record| record.is_admin))
+    }
+
+    pub async fn set_role_from_user_id(
+        user_id: &str,
+        role: &str,
+        pool: &Pool,
+    ) -> sqlx::Result<u64> {
+        let res = sqlx::query!(
+            "update users set role = $1 where user_id = $2",
+            role,
+            user_id
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(res.rows_affected())
+    }
+
+    /// Users without a row in the table are not banned.
+    pub async fn is_banned(user_id: &str, pool: &Pool) -> sqlx::Result<bool> {
+        let record = sqlx::query!(
+            r#"select banned_at is not null as "banned!" from users where user_id = $1"#,
+            user_id
+        )
+        .fetch_optional(pool)
+        .await?;
+        Ok(record.is_some_and(|record| record.banned))
+    }
 }
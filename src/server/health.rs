@@ -0,0 +1,61 @@
+use actix_web::{get, web, HttpResponse, Responder};
+use serde::Serialize;
+use sqlx::PgPool;
+
+use crate::server::{auth::auth_client::AuthClient, board::server::Games};
+
+#[derive(Debug, Serialize)]
+struct Readiness {
+    database: Result<(), String>,
+    auth: Result<(), String>,
+    shutting_down: bool,
+}
+
+impl Readiness {
+    fn is_ready(&self) -> bool {
+        self.database.is_ok() && self.auth.is_ok() && !self.shutting_down
+    }
+}
+
+#[get("/healthz")]
+async fn healthz() -> impl Responder {
+    HttpResponse::Ok().body("ok")
+}
+
+#[get("/readyz")]
+async fn readyz(
+    pool: web::Data<PgPool>,
+    auth_client: web::Data<AuthClient>,
+    games: web::Data<Games>,
+) -> impl Responder {
+    let database = async {
+        sqlx::query("select 1")
+            .execute(pool.get_ref())
+            .await
+            .map(|_| ())
+            .map_err(|err| err.to_string())
+    };
+    let auth = async {
+        auth_client
+            .check_reachable()
+            .await
+            .map_err(|err| err.to_string())
+    };
+    let (database, auth) = futures::join!(database, auth);
+
+    let readiness = Readiness {
+        database,
+        auth,
+        shutting_down: games.is_shutting_down(),
+    };
+
+    if readiness.is_ready() {
+        HttpResponse::Ok().json(readiness)
+    } else {
+        HttpResponse::ServiceUnavailable().json(readiness)
+    }
+}
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(healthz).service(readyz);
+}
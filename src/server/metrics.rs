@@ -0,0 +1,140 @@
+use actix_web::{error, get, web, HttpResponse};
+use hex_chess_core::board::GameEnd;
+use prometheus::{
+    Encoder, Histogram, HistogramOpts, IntCounter, IntCounterVec, IntGauge, Opts, Registry,
+    TextEncoder,
+};
+
+/// Prometheus metrics of the game server, exposed on `/metrics`.
+pub struct Metrics {
+    registry: Registry,
+    pub games: IntGauge,
+    pub custom_games: IntGauge,
+    pub waiting_room: IntGauge,
+    pub spectators: IntGauge,
+    pub sse_connections: IntGauge,
+    pub moves_played: IntCounter,
+    pub illegal_moves: IntCounter,
+    pub games_ended: IntCounterVec,
+    pub move_latency: Histogram,
+    pub ping_duration: Histogram,
+    pub ping_timeouts: IntCounter,
+    pub chat_messages: IntCounter,
+    pub notifications: IntCounter,
+    pub push_messages: IntCounter,
+}
+
+fn register<T: prometheus::core::Collector + Clone + 'static>(
+    registry: &Registry,
+    metric: T,
+) -> prometheus::Result<T> {
+    registry.register(Box::new(metric.clone()))?;
+    Ok(metric)
+}
+
+impl Metrics {
+    pub fn new() -> prometheus::Result<Self> {
+        let registry = Registry::new_custom(Some("hex_chess".into()), None)?;
+
+        let games = IntGauge::new("games", "Number of started games in memory.")?;
+        let custom_games = IntGauge::new(
+            "custom_games",
+            "Number of custom games waiting for an opponent.",
+        )?;
+        let waiting_room = IntGauge::new(
+            "waiting_room",
+            "Number of players waiting for a random game.",
+        )?;
+        let spectators = IntGauge::new("spectators", "Number of connected spectators.")?;
+        let sse_connections = IntGauge::new(
+            "sse_connections",
+            "Number of open game event streams, as of the last stale games sweep.",
+        )?;
+        let moves_played = IntCounter::new("moves_played_total", "Number of moves played.")?;
+        let illegal_moves = IntCounter::new(
+            "illegal_moves_total",
+            "Number of moves rejected by the server.",
+        )?;
+        let games_ended = IntCounterVec::new(
+            Opts::new(
+                "games_ended_total",
+                "Number of ended games, by kind of end.",
+            ),
+            &["kind"],
+        )?;
+        let move_latency = Histogram::with_opts(
+            HistogramOpts::new(
+                "move_latency_seconds",
+                "Time to validate a move and forward it to the opponent and spectators.",
+            )
+            .buckets(vec![0.001, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0]),
+        )?;
+        let ping_duration = Histogram::with_opts(
+            HistogramOpts::new(
+                "ping_duration_seconds",
+                "Time taken by the connection checks of the event streams.",
+            )
+            .buckets(vec![0.001, 0.01, 0.05, 0.1, 0.25, 0.5, 1.0, 2.0, 5.0]),
+        )?;
+        let ping_timeouts = IntCounter::new(
+            "ping_timeouts_total",
+            "Number of connection checks that timed out.",
+        )?;
+        let chat_messages =
+            IntCounter::new("chat_messages_total", "Number of chat messages broadcast.")?;
+        let notifications =
+            IntCounter::new("notifications_total", "Number of notifications created.")?;
+        let push_messages = IntCounter::new(
+            "push_messages_total",
+            "Number of Web Push messages accepted by push services.",
+        )?;
+
+        Ok(Metrics {
+            games: register(&registry, games)?,
+            custom_games: register(&registry, custom_games)?,
+            waiting_room: register(&registry, waiting_room)?,
+            spectators: register(&registry, spectators)?,
+            sse_connections: register(&registry, sse_connections)?,
+            moves_played: register(&registry, moves_played)?,
+            illegal_moves: register(&registry, illegal_moves)?,
+            games_ended: register(&registry, games_ended)?,
+            move_latency: register(&registry, move_latency)?,
+            ping_duration: register(&registry, ping_duration)?,
+            ping_timeouts: register(&registry, ping_timeouts)?,
+            chat_messages: register(&registry, chat_messages)?,
+            notifications: register(&registry, notifications)?,
+            push_messages: register(&registry, push_messages)?,
+            registry,
+        })
+    }
+
+    pub fn game_ended(&self, kind: &str) {
+        self.games_ended.with_label_values(&[kind]).inc();
+    }
+
+    pub fn encode(&self) -> prometheus::Result<Vec<u8>> {
+        let mut buffer = vec![];
+        TextEncoder::new().encode(&self.registry.gather(), &mut buffer)?;
+        Ok(buffer)
+    }
+}
+
+pub fn game_end_kind(end: &GameEnd) -> &'static str {
+    match end {
+        GameEnd::Win(_) => "win",
+        GameEnd::Draw => "draw",
+        GameEnd::Stalemate { .. } => "stalemate",
+    }
+}
+
+#[get("/metrics")]
+async fn metrics(metrics: web::Data<Metrics>) -> actix_web::Result<HttpResponse> {
+    let body = metrics.encode().map_err(error::ErrorInternalServerError)?;
+    Ok(HttpResponse::Ok()
+        .content_type(TextEncoder::new().format_type())
+        .body(body))
+}
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(metrics);
+}
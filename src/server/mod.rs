@@ -1,24 +1,45 @@
+pub mod admin;
 pub mod board;
+pub mod moderation;
+pub mod notifications;
+pub mod profile;
+pub mod simul;
+pub mod tournaments;
 
+#[cfg(feature = "ssr")]
+pub mod admin_api;
 #[cfg(feature = "ssr")]
 pub mod auth;
 #[cfg(feature = "ssr")]
+pub mod config;
+#[cfg(feature = "ssr")]
 pub mod db;
+#[cfg(feature = "ssr")]
+pub mod health;
+#[cfg(feature = "ssr")]
+pub mod metrics;
+#[cfg(feature = "ssr")]
+pub mod rate_limit;
+#[cfg(feature = "ssr")]
+pub mod telemetry;
 
 #[cfg(feature = "ssr")]
 #[derive(Debug, Clone)]
 pub struct BaseUrl(pub String);
 
 #[cfg(feature = "ssr")]
-impl BaseUrl {
-    pub fn new() -> Self {
-        let base_url = std::env::var("BASE_URL").expect("BASE URL must be set.");
-        BaseUrl(base_url)
-    }
+pub async fn get_pool(
+    cx: leptos::Scope,
+) -> Result<actix_web::web::Data<sqlx::PgPool>, leptos::ServerFnError> {
+    leptos_actix::extract(cx, |pool: actix_web::web::Data<sqlx::PgPool>| async move {
+        pool
+    })
+    .await
 }
+
 #[cfg(feature = "ssr")]
-impl Default for BaseUrl {
-    fn default() -> Self {
-        Self::new()
+impl BaseUrl {
+    pub fn new(config: &config::ServerConfig) -> Self {
+        BaseUrl(config.base_url.clone())
     }
 }
@@ -0,0 +1,119 @@
+use leptos::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[cfg(feature = "ssr")]
+pub mod server;
+
+pub const MAX_REPORT_COMMENT_LEN: usize = 1000;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ReportReason {
+    Cheating,
+    Harassment,
+    OffensiveUsername,
+    Other,
+}
+
+impl ReportReason {
+    pub const ALL: [ReportReason; 4] = [
+        ReportReason::Cheating,
+        ReportReason::Harassment,
+        ReportReason::OffensiveUsername,
+        ReportReason::Other,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ReportReason::Cheating => "cheating",
+            ReportReason::Harassment => "harassment",
+            ReportReason::OffensiveUsername => "offensive_username",
+            ReportReason::Other => "other",
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct BlockedUserSummary {
+    pub id: i32,
+    pub username: String,
+}
+
+/// Report the opponent of a finished game, optionally blocking them at the same time.
+#[server(ReportPlayer, "/api")]
+pub async fn report_player(
+    cx: Scope,
+    game_id: Uuid,
+    reason: ReportReason,
+    comment: Option<String>,
+    block: bool,
+) -> Result<(), ServerFnError> {
+    use crate::server::db::{block::Block, report::Report};
+
+    let comment = comment.filter(|comment| !comment.trim().is_empty());
+    if comment
+        .as_ref()
+        .is_some_and(|comment| comment.chars().count() > MAX_REPORT_COMMENT_LEN)
+    {
+        return Err(ServerFnError::Args("comment is too long.".into()));
+    }
+
+    let player_id = crate::server::board::server::get_player_id(cx).await?;
+    let pool = crate::server::get_pool(cx).await?;
+    let opponent_id = server::get_finished_game_opponent(cx, game_id, &player_id).await?;
+
+    Report::insert(
+        &player_id,
+        &opponent_id,
+        game_id,
+        reason.as_str(),
+        comment.as_deref(),
+        &pool,
+    )
+    .await?;
+    if block {
+        Block::insert(&player_id, &opponent_id, &pool).await?;
+    }
+    Ok(())
+}
+
+#[server(BlockOpponent, "/api")]
+pub async fn block_opponent(cx: Scope, game_id: Uuid) -> Result<(), ServerFnError> {
+    use crate::server::db::block::Block;
+
+    let player_id = crate::server::board::server::get_player_id(cx).await?;
+    let pool = crate::server::get_pool(cx).await?;
+    let opponent_id = server::get_finished_game_opponent(cx, game_id, &player_id).await?;
+
+    Block::insert(&player_id, &opponent_id, &pool).await?;
+    Ok(())
+}
+
+#[server(GetBlockedUsers, "/api")]
+pub async fn get_blocked_users(cx: Scope) -> Result<Vec<BlockedUserSummary>, ServerFnError> {
+    use crate::server::db::block::Block;
+
+    let player_id = crate::server::board::server::get_player_id(cx).await?;
+    let pool = crate::server::get_pool(cx).await?;
+
+    let users = Block::get_blocked_users(&player_id, &pool).await?;
+    Ok(users
+        .into_iter()
+        .map(|user| BlockedUserSummary {
+            id: user.id,
+            username: user.username,
+        })
+        .collect())
+}
+
+#[server(UnblockUser, "/api")]
+pub async fn unblock_user(cx: Scope, id: i32) -> Result<(), ServerFnError> {
+    use crate::server::db::block::Block;
+
+    let player_id = crate::server::board::server::get_player_id(cx).await?;
+    let pool = crate::server::get_pool(cx).await?;
+
+    Block::delete(&player_id, id, &pool).await?;
+    Ok(())
+}
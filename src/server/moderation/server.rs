@@ -0,0 +1,34 @@
+use leptos::ServerFnError;
+use uuid::Uuid;
+
+use crate::server::{
+    board::server::{get_games, GameError},
+    db::game_record::GameRecord,
+    get_pool,
+};
+
+/// Find the opponent of `player_id` in a finished game, either still in memory or already
+/// recorded in the database.
+pub async fn get_finished_game_opponent(
+    cx: leptos::Scope,
+    game_id: Uuid,
+    player_id: &str,
+) -> Result<String, ServerFnError> {
+    let games = get_games(cx).await?;
+    let players = match games.get_ended_game_players(game_id).await {
+        Some(players) => players,
+        None => {
+            let pool = get_pool(cx).await?;
+            let record = GameRecord::get(game_id, &pool)
+                .await?
+                .ok_or(GameError::InvalidGameId(game_id))?;
+            (record.white_player_id, record.black_player_id)
+        }
+    };
+
+    match players {
+        (white, black) if white == player_id => Ok(black),
+        (white, black) if black == player_id => Ok(white),
+        _ => Err(GameError::InvalidPlayerId { game_id }.into()),
+    }
+}
@@ -0,0 +1,149 @@
+use leptos::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[cfg(feature = "ssr")]
+pub mod push;
+#[cfg(feature = "ssr")]
+pub mod server;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationKind {
+    /// The opponent moved while the player was away from the game page.
+    OpponentMoved,
+    /// Same as `OpponentMoved` for a correspondence game, also sent by Web Push.
+    CorrespondenceMove,
+    /// Another player challenged the user, also sent by Web Push.
+    Challenge,
+    GameEnded,
+}
+
+impl NotificationKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            NotificationKind::OpponentMoved => "opponent_moved",
+            NotificationKind::CorrespondenceMove => "correspondence_move",
+            NotificationKind::Challenge => "challenge",
+            NotificationKind::GameEnded => "game_ended",
+        }
+    }
+
+    pub fn parse(kind: &str) -> Option<Self> {
+        match kind {
+            "opponent_moved" => Some(NotificationKind::OpponentMoved),
+            "correspondence_move" => Some(NotificationKind::CorrespondenceMove),
+            "challenge" => Some(NotificationKind::Challenge),
+            "game_ended" => Some(NotificationKind::GameEnded),
+            _ => None,
+        }
+    }
+
+    /// Whether the notification is also sent to the user's browsers by Web Push.
+    pub fn is_pushed(self) -> bool {
+        matches!(
+            self,
+            NotificationKind::CorrespondenceMove | NotificationKind::Challenge
+        )
+    }
+
+    /// Page the notification leads to.
+    pub fn url(self, game_id: Option<Uuid>) -> String {
+        match (self, game_id) {
+            (NotificationKind::Challenge, Some(game_id)) => format!("/challenge/{}", game_id),
+            (_, Some(game_id)) => format!("/play/{}", game_id),
+            (_, None) => "/games".into(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct NotificationSummary {
+    pub id: i32,
+    pub kind: NotificationKind,
+    pub game_id: Option<Uuid>,
+    pub read: bool,
+    pub created_at: String,
+}
+
+/// Content of the notification centre: the latest notifications and how many are unread.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct NotificationCentre {
+    pub notifications: Vec<NotificationSummary>,
+    pub unread: i64,
+}
+
+/// Event sent on the notification stream of a user.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub enum NotificationEvent {
+    Notification(NotificationSummary),
+}
+
+#[cfg(feature = "ssr")]
+impl From<&NotificationEvent> for actix_web_lab::sse::Event {
+    fn from(val: &NotificationEvent) -> Self {
+        actix_web_lab::sse::Data::new_json(val).unwrap().into()
+    }
+}
+
+#[server(GetNotifications, "/api")]
+pub async fn get_notifications(cx: Scope) -> Result<NotificationCentre, ServerFnError> {
+    let user_id = crate::server::board::server::get_player_id(cx).await?;
+    server::get_notification_centre(cx, &user_id).await
+}
+
+#[server(MarkNotificationsRead, "/api")]
+pub async fn mark_notifications_read(cx: Scope) -> Result<(), ServerFnError> {
+    use crate::server::db::notification::Notification;
+
+    let user_id = crate::server::board::server::get_player_id(cx).await?;
+    let pool = crate::server::get_pool(cx).await?;
+    Notification::mark_all_read(&user_id, &pool).await?;
+    Ok(())
+}
+
+/// VAPID public key browsers subscribe with, `None` when Web Push is disabled.
+#[server(GetPushPublicKey, "/api")]
+pub async fn get_push_public_key(cx: Scope) -> Result<Option<String>, ServerFnError> {
+    let notifier = server::get_notifier(cx).await?;
+    Ok(notifier.push_public_key().map(ToOwned::to_owned))
+}
+
+#[server(SavePushSubscription, "/api")]
+pub async fn save_push_subscription(
+    cx: Scope,
+    endpoint: String,
+    p256dh: String,
+    auth: String,
+) -> Result<(), ServerFnError> {
+    use crate::server::db::push_subscription::PushSubscription;
+
+    let user_id = crate::server::board::server::get_player_id(cx).await?;
+    let notifier = server::get_notifier(cx).await?;
+    if !notifier.allows_push_endpoint(&endpoint) {
+        return Err(ServerFnError::Args("invalid push endpoint.".into()));
+    }
+    let pool = crate::server::get_pool(cx).await?;
+    let subscription = PushSubscription {
+        endpoint,
+        user_id,
+        p256dh,
+        auth,
+    };
+    if subscription.upsert(&pool).await? == 0 {
+        return Err(ServerFnError::Args(
+            "push endpoint registered by another user.".into(),
+        ));
+    }
+    Ok(())
+}
+
+#[server(RemovePushSubscription, "/api")]
+pub async fn remove_push_subscription(cx: Scope, endpoint: String) -> Result<(), ServerFnError> {
+    use crate::server::db::push_subscription::PushSubscription;
+
+    let user_id = crate::server::board::server::get_player_id(cx).await?;
+    let pool = crate::server::get_pool(cx).await?;
+    PushSubscription::delete(&endpoint, Some(&user_id), &pool).await?;
+    Ok(())
+}
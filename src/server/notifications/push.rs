@@ -0,0 +1,223 @@
+use std::fmt::Display;
+use std::net::IpAddr;
+
+use serde::Serialize;
+use web_push::{
+    ContentEncoding, SubscriptionInfo, VapidSignatureBuilder, WebPushError, WebPushMessageBuilder,
+};
+
+use crate::server::config::NotificationsConfig;
+use crate::server::db::push_subscription::PushSubscription;
+
+use super::NotificationKind;
+
+/// Message read by the service worker to show the notification.
+#[derive(Debug, Serialize)]
+pub struct PushPayload {
+    pub kind: NotificationKind,
+    pub url: String,
+}
+
+#[derive(Debug)]
+pub enum PushError {
+    Encrypt(WebPushError),
+    Request(reqwest::Error),
+    /// The browser unsubscribed, the subscription must be forgotten.
+    Gone,
+    Rejected(reqwest::StatusCode),
+    /// The endpoint is not on an allowed push service.
+    Forbidden,
+}
+
+impl Display for PushError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PushError::Encrypt(err) => write!(f, "could not build push message: {}", err),
+            PushError::Request(err) => write!(f, "could not reach push service: {}", err),
+            PushError::Gone => f.write_str("push subscription expired."),
+            PushError::Rejected(status) => write!(f, "push service answered {}", status),
+            PushError::Forbidden => f.write_str("push endpoint is not an allowed push service."),
+        }
+    }
+}
+
+impl std::error::Error for PushError {}
+
+impl From<WebPushError> for PushError {
+    fn from(err: WebPushError) -> Self {
+        PushError::Encrypt(err)
+    }
+}
+
+impl From<reqwest::Error> for PushError {
+    fn from(err: reqwest::Error) -> Self {
+        PushError::Request(err)
+    }
+}
+
+/// Sends encrypted Web Push messages signed with the server VAPID key.
+pub struct WebPush {
+    public_key: String,
+    private_key_pem: Vec<u8>,
+    subject: String,
+    ttl: u32,
+    hosts: Vec<String>,
+    allow_loopback: bool,
+    client: reqwest::Client,
+}
+
+impl WebPush {
+    /// Read the VAPID private key, `None` when Web Push is disabled.
+    pub fn new(config: &NotificationsConfig) -> std::io::Result<Option<Self>> {
+        if !config.push_enabled {
+            return Ok(None);
+        }
+        Ok(Some(WebPush {
+            public_key: config.vapid_public_key.clone(),
+            private_key_pem: std::fs::read(&config.vapid_private_key_file)?,
+            subject: config.vapid_subject.clone(),
+            ttl: config.push_ttl_secs,
+            hosts: config.push_hosts.clone(),
+            allow_loopback: config.push_allow_loopback,
+            // a push service has no reason to redirect, following would escape the host check
+            client: reqwest::Client::builder()
+                .redirect(reqwest::redirect::Policy::none())
+                .build()
+                .map_err(|err| std::io::Error::new(std::io::ErrorKind::Other, err))?,
+        }))
+    }
+
+    /// Whether messages can be sent to `endpoint`: an https URL on one of the configured push
+    /// services, never an internal address the server could be made to reach.
+    pub fn allows_endpoint(&self, endpoint: &str) -> bool {
+        let Ok(url) = reqwest::Url::parse(endpoint) else {
+            return false;
+        };
+        let Some(host) = url.host_str().map(str::to_ascii_lowercase) else {
+            return false;
+        };
+        // addresses are only given by the local push sink, push services have a domain
+        if let Ok(ip) = host
+            .trim_start_matches('[')
+            .trim_end_matches(']')
+            .parse::<IpAddr>()
+        {
+            return self.allow_loopback && ip.is_loopback();
+        }
+        url.scheme() == "https"
+            && host != "localhost"
+            && self
+                .hosts
+                .iter()
+                .any(|allowed| host == *allowed || host.ends_with(&format!(".{}", allowed)))
+    }
+
+    pub fn public_key(&self) -> &str {
+        &self.public_key
+    }
+
+    pub async fn send(
+        &self,
+        subscription: &PushSubscription,
+        payload: &PushPayload,
+    ) -> Result<(), PushError> {
+        if !self.allows_endpoint(&subscription.endpoint) {
+            return Err(PushError::Forbidden);
+        }
+        let info = SubscriptionInfo::new(
+            &subscription.endpoint,
+            &subscription.p256dh,
+            &subscription.auth,
+        );
+        let mut signature =
+            VapidSignatureBuilder::from_pem(self.private_key_pem.as_slice(), &info)?;
+        signature.add_claim("sub", self.subject.as_str());
+
+        let content = serde_json::to_vec(payload).expect("push payload is serializable");
+        let mut builder = WebPushMessageBuilder::new(&info);
+        builder.set_ttl(self.ttl);
+        builder.set_payload(ContentEncoding::Aes128Gcm, &content);
+        builder.set_vapid_signature(signature.build()?);
+        let message = builder.build()?;
+
+        let request: http::Request<Vec<u8>> = web_push::request_builder::build_request(message);
+        let response = self.client.execute(request.try_into()?).await?;
+        match response.status() {
+            status if status.is_success() => Ok(()),
+            reqwest::StatusCode::NOT_FOUND | reqwest::StatusCode::GONE => Err(PushError::Gone),
+            status => Err(PushError::Rejected(status)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn web_push(allow_loopback: bool) -> WebPush {
+        WebPush {
+            public_key: String::new(),
+            private_key_pem: vec![],
+            subject: String::new(),
+            ttl: 0,
+            hosts: vec!["mozilla.com".to_owned()],
+            allow_loopback,
+            client: reqwest::Client::new(),
+        }
+    }
+
+    #[test]
+    fn allows_push_service_and_subdomains() {
+        let push = web_push(false);
+        assert!(push.allows_endpoint("https://mozilla.com/wpush/v2/abc"));
+        assert!(push.allows_endpoint("https://updates.push.mozilla.com/wpush/v2/abc"));
+        assert!(push.allows_endpoint("https://Push.Mozilla.com/wpush/v2/abc"));
+    }
+
+    #[test]
+    fn rejects_look_alike_hosts() {
+        let push = web_push(false);
+        assert!(!push.allows_endpoint("https://evilmozilla.com/wpush/v2/abc"));
+        assert!(!push.allows_endpoint("https://mozilla.com.evil.net/wpush/v2/abc"));
+        assert!(!push.allows_endpoint("https://fcm.googleapis.com/fcm/send/abc"));
+    }
+
+    #[test]
+    fn rejects_plain_http() {
+        assert!(!web_push(false).allows_endpoint("http://mozilla.com/wpush/v2/abc"));
+    }
+
+    #[test]
+    fn rejects_localhost() {
+        for allow_loopback in [false, true] {
+            let push = web_push(allow_loopback);
+            assert!(!push.allows_endpoint("https://localhost/push"));
+            assert!(!push.allows_endpoint("http://localhost:8080/push"));
+        }
+    }
+
+    #[test]
+    fn allows_loopback_addresses_only_when_enabled() {
+        for endpoint in ["http://127.0.0.1:8080/push", "http://[::1]:8080/push"] {
+            assert!(web_push(true).allows_endpoint(endpoint), "{}", endpoint);
+            assert!(!web_push(false).allows_endpoint(endpoint), "{}", endpoint);
+        }
+        for endpoint in [
+            "https://10.0.0.1/push",
+            "https://169.254.169.254/latest",
+            "https://[fe80::1]/push",
+            "https://[2001:db8::1]/push",
+        ] {
+            assert!(!web_push(true).allows_endpoint(endpoint), "{}", endpoint);
+            assert!(!web_push(false).allows_endpoint(endpoint), "{}", endpoint);
+        }
+    }
+
+    #[test]
+    fn rejects_unparseable_endpoints() {
+        let push = web_push(true);
+        for endpoint in ["", "not a url", "https://", "mozilla.com/wpush/v2/abc"] {
+            assert!(!push.allows_endpoint(endpoint), "{}", endpoint);
+        }
+    }
+}
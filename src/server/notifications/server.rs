@@ -0,0 +1,267 @@
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::time::Duration;
+
+use actix_web::{error, get, web};
+use actix_web_lab::sse;
+use futures::lock::Mutex;
+use leptos::ServerFnError;
+use leptos_actix::extract;
+use sqlx::PgPool;
+use tracing::{debug, instrument, warn};
+use uuid::Uuid;
+
+use crate::server::auth::user_id::MaybeUserId;
+use crate::server::board::server::test_connection;
+use crate::server::config::NotificationsConfig;
+use crate::server::db::notification::Notification;
+use crate::server::db::push_subscription::PushSubscription;
+use crate::server::get_pool;
+use crate::server::metrics::Metrics;
+
+use super::push::{PushError, PushPayload, WebPush};
+use super::{NotificationCentre, NotificationEvent, NotificationKind, NotificationSummary};
+
+/// How often the notifications past their retention are deleted.
+const RETENTION_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Kind of a stored notification that this version of the server doesn't know.
+#[derive(Debug)]
+pub struct UnknownKind(String);
+
+impl Display for UnknownKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "unknown notification kind `{}`", self.0)
+    }
+}
+
+impl std::error::Error for UnknownKind {}
+
+impl TryFrom<Notification> for NotificationSummary {
+    type Error = UnknownKind;
+
+    fn try_from(notification: Notification) -> Result<Self, Self::Error> {
+        let kind =
+            NotificationKind::parse(&notification.kind).ok_or(UnknownKind(notification.kind))?;
+        Ok(NotificationSummary {
+            id: notification.id,
+            kind,
+            game_id: notification.game_id,
+            read: notification.read,
+            created_at: notification.created_at.to_rfc3339(),
+        })
+    }
+}
+
+/// Stores the notifications of the users and delivers them on their notification streams, and
+/// by Web Push for the kinds that need it.
+pub struct Notifier {
+    /// Open notification streams of each user, one per tab.
+    streams: Mutex<HashMap<String, Vec<sse::Sender>>>,
+    push: Option<WebPush>,
+    pool: web::Data<PgPool>,
+    config: NotificationsConfig,
+    ping_timeout: Duration,
+    metrics: web::Data<Metrics>,
+}
+
+impl Notifier {
+    pub fn new(
+        config: NotificationsConfig,
+        push: Option<WebPush>,
+        pool: web::Data<PgPool>,
+        ping_timeout: Duration,
+        metrics: web::Data<Metrics>,
+    ) -> Self {
+        Notifier {
+            streams: Mutex::new(HashMap::new()),
+            push,
+            pool,
+            config,
+            ping_timeout,
+            metrics,
+        }
+    }
+
+    pub fn push_public_key(&self) -> Option<&str> {
+        self.push.as_ref().map(WebPush::public_key)
+    }
+
+    /// Whether a browser can subscribe with `endpoint`, never when Web Push is disabled.
+    pub fn allows_push_endpoint(&self, endpoint: &str) -> bool {
+        self.push
+            .as_ref()
+            .is_some_and(|push| push.allows_endpoint(endpoint))
+    }
+
+    pub async fn connect(&self, user_id: String) -> sse::Sse<sse::ChannelStream> {
+        let (sender, stream) = sse::channel(10);
+        self.streams
+            .lock()
+            .await
+            .entry(user_id)
+            .or_default()
+            .push(sender);
+        stream
+    }
+
+    /// Store a notification and deliver it to the open tabs of the user, and to their browsers
+    /// by Web Push if `kind` needs it.
+    #[instrument(skip(self))]
+    pub async fn notify(&self, user_id: &str, kind: NotificationKind, game_id: Option<Uuid>) {
+        let notification =
+            match Notification::insert(user_id, kind.as_str(), game_id, &self.pool).await {
+                Ok(notification) => notification,
+                Err(err) => {
+                    warn!(error = %err, "could not save notification");
+                    return;
+                }
+            };
+        let notification = match NotificationSummary::try_from(notification) {
+            Ok(notification) => notification,
+            Err(err) => {
+                warn!(error = %err, "could not read saved notification");
+                return;
+            }
+        };
+        self.metrics.notifications.inc();
+
+        let event = NotificationEvent::Notification(notification);
+        if let Some(senders) = self.streams.lock().await.get(user_id) {
+            let futs = senders.iter().map(|sender| sender.send(&event));
+            let _ = futures::future::join_all(futs).await; // dead streams are pruned later
+        }
+
+        if kind.is_pushed() {
+            self.push(user_id, kind, game_id).await;
+        }
+    }
+
+    async fn push(&self, user_id: &str, kind: NotificationKind, game_id: Option<Uuid>) {
+        let Some(push) = &self.push else {
+            return;
+        };
+        let subscriptions = match PushSubscription::get_for_user(user_id, &self.pool).await {
+            Ok(subscriptions) => subscriptions,
+            Err(err) => {
+                warn!(error = %err, "could not load push subscriptions");
+                return;
+            }
+        };
+        let payload = PushPayload {
+            kind,
+            url: kind.url(game_id),
+        };
+        for subscription in subscriptions {
+            match push.send(&subscription, &payload).await {
+                Ok(()) => self.metrics.push_messages.inc(),
+                Err(err @ (PushError::Gone | PushError::Forbidden)) => {
+                    debug!(endpoint = %subscription.endpoint, error = %err, "removing push subscription");
+                    let _ =
+                        PushSubscription::delete(&subscription.endpoint, None, &self.pool).await;
+                }
+                Err(err) => warn!(error = %err, "could not send push message"),
+            }
+        }
+    }
+
+    /// Drop the notification streams whose tab was closed.
+    pub async fn prune(&self) {
+        let mut streams = self.streams.lock().await;
+        for senders in streams.values_mut() {
+            let futs = senders
+                .iter()
+                .map(|sender| test_connection(sender, self.ping_timeout, &self.metrics));
+            let res = futures::future::join_all(futs).await;
+            let mut iter = res.into_iter();
+            senders.retain(|_| iter.next().unwrap());
+        }
+        streams.retain(|_, senders| !senders.is_empty());
+    }
+
+    /// Delete the notifications older than the retention period.
+    pub async fn prune_old_notifications(&self) {
+        let retention = chrono::Duration::days(self.config.retention_days.into());
+        match Notification::delete_older_than(chrono::Utc::now() - retention, &self.pool).await {
+            Ok(deleted) if deleted > 0 => debug!(deleted, "deleted old notifications"),
+            Ok(_) => (),
+            Err(err) => warn!(error = %err, "could not delete old notifications"),
+        }
+    }
+}
+
+/// Deliver a notification in the background, the caller does not wait for Web Push.
+pub fn spawn_notify(
+    notifier: web::Data<Notifier>,
+    user_id: String,
+    kind: NotificationKind,
+    game_id: Option<Uuid>,
+) {
+    actix_web::rt::spawn(async move { notifier.notify(&user_id, kind, game_id).await });
+}
+
+/// Spawn the background tasks that periodically drop the closed notification streams and the
+/// notifications past their retention.
+pub fn spawn_prune(notifier: web::Data<Notifier>, interval: Duration) {
+    let streams_notifier = notifier.clone();
+    actix_web::rt::spawn(async move {
+        let mut interval = actix_web::rt::time::interval(interval);
+        loop {
+            interval.tick().await;
+            streams_notifier.prune().await;
+        }
+    });
+    actix_web::rt::spawn(async move {
+        let mut interval = actix_web::rt::time::interval(RETENTION_INTERVAL);
+        loop {
+            interval.tick().await;
+            notifier.prune_old_notifications().await;
+        }
+    });
+}
+
+pub async fn get_notifier(cx: leptos::Scope) -> Result<web::Data<Notifier>, ServerFnError> {
+    extract(cx, |notifier: web::Data<Notifier>| async move { notifier }).await
+}
+
+pub async fn get_notification_centre(
+    cx: leptos::Scope,
+    user_id: &str,
+) -> Result<NotificationCentre, ServerFnError> {
+    let pool = get_pool(cx).await?;
+    let notifier = get_notifier(cx).await?;
+    let notifications =
+        Notification::get_recent(user_id, notifier.config.history_len.into(), &pool).await?;
+    let unread = Notification::count_unread(user_id, &pool).await?;
+    let notifications = notifications
+        .into_iter()
+        .filter_map(
+            |notification| match NotificationSummary::try_from(notification) {
+                Ok(notification) => Some(notification),
+                Err(err) => {
+                    warn!(error = %err, "skipping notification");
+                    None
+                }
+            },
+        )
+        .collect();
+    Ok(NotificationCentre {
+        notifications,
+        unread,
+    })
+}
+
+#[get("stream")]
+async fn stream(
+    notifier: web::Data<Notifier>,
+    user_id: MaybeUserId,
+) -> actix_web::Result<sse::Sse<sse::ChannelStream>> {
+    let user_id = user_id
+        .0
+        .ok_or_else(|| error::ErrorUnauthorized("player must be logged in."))?;
+    Ok(notifier.connect(user_id).await)
+}
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(stream);
+}
@@ -0,0 +1,101 @@
+use leptos::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[cfg(feature = "ssr")]
+pub mod server;
+
+/// How often clients poll the presence of their friends.
+pub const FRIENDS_REFRESH: std::time::Duration = std::time::Duration::from_secs(15);
+
+/// Public page of a player, found by the id of their user row.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct PlayerProfile {
+    pub id: i32,
+    pub username: String,
+    /// The profile of the current user, who can't challenge themselves.
+    pub is_me: bool,
+    /// Whether the current user follows this player.
+    pub is_followed: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Presence {
+    Offline,
+    /// Has the site open.
+    Online,
+    /// Playing the live game with this id, it can be spectated.
+    Playing(Uuid),
+}
+
+/// A player followed by the current user, with what they are doing right now.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct Friend {
+    pub id: i32,
+    pub username: String,
+    /// They follow the current user back.
+    pub is_mutual: bool,
+    pub presence: Presence,
+}
+
+#[server(GetPlayerProfile, "/api")]
+pub async fn get_player_profile(cx: Scope, id: i32) -> Result<PlayerProfile, ServerFnError> {
+    use crate::server::db::{follow::Follow, user::User};
+
+    let pool = crate::server::get_pool(cx).await?;
+    let user_id = server::get_user_id(cx).await?;
+    let user = User::get_from_id(id, &pool)
+        .await?
+        .filter(|user| user.banned_at.is_none())
+        .ok_or_else(|| ServerFnError::Args("this player doesn't exist.".into()))?;
+    let is_followed = match &user_id {
+        Some(user_id) => Follow::is_following(user_id, id, &pool).await?,
+        None => false,
+    };
+    Ok(PlayerProfile {
+        id: user.id,
+        is_me: user_id.as_deref() == Some(user.user_id.as_str()),
+        is_followed,
+        username: user.username,
+    })
+}
+
+/// Public id of the player with this exact username.
+#[server(FindPlayer, "/api")]
+pub async fn find_player(cx: Scope, username: String) -> Result<Option<i32>, ServerFnError> {
+    use crate::server::db::user::User;
+
+    let pool = crate::server::get_pool(cx).await?;
+    let user = User::get_from_username(username.trim(), &pool).await?;
+    Ok(user
+        .filter(|user| user.banned_at.is_none())
+        .map(|user| user.id))
+}
+
+#[server(FollowPlayer, "/api")]
+pub async fn follow_player(cx: Scope, id: i32) -> Result<(), ServerFnError> {
+    use crate::server::db::follow::Follow;
+
+    let player_id = crate::server::board::server::get_player_id(cx).await?;
+    let pool = crate::server::get_pool(cx).await?;
+    Follow::insert(&player_id, id, &pool).await?;
+    Ok(())
+}
+
+#[server(UnfollowPlayer, "/api")]
+pub async fn unfollow_player(cx: Scope, id: i32) -> Result<(), ServerFnError> {
+    use crate::server::db::follow::Follow;
+
+    let player_id = crate::server::board::server::get_player_id(cx).await?;
+    let pool = crate::server::get_pool(cx).await?;
+    Follow::delete(&player_id, id, &pool).await?;
+    Ok(())
+}
+
+/// Players followed by the current user, friends first and then by presence.
+#[server(GetFriends, "/api")]
+pub async fn get_friends(cx: Scope) -> Result<Vec<Friend>, ServerFnError> {
+    let player_id = crate::server::board::server::get_player_id(cx).await?;
+    server::get_friends(cx, &player_id).await
+}
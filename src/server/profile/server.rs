@@ -0,0 +1,54 @@
+use leptos::ServerFnError;
+use leptos_actix::extract;
+
+use crate::server::auth::user_id::MaybeUserId;
+use crate::server::board::server::get_games;
+use crate::server::db::block::Block;
+use crate::server::db::follow::Follow;
+use crate::server::get_pool;
+
+use super::{Friend, Presence};
+
+/// Id of the current user if they are logged in, profiles are public.
+pub async fn get_user_id(cx: leptos::Scope) -> Result<Option<String>, ServerFnError> {
+    extract(cx, |user_id: MaybeUserId| async move { user_id.0 }).await
+}
+
+fn presence_rank(presence: Presence) -> u8 {
+    match presence {
+        Presence::Playing(_) => 0,
+        Presence::Online => 1,
+        Presence::Offline => 2,
+    }
+}
+
+/// Followed players with their presence: playing if they are in a live game, online if they
+/// have a stream open on the game server. Players blocked either way are left out.
+pub async fn get_friends(cx: leptos::Scope, player_id: &str) -> Result<Vec<Friend>, ServerFnError> {
+    let pool = get_pool(cx).await?;
+    let games = get_games(cx).await?;
+
+    let blocked = Block::get_related_ids(player_id, &pool).await?;
+    let followed = Follow::get_followed_users(player_id, &pool).await?;
+    let games = &games;
+    let friends = followed
+        .into_iter()
+        .filter(|user| !blocked.contains(&user.user_id))
+        .map(|user| async move {
+            let presence = match games.playing_game(&user.user_id).await {
+                Some(game_id) => Presence::Playing(game_id),
+                None if games.is_online(&user.user_id).await => Presence::Online,
+                None => Presence::Offline,
+            };
+            Friend {
+                id: user.id,
+                username: user.username,
+                is_mutual: user.follows_back,
+                presence,
+            }
+        });
+    let mut friends = futures::future::join_all(friends).await;
+    // stable sort, the usernames stay in order within each group
+    friends.sort_by_key(|friend| (!friend.is_mutual, presence_rank(friend.presence)));
+    Ok(friends)
+}
@@ -0,0 +1,196 @@
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::net::IpAddr;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use actix_session::SessionExt;
+use actix_web::{
+    body::MessageBody,
+    dev::{ServiceRequest, ServiceResponse},
+    get, http, web, HttpResponse, ResponseError,
+};
+use actix_web_lab::middleware::Next;
+use tracing::debug;
+
+use crate::server::{
+    auth::user_id::{session_user_id, MaybeUserId},
+    board::server::{GameError, Games},
+    config::RateLimitConfig,
+};
+
+const PRUNE_INTERVAL: Duration = Duration::from_secs(60);
+
+struct Bucket {
+    tokens: f64,
+    updated_at: Instant,
+}
+
+/// Token buckets refilled at a constant rate, indexed by a client key.
+struct Buckets {
+    burst: f64,
+    per_sec: f64,
+    buckets: Mutex<HashMap<String, Bucket>>,
+}
+
+impl Buckets {
+    fn new(burst: u32, per_sec: f64) -> Self {
+        Buckets {
+            burst: burst.into(),
+            per_sec,
+            buckets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn refilled(&self, bucket: &Bucket, now: Instant) -> f64 {
+        let elapsed = now.duration_since(bucket.updated_at).as_secs_f64();
+        (bucket.tokens + elapsed * self.per_sec).min(self.burst)
+    }
+
+    /// Take a token from the bucket of `key`, or return how long to wait for the next one.
+    fn take(&self, key: &str) -> Result<(), Duration> {
+        let now = Instant::now();
+        let mut buckets = self.buckets.lock().unwrap();
+        let bucket = buckets.entry(key.to_owned()).or_insert(Bucket {
+            tokens: self.burst,
+            updated_at: now,
+        });
+        bucket.tokens = self.refilled(bucket, now);
+        bucket.updated_at = now;
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64(
+                (1.0 - bucket.tokens) / self.per_sec,
+            ))
+        }
+    }
+
+    /// Forget the buckets that are full again, they behave like new ones.
+    fn prune(&self) {
+        let now = Instant::now();
+        let mut buckets = self.buckets.lock().unwrap();
+        buckets.retain(|_, bucket| self.refilled(bucket, now) < self.burst);
+    }
+}
+
+pub struct RateLimiter {
+    enabled: bool,
+    trusted_proxies: Vec<IpAddr>,
+    per_ip: Buckets,
+    per_user: Buckets,
+}
+
+impl RateLimiter {
+    pub fn new(config: &RateLimitConfig) -> Self {
+        RateLimiter {
+            enabled: config.enabled,
+            trusted_proxies: config.trusted_proxies.clone(),
+            per_ip: Buckets::new(config.ip_burst, config.ip_per_sec),
+            per_user: Buckets::new(config.user_burst, config.user_per_sec),
+        }
+    }
+
+    pub fn check(&self, ip: Option<&str>, user_id: Option<&str>) -> Result<(), RateLimited> {
+        if !self.enabled {
+            return Ok(());
+        }
+        let res = ip
+            .map_or(Ok(()), |ip| self.per_ip.take(ip))
+            .and_then(|_| user_id.map_or(Ok(()), |user_id| self.per_user.take(user_id)));
+        res.map_err(|retry_after| RateLimited { retry_after })
+    }
+
+    /// Address of the client, from the forwarded headers only when the peer is a trusted proxy
+    /// since any client can set them.
+    fn client_ip(&self, req: &ServiceRequest) -> Option<String> {
+        let peer = req.peer_addr()?.ip();
+        if self.trusted_proxies.contains(&peer) {
+            if let Some(ip) = req.connection_info().realip_remote_addr() {
+                return Some(ip.to_owned());
+            }
+        }
+        Some(peer.to_string())
+    }
+
+    pub fn prune(&self) {
+        self.per_ip.prune();
+        self.per_user.prune();
+    }
+}
+
+#[derive(Debug)]
+pub struct RateLimited {
+    retry_after: Duration,
+}
+
+impl Display for RateLimited {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("too many requests, slow down.")
+    }
+}
+
+impl ResponseError for RateLimited {
+    fn status_code(&self) -> http::StatusCode {
+        http::StatusCode::TOO_MANY_REQUESTS
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        let retry_after = self.retry_after.as_secs_f64().ceil() as u64;
+        HttpResponse::TooManyRequests()
+            .insert_header((http::header::RETRY_AFTER, retry_after.max(1)))
+            .body(self.to_string())
+    }
+}
+
+/// Middleware throttling the `/api` requests by client address and by logged in user.
+///
+/// The user is only known if their id is already cached in the session, the address bucket
+/// still applies otherwise.
+pub async fn rate_limit(
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<impl MessageBody>, actix_web::Error> {
+    if req.path().starts_with("/api/") {
+        if let Some(limiter) = req.app_data::<web::Data<RateLimiter>>() {
+            let ip = limiter.client_ip(&req);
+            let user_id = session_user_id(&req.get_session());
+            if let Err(err) = limiter.check(ip.as_deref(), user_id.as_deref()) {
+                debug!(?ip, ?user_id, path = req.path(), "request rate limited");
+                return Err(err.into());
+            }
+        }
+    }
+    next.call(req).await
+}
+
+/// Spawn the background task that forgets idle buckets.
+pub fn spawn_prune(limiter: web::Data<RateLimiter>) {
+    actix_web::rt::spawn(async move {
+        let mut interval = actix_web::rt::time::interval(PRUNE_INTERVAL);
+        loop {
+            interval.tick().await;
+            limiter.prune();
+        }
+    });
+}
+
+/// Lets the client find out if a failed request was throttled, event streams don't expose the
+/// status code of their responses.
+///
+/// Answers `429` if the client is out of tokens or has reached the custom games cap.
+#[get("/api/rate_limit")]
+async fn rate_limit_status(
+    games: web::Data<Games>,
+    user_id: MaybeUserId,
+) -> Result<HttpResponse, GameError> {
+    if let Some(user_id) = user_id.0 {
+        games.check_custom_game_limit(&user_id).await?;
+    }
+    Ok(HttpResponse::NoContent().finish())
+}
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(rate_limit_status);
+}
@@ -0,0 +1,125 @@
+use leptos::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::server::tournaments::PairingResult;
+
+#[cfg(feature = "ssr")]
+pub mod server;
+
+/// How often clients poll the boards of a simul.
+pub const SIMUL_REFRESH: std::time::Duration = std::time::Duration::from_secs(5);
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SimulStatus {
+    /// Challengers can join until the host starts the simul.
+    Open,
+    Running,
+    Finished,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct SimulSummary {
+    pub id: Uuid,
+    pub host: String,
+    pub status: SimulStatus,
+    pub challengers: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct SimulBoard {
+    pub challenger: String,
+    /// `None` until the simul starts.
+    pub game_id: Option<Uuid>,
+    /// Result from the point of view of the host, who plays white on every board. `None`
+    /// while the game is played.
+    pub result: Option<PairingResult>,
+    /// The challenger has the simul page open and gets their board when it starts.
+    pub ready: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct SimulDetails {
+    pub summary: SimulSummary,
+    pub boards: Vec<SimulBoard>,
+    pub is_host: bool,
+    pub is_challenger: bool,
+}
+
+impl SimulDetails {
+    /// Points of the host and of the challengers on the finished boards, in quarter points.
+    pub fn score(&self) -> (u32, u32) {
+        self.boards
+            .iter()
+            .filter_map(|board| board.result)
+            .map(PairingResult::points)
+            .fold((0, 0), |(host, challengers), (white, black)| {
+                (host + white, challengers + black)
+            })
+    }
+
+    /// Wins, draws and losses of the host.
+    pub fn host_record(&self) -> (usize, usize, usize) {
+        let (mut wins, mut draws, mut losses) = (0, 0, 0);
+        for (white, black) in self
+            .boards
+            .iter()
+            .filter_map(|board| board.result)
+            .map(PairingResult::points)
+        {
+            match white.cmp(&black) {
+                std::cmp::Ordering::Greater => wins += 1,
+                std::cmp::Ordering::Equal => draws += 1,
+                std::cmp::Ordering::Less => losses += 1,
+            }
+        }
+        (wins, draws, losses)
+    }
+}
+
+#[server(GetSimuls, "/api")]
+pub async fn get_simuls(cx: Scope) -> Result<Vec<SimulSummary>, ServerFnError> {
+    let simuls = server::get_simuls(cx).await?;
+    Ok(simuls.summaries().await)
+}
+
+#[server(GetSimul, "/api")]
+pub async fn get_simul(cx: Scope, id: Uuid) -> Result<SimulDetails, ServerFnError> {
+    let simuls = server::get_simuls(cx).await?;
+    let user_id = crate::server::profile::server::get_user_id(cx).await?;
+    Ok(simuls.details(id, user_id.as_deref()).await?)
+}
+
+#[server(CreateSimul, "/api")]
+pub async fn create_simul(cx: Scope) -> Result<Uuid, ServerFnError> {
+    let (player_id, username) = server::get_unbanned_user(cx).await?;
+    let simuls = server::get_simuls(cx).await?;
+    Ok(simuls.create(player_id, username).await?)
+}
+
+#[server(JoinSimul, "/api")]
+pub async fn join_simul(cx: Scope, id: Uuid) -> Result<(), ServerFnError> {
+    let (player_id, username) = server::get_unbanned_user(cx).await?;
+    let simuls = server::get_simuls(cx).await?;
+    simuls.join(id, player_id, username).await?;
+    Ok(())
+}
+
+#[server(LeaveSimul, "/api")]
+pub async fn leave_simul(cx: Scope, id: Uuid) -> Result<(), ServerFnError> {
+    let player_id = crate::server::board::server::get_player_id(cx).await?;
+    let simuls = server::get_simuls(cx).await?;
+    simuls.leave(id, &player_id).await?;
+    Ok(())
+}
+
+/// Start a game against every challenger waiting on the simul page, only the host can.
+#[server(StartSimul, "/api")]
+pub async fn start_simul(cx: Scope, id: Uuid) -> Result<(), ServerFnError> {
+    let player_id = crate::server::board::server::get_player_id(cx).await?;
+    let simuls = server::get_simuls(cx).await?;
+    let games = crate::server::board::server::get_games(cx).await?;
+    simuls.start(id, &player_id, &games).await?;
+    Ok(())
+}
@@ -0,0 +1,523 @@
+use std::collections::HashMap;
+use std::fmt::Display;
+
+use actix_web::{get, web};
+use actix_web_lab::sse;
+use chrono::{DateTime, Utc};
+use futures::lock::Mutex;
+use hex_chess_core::piece::Color;
+use leptos::ServerFnError;
+use leptos_actix::extract;
+use sqlx::PgPool;
+use tracing::{info, instrument, warn};
+use uuid::Uuid;
+
+use crate::server::auth::user_id::MaybeUserId;
+use crate::server::board::server::{check_id, GameError, Games, Player};
+use crate::server::board::GameEvent;
+use crate::server::config::SimulsConfig;
+use crate::server::db::user::User;
+use crate::server::tournaments::PairingResult;
+
+use super::{SimulBoard, SimulDetails, SimulStatus, SimulSummary};
+
+struct Challenger {
+    user_id: String,
+    username: String,
+    /// Lobby stream of the challenger while they wait for the simul to start.
+    ready: Option<Player>,
+    game_id: Option<Uuid>,
+    result: Option<PairingResult>,
+}
+
+struct Simul {
+    id: Uuid,
+    host_id: String,
+    host_name: String,
+    status: SimulStatus,
+    created_at: DateTime<Utc>,
+    finished_at: Option<DateTime<Utc>>,
+    challengers: Vec<Challenger>,
+    /// Stream shared by all the boards of the host, `None` until they open the simul page.
+    host: Option<sse::Sender>,
+    /// Set while the boards are started, without the simuls lock.
+    starting: bool,
+}
+
+impl Simul {
+    fn challenger_mut(&mut self, user_id: &str) -> Option<&mut Challenger> {
+        self.challengers
+            .iter_mut()
+            .find(|challenger| challenger.user_id == user_id)
+    }
+
+    fn summary(&self) -> SimulSummary {
+        SimulSummary {
+            id: self.id,
+            host: self.host_name.clone(),
+            status: self.status,
+            challengers: self.challengers.len(),
+        }
+    }
+
+    fn boards(&self) -> Vec<SimulBoard> {
+        self.challengers
+            .iter()
+            .map(|challenger| SimulBoard {
+                challenger: challenger.username.clone(),
+                game_id: challenger.game_id,
+                result: challenger.result,
+                ready: challenger.ready.is_some(),
+            })
+            .collect()
+    }
+
+    /// Boards still being played, to look their results up without holding the simuls lock.
+    fn running_games(&self) -> impl Iterator<Item = (Uuid, Uuid)> + '_ {
+        self.challengers
+            .iter()
+            .filter(|challenger| challenger.result.is_none())
+            .filter_map(move |challenger| Some((self.id, challenger.game_id?)))
+    }
+
+    /// Score a game that ended, the simul is over once every board is.
+    fn record_result(&mut self, game_id: Uuid, result: PairingResult) {
+        let Some(challenger) = self
+            .challengers
+            .iter_mut()
+            .find(|challenger| challenger.game_id == Some(game_id) && challenger.result.is_none())
+        else {
+            return;
+        };
+        info!(simul_id = %self.id, %game_id, ?result, "simul game ended");
+        challenger.result = Some(result);
+
+        if self
+            .challengers
+            .iter()
+            .all(|challenger| challenger.result.is_some())
+        {
+            info!(simul_id = %self.id, "simul finished");
+            self.status = SimulStatus::Finished;
+            self.finished_at = Some(Utc::now());
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SimulError {
+    InvalidSimulId(Uuid),
+    AlreadyHosting,
+    NotHost,
+    HostCannotJoin,
+    NotChallenger,
+    AlreadyStarted,
+    SimulFull { max_boards: usize },
+    HostNotConnected,
+    NoChallengers,
+    ShuttingDown,
+}
+
+impl Display for SimulError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SimulError::InvalidSimulId(id) => write!(f, "simul with id {} don't exist.", id),
+            SimulError::AlreadyHosting => f.write_str("you are already hosting a simul."),
+            SimulError::NotHost => f.write_str("only the host can start the simul."),
+            SimulError::HostCannotJoin => f.write_str("you can't challenge yourself."),
+            SimulError::NotChallenger => f.write_str("you did not join this simul."),
+            SimulError::AlreadyStarted => f.write_str("the simul already started."),
+            SimulError::SimulFull { max_boards } => {
+                write!(f, "a simul has at most {} boards.", max_boards)
+            }
+            SimulError::HostNotConnected => {
+                f.write_str("the simul page of the host must be open to start.")
+            }
+            SimulError::NoChallengers => f.write_str("no challenger is waiting."),
+            SimulError::ShuttingDown => f.write_str("the server is restarting, try again soon."),
+        }
+    }
+}
+
+impl actix_web::ResponseError for SimulError {
+    fn status_code(&self) -> http::StatusCode {
+        match self {
+            SimulError::InvalidSimulId(_) => http::StatusCode::NOT_FOUND,
+            SimulError::NotHost | SimulError::NotChallenger => http::StatusCode::FORBIDDEN,
+            SimulError::ShuttingDown => http::StatusCode::SERVICE_UNAVAILABLE,
+            _ => http::StatusCode::BAD_REQUEST,
+        }
+    }
+}
+
+impl std::error::Error for SimulError {}
+
+pub struct Simuls {
+    simuls: Mutex<HashMap<Uuid, Simul>>,
+    config: SimulsConfig,
+}
+
+impl Simuls {
+    pub fn new(config: SimulsConfig) -> Self {
+        Simuls {
+            simuls: Mutex::new(HashMap::new()),
+            config,
+        }
+    }
+
+    /// Open simuls first, then the running ones, newest first.
+    pub async fn summaries(&self) -> Vec<SimulSummary> {
+        let simuls = self.simuls.lock().await;
+        let mut simuls: Vec<&Simul> = simuls.values().collect();
+        simuls.sort_by_key(|simul| {
+            let status = match simul.status {
+                SimulStatus::Open => 0,
+                SimulStatus::Running => 1,
+                SimulStatus::Finished => 2,
+            };
+            (status, std::cmp::Reverse(simul.created_at))
+        });
+        simuls.into_iter().map(Simul::summary).collect()
+    }
+
+    pub async fn details(
+        &self,
+        id: Uuid,
+        user_id: Option<&str>,
+    ) -> Result<SimulDetails, SimulError> {
+        let simuls = self.simuls.lock().await;
+        let simul = simuls.get(&id).ok_or(SimulError::InvalidSimulId(id))?;
+        Ok(SimulDetails {
+            summary: simul.summary(),
+            boards: simul.boards(),
+            is_host: user_id == Some(simul.host_id.as_str()),
+            is_challenger: user_id.is_some_and(|user_id| {
+                simul
+                    .challengers
+                    .iter()
+                    .any(|challenger| challenger.user_id == user_id)
+            }),
+        })
+    }
+
+    #[instrument(skip(self))]
+    pub async fn create(&self, host_id: String, host_name: String) -> Result<Uuid, SimulError> {
+        let mut simuls = self.simuls.lock().await;
+        if simuls
+            .values()
+            .any(|simul| simul.host_id == host_id && simul.status != SimulStatus::Finished)
+        {
+            return Err(SimulError::AlreadyHosting);
+        }
+        let id = Uuid::new_v4();
+        simuls.insert(
+            id,
+            Simul {
+                id,
+                host_id,
+                host_name,
+                status: SimulStatus::Open,
+                created_at: Utc::now(),
+                finished_at: None,
+                challengers: vec![],
+                host: None,
+                starting: false,
+            },
+        );
+        info!(simul_id = %id, "created simul");
+        Ok(id)
+    }
+
+    #[instrument(skip(self, username))]
+    pub async fn join(
+        &self,
+        id: Uuid,
+        user_id: String,
+        username: String,
+    ) -> Result<(), SimulError> {
+        let mut simuls = self.simuls.lock().await;
+        let simul = simuls.get_mut(&id).ok_or(SimulError::InvalidSimulId(id))?;
+        if simul.host_id == user_id {
+            return Err(SimulError::HostCannotJoin);
+        }
+        if simul.status != SimulStatus::Open {
+            return Err(SimulError::AlreadyStarted);
+        }
+        if simul.challenger_mut(&user_id).is_some() {
+            return Ok(());
+        }
+        if simul.challengers.len() >= self.config.max_boards {
+            return Err(SimulError::SimulFull {
+                max_boards: self.config.max_boards,
+            });
+        }
+        simul.challengers.push(Challenger {
+            user_id,
+            username,
+            ready: None,
+            game_id: None,
+            result: None,
+        });
+        info!("player joined simul");
+        Ok(())
+    }
+
+    #[instrument(skip(self))]
+    pub async fn leave(&self, id: Uuid, user_id: &str) -> Result<(), SimulError> {
+        let mut simuls = self.simuls.lock().await;
+        let simul = simuls.get_mut(&id).ok_or(SimulError::InvalidSimulId(id))?;
+        if simul.status != SimulStatus::Open || simul.starting {
+            return Err(SimulError::AlreadyStarted);
+        }
+        let len = simul.challengers.len();
+        simul
+            .challengers
+            .retain(|challenger| challenger.user_id != user_id);
+        if simul.challengers.len() == len {
+            return Err(SimulError::NotChallenger);
+        }
+        info!("player left simul");
+        Ok(())
+    }
+
+    /// Start a board against every challenger waiting in the lobby, the host plays white on
+    /// all of them. Challengers who are not there are left out. The games are started without
+    /// the simuls lock, as each start pings both players.
+    #[instrument(skip(self, games))]
+    pub async fn start(&self, id: Uuid, host_id: &str, games: &Games) -> Result<(), SimulError> {
+        if games.is_shutting_down() {
+            return Err(SimulError::ShuttingDown);
+        }
+        let mut simuls = self.simuls.lock().await;
+        let simul = simuls.get_mut(&id).ok_or(SimulError::InvalidSimulId(id))?;
+        if simul.host_id != host_id {
+            return Err(SimulError::NotHost);
+        }
+        if simul.status != SimulStatus::Open || simul.starting {
+            return Err(SimulError::AlreadyStarted);
+        }
+        let host = simul.host.clone().ok_or(SimulError::HostNotConnected)?;
+        let mut ready: Vec<(String, Player)> = simul
+            .challengers
+            .iter_mut()
+            .filter_map(|challenger| Some((challenger.user_id.clone(), challenger.ready.take()?)))
+            .collect();
+        if ready.is_empty() {
+            return Err(SimulError::NoChallengers);
+        }
+        simul.starting = true;
+        drop(simuls);
+
+        let mut started = vec![];
+        let mut host_left = false;
+        // reversed to pop the challengers in their order
+        ready.reverse();
+        while let Some((user_id, player)) = ready.pop() {
+            let game_id = Uuid::new_v4();
+            let host = Player::simul_host(host.clone(), host_id.to_owned(), game_id);
+            match games.start_new_game(host, player, game_id).await {
+                Ok(()) => started.push((user_id, game_id)),
+                // the host left, the other challengers keep waiting
+                Err(Some(player)) if player.has_id(&user_id) => {
+                    ready.push((user_id, player));
+                    host_left = true;
+                    break;
+                }
+                Err(_) => {}
+            }
+        }
+
+        let mut simuls = self.simuls.lock().await;
+        let simul = simuls.get_mut(&id).ok_or(SimulError::InvalidSimulId(id))?;
+        simul.starting = false;
+        for (user_id, game_id) in started {
+            if let Some(challenger) = simul.challenger_mut(&user_id) {
+                challenger.game_id = Some(game_id);
+            }
+        }
+        for (user_id, player) in ready {
+            if let Some(challenger) = simul.challenger_mut(&user_id) {
+                challenger.ready.get_or_insert(player);
+            }
+        }
+        if simul
+            .challengers
+            .iter()
+            .all(|challenger| challenger.game_id.is_none())
+        {
+            simul.host = None;
+            return Err(if host_left {
+                SimulError::HostNotConnected
+            } else {
+                SimulError::NoChallengers
+            });
+        }
+        simul
+            .challengers
+            .retain(|challenger| challenger.game_id.is_some());
+        simul.status = SimulStatus::Running;
+        info!(boards = simul.challengers.len(), "simul started");
+        Ok(())
+    }
+
+    /// Stream of the host, receiving the events of all their boards. A host coming back to a
+    /// running simul gets their boards back on it.
+    pub async fn host_stream(
+        &self,
+        id: Uuid,
+        host_id: String,
+        games: &Games,
+    ) -> Result<sse::Sse<sse::ChannelStream>, SimulError> {
+        let mut simuls = self.simuls.lock().await;
+        let simul = simuls.get_mut(&id).ok_or(SimulError::InvalidSimulId(id))?;
+        if simul.host_id != host_id {
+            return Err(SimulError::NotHost);
+        }
+
+        // every board sends its moves and chat messages on the same stream
+        let (sender, stream) = sse::channel(10 * self.config.max_boards);
+        let game_ids: Vec<Uuid> = simul
+            .challengers
+            .iter()
+            .filter_map(|challenger| challenger.game_id)
+            .collect();
+        simul.host = Some(sender.clone());
+        drop(simuls);
+
+        let _ = sender.send(&GameEvent::WaitingForOpponent).await;
+        for game_id in game_ids {
+            match games.rejoin_simul_board(game_id, &host_id, &sender).await {
+                Ok(()) => {}
+                // the game is over and left the game server
+                Err(GameError::InvalidGameId(_)) => {}
+                Err(err) => warn!(%game_id, error = %err, "could not rejoin simul board"),
+            }
+        }
+        Ok(stream)
+    }
+
+    /// Wait on the simul page for the host to start, challengers whose board started are sent
+    /// to it.
+    pub async fn join_lobby(
+        &self,
+        id: Uuid,
+        user_id: String,
+    ) -> Result<sse::Sse<sse::ChannelStream>, SimulError> {
+        let mut simuls = self.simuls.lock().await;
+        let simul = simuls.get_mut(&id).ok_or(SimulError::InvalidSimulId(id))?;
+        let status = simul.status;
+        let challenger = simul
+            .challenger_mut(&user_id)
+            .ok_or(SimulError::NotChallenger)?;
+
+        let (player, stream) = Player::new_with_stream(user_id);
+        match challenger.game_id {
+            Some(game_id) => {
+                let _ = player.send(&GameEvent::start(game_id, Color::Black)).await;
+            }
+            None => {
+                let _ = player.send(&GameEvent::WaitingForOpponent).await;
+                if status == SimulStatus::Open {
+                    challenger.ready = Some(player);
+                }
+            }
+        }
+        Ok(stream)
+    }
+
+    /// Score the finished boards of the running simuls, and remove the simuls finished for
+    /// longer than `finished_ttl`. The results are looked up without the simuls lock.
+    pub async fn tick(&self, games: &Games, pool: &PgPool) {
+        let running: Vec<(Uuid, Uuid)> = self
+            .simuls
+            .lock()
+            .await
+            .values()
+            .filter(|simul| simul.status == SimulStatus::Running)
+            .flat_map(Simul::running_games)
+            .collect();
+        let mut results = vec![];
+        for (simul_id, game_id) in running {
+            if let Some(result) = games.game_result(game_id, pool).await {
+                results.push((simul_id, game_id, result));
+            }
+        }
+
+        let mut simuls = self.simuls.lock().await;
+        for (simul_id, game_id, result) in results {
+            if let Some(simul) = simuls.get_mut(&simul_id) {
+                simul.record_result(game_id, result);
+            }
+        }
+
+        let ttl = self.config.finished_ttl();
+        let now = Utc::now();
+        simuls.retain(|id, simul| {
+            let Some(finished_at) = simul.finished_at else {
+                return true;
+            };
+            let keep = (now - finished_at).to_std().map_or(true, |age| age < ttl);
+            if !keep {
+                info!(simul_id = %id, "removed finished simul");
+            }
+            keep
+        });
+    }
+}
+
+/// Spawn the background task that scores the simul boards.
+pub fn spawn_simul_ticks(
+    simuls: web::Data<Simuls>,
+    games: web::Data<Games>,
+    pool: web::Data<PgPool>,
+) {
+    actix_web::rt::spawn(async move {
+        let mut interval = actix_web::rt::time::interval(simuls.config.tick_interval());
+        loop {
+            interval.tick().await;
+            simuls.tick(&games, &pool).await;
+        }
+    });
+}
+
+pub async fn get_simuls(cx: leptos::Scope) -> Result<web::Data<Simuls>, ServerFnError> {
+    extract(cx, |simuls: web::Data<Simuls>| async move { simuls }).await
+}
+
+/// Id and username of the current user, banned users can't host or join a simul.
+pub async fn get_unbanned_user(cx: leptos::Scope) -> Result<(String, String), ServerFnError> {
+    let player_id = crate::server::board::server::get_player_id(cx).await?;
+    let pool = crate::server::get_pool(cx).await?;
+    let user = User::get_from_user_id(&player_id, &pool).await?;
+    if user.banned_at.is_some() {
+        return Err(GameError::PlayerBanned.into());
+    }
+    Ok((player_id, user.username))
+}
+
+#[get("{id}/host")]
+#[instrument(skip_all, fields(simul_id = %id))]
+async fn host(
+    simuls: web::Data<Simuls>,
+    games: web::Data<Games>,
+    id: web::Path<Uuid>,
+    user_id: MaybeUserId,
+) -> actix_web::Result<sse::Sse<sse::ChannelStream>> {
+    let user_id = check_id(user_id)?;
+    Ok(simuls.host_stream(id.into_inner(), user_id, &games).await?)
+}
+
+#[get("{id}/lobby")]
+#[instrument(skip_all, fields(simul_id = %id))]
+async fn lobby(
+    simuls: web::Data<Simuls>,
+    id: web::Path<Uuid>,
+    user_id: MaybeUserId,
+) -> actix_web::Result<sse::Sse<sse::ChannelStream>> {
+    let user_id = check_id(user_id)?;
+    Ok(simuls.join_lobby(id.into_inner(), user_id).await?)
+}
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(host).service(lobby);
+}
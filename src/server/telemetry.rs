@@ -0,0 +1,112 @@
+use std::fmt::Display;
+
+use actix_web::{get, http::StatusCode, put, web, HttpResponse, ResponseError};
+use tracing::info;
+use tracing_subscriber::{
+    filter::ParseError, fmt, prelude::*, reload, util::TryInitError, EnvFilter, Registry,
+};
+
+use crate::server::{auth::admin_token::AdminToken, config::LoggingConfig};
+
+/// Handle to the live log filter, used to change the log levels without restarting the server.
+#[derive(Clone)]
+pub struct LogLevelHandle(reload::Handle<EnvFilter, Registry>);
+
+#[derive(Debug)]
+pub enum TelemetryError {
+    InvalidFilter(ParseError),
+    Init(TryInitError),
+    Reload(reload::Error),
+}
+
+impl Display for TelemetryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TelemetryError::InvalidFilter(err) => write!(f, "invalid log filter: {}", err),
+            TelemetryError::Init(err) => write!(f, "could not initialize logging: {}", err),
+            TelemetryError::Reload(err) => write!(f, "could not change log filter: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for TelemetryError {}
+
+impl ResponseError for TelemetryError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            TelemetryError::InvalidFilter(_) => StatusCode::BAD_REQUEST,
+            TelemetryError::Init(_) | TelemetryError::Reload(_) => {
+                StatusCode::INTERNAL_SERVER_ERROR
+            }
+        }
+    }
+}
+
+impl From<ParseError> for TelemetryError {
+    fn from(value: ParseError) -> Self {
+        TelemetryError::InvalidFilter(value)
+    }
+}
+
+impl From<TryInitError> for TelemetryError {
+    fn from(value: TryInitError) -> Self {
+        TelemetryError::Init(value)
+    }
+}
+
+impl From<reload::Error> for TelemetryError {
+    fn from(value: reload::Error) -> Self {
+        TelemetryError::Reload(value)
+    }
+}
+
+/// Install the global tracing subscriber, writing either human readable or JSON logs.
+pub fn init(config: &LoggingConfig) -> Result<LogLevelHandle, TelemetryError> {
+    let filter = EnvFilter::try_new(&config.level)?;
+    let (filter, handle) = reload::Layer::new(filter);
+    let registry = tracing_subscriber::registry().with(filter);
+
+    if config.json {
+        registry.with(fmt::layer().json()).try_init()?;
+    } else {
+        registry.with(fmt::layer()).try_init()?;
+    }
+
+    Ok(LogLevelHandle(handle))
+}
+
+impl LogLevelHandle {
+    pub fn current(&self) -> Result<String, TelemetryError> {
+        Ok(self.0.with_current(|filter| filter.to_string())?)
+    }
+
+    pub fn set(&self, directives: &str) -> Result<(), TelemetryError> {
+        let filter = EnvFilter::try_new(directives)?;
+        self.0.reload(filter)?;
+        Ok(())
+    }
+}
+
+#[get("/log_level")]
+async fn get_log_level(
+    _admin: AdminToken,
+    handle: web::Data<LogLevelHandle>,
+) -> Result<String, TelemetryError> {
+    handle.current()
+}
+
+#[put("/log_level")]
+async fn set_log_level(
+    _admin: AdminToken,
+    handle: web::Data<LogLevelHandle>,
+    directives: String,
+) -> Result<HttpResponse, TelemetryError> {
+    let directives = directives.trim();
+    handle.set(directives)?;
+    info!(directives, "changed log filter");
+    Ok(HttpResponse::NoContent().finish())
+}
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(get_log_level).service(set_log_level);
+}
@@ -0,0 +1,256 @@
+//! Single and double elimination brackets.
+
+use super::BracketSide;
+
+/// Where the player of a match comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Slot {
+    /// Position in the seeding, a bye when there are fewer players.
+    Seed(usize),
+    Winner(usize),
+    Loser(usize),
+}
+
+/// Players of a match once it is decided, as indices in the seeding. `None` is a bye.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Outcome {
+    pub winner: Option<usize>,
+    pub loser: Option<usize>,
+}
+
+#[derive(Debug, Clone)]
+pub struct BracketMatch {
+    pub side: BracketSide,
+    /// Round in its side of the bracket, from 1.
+    pub round: u32,
+    /// Round of the tournament schedule the match is played in, from 1.
+    pub scheduled_round: u32,
+    pub slots: [Slot; 2],
+    pub outcome: Option<Outcome>,
+}
+
+impl BracketMatch {
+    fn new(side: BracketSide, round: u32, scheduled_round: u32, slots: [Slot; 2]) -> Self {
+        BracketMatch {
+            side,
+            round,
+            scheduled_round,
+            slots,
+            outcome: None,
+        }
+    }
+}
+
+/// Seed positions of the first round, the best seeds meet as late as possible: 0 plays
+/// `size - 1`, and the two best seeds are in opposite halves.
+fn seed_order(size: usize) -> Vec<usize> {
+    let mut order = vec![0];
+    while order.len() < size {
+        let len = order.len();
+        order = order
+            .into_iter()
+            .flat_map(|seed| [seed, 2 * len - 1 - seed])
+            .collect();
+    }
+    order
+}
+
+/// Matches of the winners bracket, each round after the previous one. Returns the indices of
+/// the matches of each round.
+fn winners_bracket(size: usize, matches: &mut Vec<BracketMatch>, double: bool) -> Vec<Vec<usize>> {
+    let order = seed_order(size);
+    let mut rounds: Vec<Vec<usize>> = vec![];
+    let mut round = 1;
+    let mut previous: Vec<Slot> = order.into_iter().map(Slot::Seed).collect();
+    while previous.len() > 1 {
+        // in a double elimination the winners rounds leave room for the losers bracket
+        let scheduled_round = if double && round > 1 {
+            2 * round - 2
+        } else {
+            round
+        };
+        let mut ids = vec![];
+        for pair in previous.chunks(2) {
+            ids.push(matches.len());
+            matches.push(BracketMatch::new(
+                BracketSide::Winners,
+                round,
+                scheduled_round,
+                [pair[0], pair[1]],
+            ));
+        }
+        previous = ids.iter().copied().map(Slot::Winner).collect();
+        rounds.push(ids);
+        round += 1;
+    }
+    rounds
+}
+
+/// Bracket for `players` players, a power of two is filled with byes for the best seeds.
+pub fn single_elimination(players: usize) -> Vec<BracketMatch> {
+    let size = players.max(2).next_power_of_two();
+    let mut matches = vec![];
+    winners_bracket(size, &mut matches, false);
+    matches
+}
+
+/// Bracket for `players` players where the losers of the winners bracket get a second chance
+/// in the losers bracket. Its champion meets the winners bracket champion in a single grand
+/// final.
+pub fn double_elimination(players: usize) -> Vec<BracketMatch> {
+    let size = players.max(2).next_power_of_two();
+    let mut matches = vec![];
+    let winners = winners_bracket(size, &mut matches, true);
+    let winners_rounds = winners.len() as u32;
+    let winners_final = Slot::Winner(winners[winners.len() - 1][0]);
+
+    // with two players the loser of the only match goes straight to the grand final
+    let mut losers_champion = Slot::Loser(winners[0][0]);
+    if size > 2 {
+        // first losers round: the losers of the first winners round meet each other
+        let mut previous: Vec<usize> = vec![];
+        for pair in winners[0].chunks(2) {
+            previous.push(matches.len());
+            matches.push(BracketMatch::new(
+                BracketSide::Losers,
+                1,
+                2,
+                [Slot::Loser(pair[0]), Slot::Loser(pair[1])],
+            ));
+        }
+        let mut round = 2;
+        for (j, dropping) in winners.iter().enumerate().skip(1) {
+            // the losers of a winners round meet the survivors, in reverse order every other
+            // round to avoid rematches
+            let mut ids = vec![];
+            for (i, &survivor) in previous.iter().enumerate() {
+                let dropped = if j % 2 == 1 {
+                    dropping[dropping.len() - 1 - i]
+                } else {
+                    dropping[i]
+                };
+                ids.push(matches.len());
+                matches.push(BracketMatch::new(
+                    BracketSide::Losers,
+                    round,
+                    round + 1,
+                    [Slot::Winner(survivor), Slot::Loser(dropped)],
+                ));
+            }
+            round += 1;
+            previous = ids;
+
+            if previous.len() > 1 {
+                let mut ids = vec![];
+                for pair in previous.chunks(2) {
+                    ids.push(matches.len());
+                    matches.push(BracketMatch::new(
+                        BracketSide::Losers,
+                        round,
+                        round + 1,
+                        [Slot::Winner(pair[0]), Slot::Winner(pair[1])],
+                    ));
+                }
+                round += 1;
+                previous = ids;
+            }
+        }
+        losers_champion = Slot::Winner(previous[0]);
+    }
+
+    matches.push(BracketMatch::new(
+        BracketSide::GrandFinal,
+        1,
+        2 * winners_rounds,
+        [winners_final, losers_champion],
+    ));
+    matches
+}
+
+/// Player of a slot as an index in the seeding: `None` while the match it comes from is not
+/// decided, `Some(None)` for a bye.
+pub fn resolve(matches: &[BracketMatch], slot: Slot, players: usize) -> Option<Option<usize>> {
+    match slot {
+        Slot::Seed(seed) => Some((seed < players).then_some(seed)),
+        Slot::Winner(id) => matches[id].outcome.map(|outcome| outcome.winner),
+        Slot::Loser(id) => matches[id].outcome.map(|outcome| outcome.loser),
+    }
+}
+
+/// Number of scheduled rounds of a bracket.
+pub fn rounds(matches: &[BracketMatch]) -> u32 {
+    matches
+        .iter()
+        .map(|bracket_match| bracket_match.scheduled_round)
+        .max()
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn references(matches: &[BracketMatch], slot: Slot) -> usize {
+        matches
+            .iter()
+            .flat_map(|bracket_match| bracket_match.slots)
+            .filter(|other| *other == slot)
+            .count()
+    }
+
+    #[test]
+    fn single_elimination_gives_the_byes_to_the_best_seeds() {
+        let matches = single_elimination(5);
+        assert_eq!(matches.len(), 7);
+        assert_eq!(rounds(&matches), 3);
+
+        let first_round: Vec<[Option<usize>; 2]> = matches[..4]
+            .iter()
+            .map(|bracket_match| {
+                bracket_match
+                    .slots
+                    .map(|slot| resolve(&matches, slot, 5).unwrap())
+            })
+            .collect();
+        assert_eq!(
+            first_round,
+            vec![
+                [Some(0), None],
+                [Some(3), Some(4)],
+                [Some(1), None],
+                [Some(2), None],
+            ]
+        );
+    }
+
+    #[test]
+    fn double_elimination_gives_every_player_a_second_chance() {
+        for players in [2, 3, 4, 6, 8, 13, 16] {
+            let matches = double_elimination(players);
+            let grand_final = matches.len() - 1;
+            assert_eq!(matches[grand_final].side, BracketSide::GrandFinal);
+
+            for (id, bracket_match) in matches.iter().enumerate() {
+                let winner_moves_on = usize::from(id != grand_final);
+                let loser_moves_on = usize::from(bracket_match.side == BracketSide::Winners);
+                assert_eq!(references(&matches, Slot::Winner(id)), winner_moves_on);
+                assert_eq!(references(&matches, Slot::Loser(id)), loser_moves_on);
+            }
+        }
+    }
+
+    #[test]
+    fn matches_are_scheduled_after_the_ones_they_depend_on() {
+        for players in [2, 4, 5, 8, 16] {
+            let matches = double_elimination(players);
+            for bracket_match in &matches {
+                for slot in bracket_match.slots {
+                    if let Slot::Winner(id) | Slot::Loser(id) = slot {
+                        assert!(matches[id].scheduled_round < bracket_match.scheduled_round);
+                    }
+                }
+            }
+            assert_eq!(rounds(&matches), matches.last().unwrap().scheduled_round);
+        }
+    }
+}
@@ -0,0 +1,312 @@
+use hex_chess_core::piece::Color;
+use leptos::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[cfg(feature = "ssr")]
+mod bracket;
+#[cfg(feature = "ssr")]
+mod pairing;
+#[cfg(feature = "ssr")]
+pub mod server;
+
+/// How often clients poll the standings of a tournament.
+pub const TOURNAMENT_REFRESH: std::time::Duration = std::time::Duration::from_secs(5);
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TournamentFormat {
+    /// Players with similar scores meet each round, never twice.
+    Swiss { rounds: u32 },
+    /// Players are paired again as soon as their game ends, until the time runs out. Wins
+    /// count double after two wins in a row.
+    Arena { minutes: u32 },
+    /// Everyone meets everyone once, a round starts every `round_minutes` at the earliest.
+    RoundRobin { round_minutes: u32 },
+    /// Knockout bracket, a lost match eliminates the player.
+    SingleElimination { round_minutes: u32 },
+    /// Knockout bracket where players are eliminated after their second lost match, the
+    /// winners and losers bracket champions meet in the grand final.
+    DoubleElimination { round_minutes: u32 },
+}
+
+impl TournamentFormat {
+    /// Time between two scheduled rounds, Swiss and arena pairings are made as soon as possible.
+    pub fn round_minutes(self) -> Option<u32> {
+        match self {
+            TournamentFormat::Swiss { .. } | TournamentFormat::Arena { .. } => None,
+            TournamentFormat::RoundRobin { round_minutes }
+            | TournamentFormat::SingleElimination { round_minutes }
+            | TournamentFormat::DoubleElimination { round_minutes } => Some(round_minutes),
+        }
+    }
+
+    pub fn is_bracket(self) -> bool {
+        matches!(
+            self,
+            TournamentFormat::SingleElimination { .. } | TournamentFormat::DoubleElimination { .. }
+        )
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TournamentStatus {
+    Registration,
+    Running,
+    Finished,
+}
+
+fn other_color(color: Color) -> Color {
+    match color {
+        Color::White => Color::Black,
+        Color::Black => Color::White,
+    }
+}
+
+/// Result of a pairing, from the point of view of the white player.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PairingResult {
+    Win(Color),
+    Draw,
+    /// The winner of a stalemate gets 3/4 of the points.
+    Stalemate {
+        winner: Color,
+    },
+    /// The other player was not there to play.
+    Forfeit {
+        winner: Color,
+    },
+    /// Neither player was there, or the game was abandoned.
+    DoubleForfeit,
+    /// Nobody was left to pair the white player with.
+    Bye,
+}
+
+impl PairingResult {
+    /// Points of the white and black players, in quarter points.
+    pub fn points(self) -> (u32, u32) {
+        match self {
+            PairingResult::Win(Color::White)
+            | PairingResult::Forfeit {
+                winner: Color::White,
+            } => (4, 0),
+            PairingResult::Win(Color::Black)
+            | PairingResult::Forfeit {
+                winner: Color::Black,
+            } => (0, 4),
+            PairingResult::Draw => (2, 2),
+            PairingResult::Stalemate {
+                winner: Color::White,
+            } => (3, 1),
+            PairingResult::Stalemate {
+                winner: Color::Black,
+            } => (1, 3),
+            PairingResult::DoubleForfeit => (0, 0),
+            PairingResult::Bye => (4, 0),
+        }
+    }
+
+    /// Same result with the colours swapped.
+    pub fn reversed(self) -> Self {
+        match self {
+            PairingResult::Win(color) => PairingResult::Win(other_color(color)),
+            PairingResult::Stalemate { winner } => PairingResult::Stalemate {
+                winner: other_color(winner),
+            },
+            PairingResult::Forfeit { winner } => PairingResult::Forfeit {
+                winner: other_color(winner),
+            },
+            PairingResult::Draw | PairingResult::DoubleForfeit | PairingResult::Bye => self,
+        }
+    }
+
+    /// Score as shown in the pairings table.
+    pub fn display(self) -> &'static str {
+        match self {
+            PairingResult::Win(Color::White) => "1 - 0",
+            PairingResult::Win(Color::Black) => "0 - 1",
+            PairingResult::Draw => "½ - ½",
+            PairingResult::Stalemate {
+                winner: Color::White,
+            } => "¾ - ¼",
+            PairingResult::Stalemate {
+                winner: Color::Black,
+            } => "¼ - ¾",
+            PairingResult::Forfeit {
+                winner: Color::White,
+            } => "+ - −",
+            PairingResult::Forfeit {
+                winner: Color::Black,
+            } => "− - +",
+            PairingResult::DoubleForfeit => "− - −",
+            PairingResult::Bye => "bye",
+        }
+    }
+}
+
+/// Format quarter points like `2.75`.
+pub fn display_points(quarters: u32) -> String {
+    let points = quarters as f32 / 4.0;
+    format!("{}", points)
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct TournamentSummary {
+    pub id: Uuid,
+    pub name: String,
+    pub format: TournamentFormat,
+    pub status: TournamentStatus,
+    pub players: usize,
+    /// Round being played, 0 before the first one.
+    pub round: u32,
+    /// Number of rounds, known once a round-robin or a bracket starts.
+    pub rounds: Option<u32>,
+    /// Minutes before the next scheduled round can start.
+    pub next_round_in: Option<u32>,
+    /// Minutes left before a running arena stops pairing players, rounded up.
+    pub minutes_left: Option<u32>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct TournamentList {
+    pub tournaments: Vec<TournamentSummary>,
+    /// Admins can create and start tournaments.
+    pub is_admin: bool,
+}
+
+/// A line of the standings, all points are in quarter points.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct Standing {
+    pub rank: usize,
+    pub username: String,
+    pub points: u32,
+    pub games: u32,
+    pub wins: u32,
+    /// Sum of the points of the opponents.
+    pub buchholz: u32,
+    /// Sum of the points of the opponents weighted by the result against them.
+    pub sonneborn_berger: u32,
+    pub withdrawn: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct PairingSummary {
+    pub round: u32,
+    pub white: String,
+    /// `None` for a bye.
+    pub black: Option<String>,
+    pub game_id: Option<Uuid>,
+    /// `None` while the game is played.
+    pub result: Option<PairingResult>,
+    /// Minutes left for the players to join the lobby before they lose by forfeit.
+    pub join_deadline_in: Option<u32>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum BracketSide {
+    Winners,
+    Losers,
+    GrandFinal,
+}
+
+/// A match of a bracket, it can take several games when they end in a draw.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct BracketMatchSummary {
+    pub side: BracketSide,
+    /// Round in its side of the bracket, from 1.
+    pub round: u32,
+    /// `None` while the previous match is played, or for a bye.
+    pub players: [Option<String>; 2],
+    /// Index in `players` of the player who went through.
+    pub winner: Option<usize>,
+    /// Results of the games of the match, from the point of view of the first player.
+    pub results: Vec<PairingResult>,
+    pub game_id: Option<Uuid>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct TournamentDetails {
+    pub summary: TournamentSummary,
+    pub standings: Vec<Standing>,
+    /// Latest first.
+    pub pairings: Vec<PairingSummary>,
+    /// Matches of a single or double elimination, winners bracket first.
+    pub bracket: Vec<BracketMatchSummary>,
+    pub is_registered: bool,
+    pub is_admin: bool,
+}
+
+#[server(GetTournaments, "/api")]
+pub async fn get_tournaments(cx: Scope) -> Result<TournamentList, ServerFnError> {
+    let tournaments = server::get_tournaments(cx).await?;
+    Ok(TournamentList {
+        tournaments: tournaments.summaries().await,
+        is_admin: server::is_admin(cx).await?,
+    })
+}
+
+#[server(GetTournament, "/api")]
+pub async fn get_tournament(cx: Scope, id: Uuid) -> Result<TournamentDetails, ServerFnError> {
+    let tournaments = server::get_tournaments(cx).await?;
+    let user_id = crate::server::profile::server::get_user_id(cx).await?;
+    let is_admin = server::is_admin(cx).await?;
+    Ok(tournaments
+        .details(id, user_id.as_deref(), is_admin)
+        .await?)
+}
+
+#[server(CreateTournament, "/api")]
+pub async fn create_tournament(
+    cx: Scope,
+    name: String,
+    format: TournamentFormat,
+) -> Result<Uuid, ServerFnError> {
+    let admin_id = crate::server::admin::server::get_admin_id(cx).await?;
+    let tournaments = server::get_tournaments(cx).await?;
+    let id = tournaments.create(name, format, &admin_id).await?;
+    crate::server::admin::server::audit(cx, &admin_id, "create_tournament", &id.to_string(), None)
+        .await?;
+    Ok(id)
+}
+
+#[server(StartTournament, "/api")]
+pub async fn start_tournament(cx: Scope, id: Uuid) -> Result<(), ServerFnError> {
+    let admin_id = crate::server::admin::server::get_admin_id(cx).await?;
+    let tournaments = server::get_tournaments(cx).await?;
+    tournaments.start(id).await?;
+    crate::server::admin::server::audit(cx, &admin_id, "start_tournament", &id.to_string(), None)
+        .await
+}
+
+#[server(RegisterTournament, "/api")]
+pub async fn register_tournament(cx: Scope, id: Uuid) -> Result<(), ServerFnError> {
+    use crate::server::db::user::User;
+
+    let player_id = crate::server::board::server::get_player_id(cx).await?;
+    let pool = crate::server::get_pool(cx).await?;
+    let user = User::get_from_user_id(&player_id, &pool).await?;
+    if user.banned_at.is_some() {
+        return Err(crate::server::board::server::GameError::PlayerBanned.into());
+    }
+    let tournaments = server::get_tournaments(cx).await?;
+    tournaments.register(id, player_id, user.username).await?;
+    Ok(())
+}
+
+#[server(WithdrawTournament, "/api")]
+pub async fn withdraw_tournament(cx: Scope, id: Uuid) -> Result<(), ServerFnError> {
+    let player_id = crate::server::board::server::get_player_id(cx).await?;
+    let tournaments = server::get_tournaments(cx).await?;
+    tournaments.withdraw(id, &player_id).await?;
+    Ok(())
+}
+
+/// Tournament a game was played in, to go back to it once the game is over.
+#[server(GetGameTournament, "/api")]
+pub async fn get_game_tournament(cx: Scope, game_id: Uuid) -> Result<Option<Uuid>, ServerFnError> {
+    let tournaments = server::get_tournaments(cx).await?;
+    Ok(tournaments.tournament_of_game(game_id).await)
+}
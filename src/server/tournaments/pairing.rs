@@ -0,0 +1,400 @@
+//! Pairing rules and tie-breaks of the tournament formats.
+
+use std::cmp::Reverse;
+use std::collections::HashMap;
+
+use hex_chess_core::piece::Color;
+
+use super::server::Participant;
+
+/// Colour a player must get next, `strong` when giving them the other one would leave them
+/// with two more games of a colour or three in a row.
+fn due_color(player: &Participant) -> Option<(Color, bool)> {
+    let balance = player.color_balance();
+    let last_two = player.last_colors(2);
+    let repeated = last_two.len() == 2 && last_two[0] == last_two[1];
+    let due = match balance {
+        balance if balance > 0 => Color::Black,
+        balance if balance < 0 => Color::White,
+        _ => match last_two.last()? {
+            Color::White => Color::Black,
+            Color::Black => Color::White,
+        },
+    };
+    Some((due, balance.abs() >= 2 || repeated))
+}
+
+fn colors_compatible(a: &Participant, b: &Participant) -> bool {
+    !matches!(
+        (due_color(a), due_color(b)),
+        (Some((a, true)), Some((b, true))) if a == b
+    )
+}
+
+/// Whether `a` gets white against `b`: the player who had fewer white games, then the one who
+/// had black last, then `a`, who is ranked higher.
+pub fn gets_white(a: &Participant, b: &Participant) -> bool {
+    match a.color_balance().cmp(&b.color_balance()) {
+        std::cmp::Ordering::Less => true,
+        std::cmp::Ordering::Greater => false,
+        std::cmp::Ordering::Equal => !matches!(
+            (a.last_colors(1).first(), b.last_colors(1).first()),
+            (Some(Color::White), Some(Color::Black))
+        ),
+    }
+}
+
+/// Indices of `players` ordered by points, then registration order.
+fn ranked(players: &[&Participant]) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..players.len()).collect();
+    order.sort_by_key(|&i| (Reverse(players[i].points()), players[i].seed));
+    order
+}
+
+/// Pairings of the next Swiss round as indices of `players`, white first and `None` for the bye.
+///
+/// Players are paired top down with the closest ranked player they haven't met yet, keeping
+/// the colours balanced when possible. The bye goes to the lowest ranked player who never had
+/// one.
+pub fn swiss_pairings(players: &[&Participant]) -> Vec<(usize, Option<usize>)> {
+    let mut order = ranked(players);
+    let mut pairings = vec![];
+
+    if order.len() % 2 == 1 {
+        let bye = order
+            .iter()
+            .rposition(|&i| !players[i].had_bye())
+            .unwrap_or(order.len() - 1);
+        pairings.push((order.remove(bye), None));
+    }
+
+    let mut paired = vec![false; players.len()];
+    for (pos, &i) in order.iter().enumerate() {
+        if paired[i] {
+            continue;
+        }
+        let candidates = || order[pos + 1..].iter().copied().filter(|&j| !paired[j]);
+        let new_opponent = |j: &usize| !players[i].has_played(players[*j]);
+        let opponent = candidates()
+            .filter(new_opponent)
+            .find(|&j| colors_compatible(players[i], players[j]))
+            .or_else(|| candidates().find(new_opponent))
+            .or_else(|| candidates().next());
+        let Some(j) = opponent else {
+            continue;
+        };
+        paired[i] = true;
+        paired[j] = true;
+        if gets_white(players[i], players[j]) {
+            pairings.push((i, Some(j)));
+        } else {
+            pairings.push((j, Some(i)));
+        }
+    }
+    pairings
+}
+
+/// Pairings of the arena players waiting for a game, white first. Players are paired with the
+/// next one in the ranking, avoiding an immediate rematch. With an odd count the last one
+/// waits for the next game to end.
+pub fn arena_pairings(players: &[&Participant]) -> Vec<(usize, usize)> {
+    let mut order = ranked(players);
+    let mut pairings = vec![];
+    let mut pos = 0;
+    while pos + 1 < order.len() {
+        let (i, j) = (order[pos], order[pos + 1]);
+        if players[i].last_opponent() == Some(players[j].user_id.as_str()) && pos + 2 < order.len()
+        {
+            order.swap(pos + 1, pos + 2);
+        }
+        let (i, j) = (order[pos], order[pos + 1]);
+        if gets_white(players[i], players[j]) {
+            pairings.push((i, j));
+        } else {
+            pairings.push((j, i));
+        }
+        pos += 2;
+    }
+    pairings
+}
+
+/// Rounds of a round-robin between `players` players with the circle method, white first and
+/// `None` for the bye when the count is odd. Every player meets every other one once.
+pub fn round_robin_rounds(players: usize) -> Vec<Vec<(usize, Option<usize>)>> {
+    let count = players + players % 2;
+    let mut circle: Vec<usize> = (0..count).collect();
+    let mut rounds = vec![];
+    for round in 0..count.saturating_sub(1) {
+        let mut pairings = vec![];
+        for i in 0..count / 2 {
+            let (mut a, mut b) = (circle[i], circle[count - 1 - i]);
+            // the fixed player alternates colours, the others follow the rotation
+            if (i == 0 && round % 2 == 1) || (i > 0 && i % 2 == 1) {
+                std::mem::swap(&mut a, &mut b);
+            }
+            match (a < players, b < players) {
+                (true, true) => pairings.push((a, Some(b))),
+                (true, false) => pairings.push((a, None)),
+                (false, true) => pairings.push((b, None)),
+                (false, false) => {}
+            }
+        }
+        rounds.push(pairings);
+        circle[1..].rotate_right(1);
+    }
+    rounds
+}
+
+/// Tie-breaks of a participant, in quarter points.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TieBreaks {
+    pub buchholz: u32,
+    pub sonneborn_berger: u32,
+}
+
+/// Indices of `participants` in standings order, with their tie-breaks: points, then
+/// Buchholz, then Sonneborn-Berger, then wins, then registration order.
+pub fn standings(participants: &[Participant]) -> Vec<(usize, TieBreaks)> {
+    let points: HashMap<&str, u32> = participants
+        .iter()
+        .map(|participant| (participant.user_id.as_str(), participant.points()))
+        .collect();
+
+    let mut standings: Vec<(usize, TieBreaks)> = participants
+        .iter()
+        .enumerate()
+        .map(|(i, participant)| {
+            let mut tie_breaks = TieBreaks::default();
+            // in sixteenths of a point, rounded down once at the end
+            let mut sonneborn_berger = 0;
+            for game in &participant.results {
+                let Some(opponent_points) = game
+                    .opponent
+                    .as_deref()
+                    .and_then(|opponent| points.get(opponent))
+                else {
+                    continue;
+                };
+                tie_breaks.buchholz += opponent_points;
+                sonneborn_berger += opponent_points * game.points;
+            }
+            tie_breaks.sonneborn_berger = sonneborn_berger / 4;
+            (i, tie_breaks)
+        })
+        .collect();
+
+    standings.sort_by_key(|(i, tie_breaks)| {
+        let participant = &participants[*i];
+        (
+            Reverse(participant.points()),
+            Reverse(tie_breaks.buchholz),
+            Reverse(tie_breaks.sonneborn_berger),
+            Reverse(participant.wins()),
+            participant.seed,
+        )
+    });
+    standings
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+    use crate::server::tournaments::server::PlayedGame;
+    use crate::server::tournaments::PairingResult;
+
+    fn participants(count: usize) -> Vec<Participant> {
+        (0..count)
+            .map(|seed| Participant::new(format!("id{}", seed), format!("player{}", seed), seed))
+            .collect()
+    }
+
+    fn play(players: &mut [Participant], white: usize, black: usize, result: PairingResult) {
+        let (white_points, black_points) = result.points();
+        let games = [
+            (white, black, Color::White, white_points),
+            (black, white, Color::Black, black_points),
+        ];
+        for (player, opponent, color, points) in games {
+            let opponent = players[opponent].user_id.clone();
+            players[player].results.push(PlayedGame {
+                opponent: Some(opponent),
+                color: Some(color),
+                points,
+                won: points == 4,
+            });
+        }
+    }
+
+    fn bye(players: &mut [Participant], player: usize) {
+        players[player].results.push(PlayedGame {
+            opponent: None,
+            color: None,
+            points: 4,
+            won: false,
+        });
+    }
+
+    fn pair(a: usize, b: usize) -> (usize, usize) {
+        (a.min(b), a.max(b))
+    }
+
+    #[test]
+    fn swiss_gives_the_bye_to_the_lowest_ranked_player() {
+        let players = participants(5);
+        let players: Vec<&Participant> = players.iter().collect();
+        let pairings = swiss_pairings(&players);
+
+        assert_eq!(pairings.len(), 3);
+        assert_eq!(
+            pairings.iter().filter(|(_, black)| black.is_none()).count(),
+            1
+        );
+        assert!(pairings.contains(&(4, None)));
+        let mut paired: Vec<usize> = pairings
+            .iter()
+            .flat_map(|&(white, black)| [Some(white), black])
+            .flatten()
+            .collect();
+        paired.sort();
+        assert_eq!(paired, vec![0, 1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn swiss_gives_a_single_bye_per_player() {
+        let mut players = participants(5);
+        play(&mut players, 0, 1, PairingResult::Draw);
+        play(&mut players, 2, 3, PairingResult::Draw);
+        bye(&mut players, 4);
+        let players: Vec<&Participant> = players.iter().collect();
+        let pairings = swiss_pairings(&players);
+
+        assert!(pairings.contains(&(3, None)));
+        assert!(pairings
+            .iter()
+            .all(|&(white, black)| white != 4 || black.is_some()));
+    }
+
+    #[test]
+    fn swiss_avoids_rematches() {
+        let mut players = participants(4);
+        play(&mut players, 0, 1, PairingResult::Draw);
+        play(&mut players, 2, 3, PairingResult::Draw);
+        let players: Vec<&Participant> = players.iter().collect();
+        let pairings: HashSet<(usize, usize)> = swiss_pairings(&players)
+            .into_iter()
+            .map(|(white, black)| pair(white, black.unwrap()))
+            .collect();
+
+        assert_eq!(pairings, HashSet::from([(0, 2), (1, 3)]));
+    }
+
+    #[test]
+    fn swiss_balances_colors() {
+        let mut players = participants(2);
+        play(&mut players, 0, 1, PairingResult::Draw);
+        let players: Vec<&Participant> = players.iter().collect();
+
+        assert_eq!(swiss_pairings(&players), vec![(1, Some(0))]);
+    }
+
+    #[test]
+    fn arena_avoids_an_immediate_rematch() {
+        let mut players = participants(3);
+        play(&mut players, 0, 1, PairingResult::Win(Color::White));
+        let players: Vec<&Participant> = players.iter().collect();
+        let pairings = arena_pairings(&players);
+
+        assert_eq!(pairings.len(), 1);
+        let (white, black) = pairings[0];
+        assert_eq!(pair(white, black), (0, 2));
+    }
+
+    #[test]
+    fn arena_leaves_the_last_player_waiting() {
+        let players = participants(5);
+        let players: Vec<&Participant> = players.iter().collect();
+        let pairings = arena_pairings(&players);
+
+        assert_eq!(pairings.len(), 2);
+        assert!(pairings
+            .iter()
+            .all(|&(white, black)| white != 4 && black != 4));
+    }
+
+    #[test]
+    fn round_robin_pairs_everyone_once() {
+        for players in 2..=9 {
+            let rounds = round_robin_rounds(players);
+            assert_eq!(rounds.len(), players + players % 2 - 1);
+
+            let mut pairs = HashSet::new();
+            let mut byes = vec![0; players];
+            for round in &rounds {
+                let mut seen = HashSet::new();
+                for &(white, black) in round {
+                    assert!(seen.insert(white));
+                    match black {
+                        Some(black) => {
+                            assert!(seen.insert(black));
+                            assert!(pairs.insert(pair(white, black)));
+                        }
+                        None => byes[white] += 1,
+                    }
+                }
+                assert_eq!(seen.len(), players);
+            }
+            assert_eq!(pairs.len(), players * (players - 1) / 2);
+            assert!(byes.iter().all(|&count| count == players % 2));
+        }
+    }
+
+    #[test]
+    fn standings_break_ties_with_buchholz_then_sonneborn_berger() {
+        let mut players = participants(4);
+        play(&mut players, 0, 2, PairingResult::Win(Color::White));
+        play(&mut players, 1, 3, PairingResult::Win(Color::White));
+        play(&mut players, 2, 3, PairingResult::Win(Color::White));
+        let standings = standings(&players);
+
+        let order: Vec<usize> = standings.iter().map(|&(i, _)| i).collect();
+        assert_eq!(order, vec![0, 2, 1, 3]);
+        assert_eq!(
+            standings[0].1,
+            TieBreaks {
+                buchholz: 4,
+                sonneborn_berger: 4,
+            }
+        );
+        assert_eq!(
+            standings[1].1,
+            TieBreaks {
+                buchholz: 4,
+                sonneborn_berger: 0,
+            }
+        );
+    }
+
+    #[test]
+    fn standings_round_sonneborn_berger_once() {
+        let mut players = participants(3);
+        let stalemate = PairingResult::Stalemate {
+            winner: Color::White,
+        };
+        play(&mut players, 0, 1, stalemate);
+        play(&mut players, 0, 2, stalemate);
+        let standings = standings(&players);
+
+        assert_eq!(standings[0].0, 0);
+        assert_eq!(standings[0].1.sonneborn_berger, 1);
+    }
+
+    #[test]
+    fn standings_fall_back_to_registration_order() {
+        let players = participants(3);
+        let order: Vec<usize> = standings(&players).iter().map(|&(i, _)| i).collect();
+
+        assert_eq!(order, vec![0, 1, 2]);
+    }
+}
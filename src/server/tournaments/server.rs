@@ -0,0 +1,1387 @@
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::time::Duration;
+
+use actix_web::{get, web, HttpResponse};
+use actix_web_lab::sse;
+use chrono::{DateTime, Utc};
+use futures::lock::Mutex;
+use hex_chess_core::board::GameEnd;
+use hex_chess_core::piece::Color;
+use leptos::ServerFnError;
+use leptos_actix::extract;
+use sqlx::PgPool;
+use tracing::{info, instrument, warn};
+use uuid::Uuid;
+
+use crate::server::auth::user_id::MaybeUserId;
+use crate::server::board::server::{check_id, Games, Player};
+use crate::server::board::GameEvent;
+use crate::server::config::TournamentsConfig;
+use crate::server::db::game_record::GameRecord;
+use crate::server::db::user::User;
+
+use super::bracket::{self, BracketMatch, Outcome};
+use super::pairing;
+use super::{
+    display_points, BracketMatchSummary, PairingResult, PairingSummary, Standing,
+    TournamentDetails, TournamentFormat, TournamentStatus, TournamentSummary,
+};
+
+/// A game of a participant, `opponent` is `None` for a bye.
+pub struct PlayedGame {
+    pub opponent: Option<String>,
+    pub color: Option<Color>,
+    /// Quarter points scored, doubled in an arena during a win streak.
+    pub points: u32,
+    pub won: bool,
+}
+
+pub struct Participant {
+    pub user_id: String,
+    pub username: String,
+    /// Registration order, breaks the ties of the pairings.
+    pub seed: usize,
+    pub withdrawn: bool,
+    pub results: Vec<PlayedGame>,
+    /// Lobby stream of the participant while they wait for a pairing.
+    ready: Option<Player>,
+    /// Game being played.
+    playing: Option<Uuid>,
+}
+
+impl Participant {
+    pub(super) fn new(user_id: String, username: String, seed: usize) -> Self {
+        Participant {
+            user_id,
+            username,
+            seed,
+            withdrawn: false,
+            results: vec![],
+            ready: None,
+            playing: None,
+        }
+    }
+
+    pub fn points(&self) -> u32 {
+        self.results.iter().map(|game| game.points).sum()
+    }
+
+    pub fn wins(&self) -> u32 {
+        self.results.iter().filter(|game| game.won).count() as u32
+    }
+
+    /// White games minus black games.
+    pub fn color_balance(&self) -> i32 {
+        self.results
+            .iter()
+            .filter_map(|game| game.color)
+            .map(|color| match color {
+                Color::White => 1,
+                Color::Black => -1,
+            })
+            .sum()
+    }
+
+    /// Colours of the last `count` games, oldest first.
+    pub fn last_colors(&self, count: usize) -> Vec<Color> {
+        let colors: Vec<Color> = self.results.iter().filter_map(|game| game.color).collect();
+        colors[colors.len().saturating_sub(count)..].to_vec()
+    }
+
+    pub fn last_opponent(&self) -> Option<&str> {
+        self.results.last()?.opponent.as_deref()
+    }
+
+    pub fn has_played(&self, other: &Participant) -> bool {
+        self.results
+            .iter()
+            .any(|game| game.opponent.as_deref() == Some(other.user_id.as_str()))
+    }
+
+    pub fn had_bye(&self) -> bool {
+        self.results.iter().any(|game| game.opponent.is_none())
+    }
+
+    /// Consecutive wins at the end of the results.
+    fn streak(&self) -> usize {
+        self.results
+            .iter()
+            .rev()
+            .take_while(|game| game.won)
+            .count()
+    }
+
+    fn is_active(&self) -> bool {
+        !self.withdrawn
+    }
+}
+
+struct Pairing {
+    round: u32,
+    white: String,
+    black: Option<String>,
+    game_id: Option<Uuid>,
+    result: Option<PairingResult>,
+    /// Players missing from the lobby at this time lose by forfeit.
+    join_deadline: Option<DateTime<Utc>>,
+    /// Games still running at this time are adjudicated.
+    deadline: Option<DateTime<Utc>>,
+    /// Match of the bracket the game belongs to.
+    bracket_match: Option<usize>,
+}
+
+impl Pairing {
+    fn new(round: u32, white: String, black: Option<String>) -> Self {
+        Pairing {
+            round,
+            white,
+            black,
+            game_id: None,
+            result: None,
+            join_deadline: None,
+            deadline: None,
+            bracket_match: None,
+        }
+    }
+
+    fn is_pending(&self) -> bool {
+        self.result.is_none() && self.game_id.is_none() && self.black.is_some()
+    }
+
+    fn can_wait(&self) -> bool {
+        self.join_deadline
+            .is_some_and(|deadline| Utc::now() < deadline)
+    }
+}
+
+/// Game of a tournament to score without holding the registry lock.
+struct RunningGame {
+    tournament_id: Uuid,
+    pairing_index: usize,
+    game_id: Uuid,
+    /// The round time limit passed.
+    is_overdue: bool,
+}
+
+/// Pairing whose players are both in the lobby, its game is started without holding the
+/// registry lock.
+struct GameStart {
+    tournament_id: Uuid,
+    pairing_index: usize,
+    white: Player,
+    black: Player,
+}
+
+pub struct Tournament {
+    id: Uuid,
+    name: String,
+    format: TournamentFormat,
+    status: TournamentStatus,
+    participants: Vec<Participant>,
+    pairings: Vec<Pairing>,
+    round: u32,
+    started_at: Option<DateTime<Utc>>,
+    /// End of an arena, set when it starts.
+    ends_at: Option<DateTime<Utc>>,
+    created_at: DateTime<Utc>,
+    finished_at: Option<DateTime<Utc>>,
+    /// Rounds of a round-robin as indices in `participants`, set when it starts.
+    round_robin: Vec<Vec<(usize, Option<usize>)>>,
+    /// Matches of a bracket, seeds are indices in `participants`.
+    bracket: Vec<BracketMatch>,
+}
+
+/// Minutes until `time`, rounded up.
+fn minutes_until(time: DateTime<Utc>) -> u32 {
+    let seconds = (time - Utc::now()).num_seconds().max(0);
+    ((seconds + 59) / 60) as u32
+}
+
+impl Tournament {
+    fn participant(&self, user_id: &str) -> Option<&Participant> {
+        self.participants
+            .iter()
+            .find(|participant| participant.user_id == user_id)
+    }
+
+    fn participant_mut(&mut self, user_id: &str) -> Option<&mut Participant> {
+        self.participants
+            .iter_mut()
+            .find(|participant| participant.user_id == user_id)
+    }
+
+    fn is_registration_open(&self) -> bool {
+        match (self.status, self.format) {
+            (TournamentStatus::Registration, _) => true,
+            (TournamentStatus::Running, TournamentFormat::Arena { .. }) => {
+                self.ends_at.is_some_and(|ends_at| Utc::now() < ends_at)
+            }
+            _ => false,
+        }
+    }
+
+    /// Number of rounds, once they are known.
+    fn rounds(&self) -> Option<u32> {
+        match self.format {
+            TournamentFormat::Swiss { rounds } => Some(rounds),
+            TournamentFormat::Arena { .. } => None,
+            TournamentFormat::RoundRobin { .. } => {
+                (!self.round_robin.is_empty()).then_some(self.round_robin.len() as u32)
+            }
+            TournamentFormat::SingleElimination { .. }
+            | TournamentFormat::DoubleElimination { .. } => {
+                (!self.bracket.is_empty()).then(|| bracket::rounds(&self.bracket))
+            }
+        }
+    }
+
+    /// Earliest start of a scheduled round, the first one starts with the tournament.
+    fn round_start(&self, round: u32) -> Option<DateTime<Utc>> {
+        let round_minutes = self.format.round_minutes()?;
+        let offset = i64::from(round.saturating_sub(1)) * i64::from(round_minutes);
+        Some(self.started_at? + chrono::Duration::minutes(offset))
+    }
+
+    fn summary(&self) -> TournamentSummary {
+        let next_round_in = (self.status == TournamentStatus::Running)
+            .then(|| self.round_start(self.round + 1))
+            .flatten()
+            .filter(|start| *start > Utc::now())
+            .map(minutes_until);
+        TournamentSummary {
+            id: self.id,
+            name: self.name.clone(),
+            format: self.format,
+            status: self.status,
+            players: self
+                .participants
+                .iter()
+                .filter(|participant| participant.is_active())
+                .count(),
+            round: self.round,
+            rounds: self.rounds(),
+            next_round_in,
+            minutes_left: self.ends_at.map(minutes_until),
+        }
+    }
+
+    fn username(&self, user_id: &str) -> String {
+        self.participant(user_id)
+            .map_or_else(String::new, |participant| participant.username.clone())
+    }
+
+    fn standings(&self) -> Vec<Standing> {
+        pairing::standings(&self.participants)
+            .into_iter()
+            .enumerate()
+            .map(|(rank, (i, tie_breaks))| {
+                let participant = &self.participants[i];
+                Standing {
+                    rank: rank + 1,
+                    username: participant.username.clone(),
+                    points: participant.points(),
+                    games: participant.results.len() as u32,
+                    wins: participant.wins(),
+                    buchholz: tie_breaks.buchholz,
+                    sonneborn_berger: tie_breaks.sonneborn_berger,
+                    withdrawn: participant.withdrawn,
+                }
+            })
+            .collect()
+    }
+
+    fn pairing_summaries(&self) -> Vec<PairingSummary> {
+        self.pairings
+            .iter()
+            .rev()
+            .map(|pairing| PairingSummary {
+                round: pairing.round,
+                white: self.username(&pairing.white),
+                black: pairing.black.as_deref().map(|black| self.username(black)),
+                game_id: pairing.game_id,
+                result: pairing.result,
+                join_deadline_in: pairing
+                    .join_deadline
+                    .filter(|_| pairing.is_pending())
+                    .map(minutes_until),
+            })
+            .collect()
+    }
+
+    fn bracket_summaries(&self) -> Vec<BracketMatchSummary> {
+        let players = self.participants.len();
+        self.bracket
+            .iter()
+            .enumerate()
+            .map(|(id, bracket_match)| {
+                let seeds = bracket_match
+                    .slots
+                    .map(|slot| bracket::resolve(&self.bracket, slot, players).flatten());
+                let games: Vec<&Pairing> = self
+                    .pairings
+                    .iter()
+                    .filter(|pairing| pairing.bracket_match == Some(id))
+                    .collect();
+                let first_player = seeds[0].map(|seed| self.participants[seed].user_id.as_str());
+                BracketMatchSummary {
+                    side: bracket_match.side,
+                    round: bracket_match.round,
+                    players: seeds
+                        .map(|seed| seed.map(|seed| self.participants[seed].username.clone())),
+                    winner: bracket_match
+                        .outcome
+                        .and_then(|outcome| outcome.winner)
+                        .and_then(|winner| seeds.iter().position(|seed| *seed == Some(winner))),
+                    results: games
+                        .iter()
+                        .filter_map(|pairing| {
+                            let result = pairing.result?;
+                            Some(if first_player == Some(pairing.white.as_str()) {
+                                result
+                            } else {
+                                result.reversed()
+                            })
+                        })
+                        .collect(),
+                    game_id: games
+                        .iter()
+                        .find(|pairing| pairing.result.is_none())
+                        .and_then(|pairing| pairing.game_id),
+                }
+            })
+            .collect()
+    }
+
+    /// Score the pairing and free its players for the next one.
+    fn record_result(&mut self, pairing_index: usize, result: PairingResult) {
+        let pairing = &mut self.pairings[pairing_index];
+        pairing.result = Some(result);
+        let white = pairing.white.clone();
+        let black = pairing.black.clone();
+        let (white_points, black_points) = result.points();
+        let is_arena = matches!(self.format, TournamentFormat::Arena { .. });
+
+        let players = [
+            (
+                Some(white.clone()),
+                black.clone(),
+                Color::White,
+                white_points,
+            ),
+            (black, Some(white), Color::Black, black_points),
+        ];
+        for (player_id, opponent, color, points) in players {
+            let Some(participant) = player_id.and_then(|id| self.participant_mut(&id)) else {
+                continue;
+            };
+            let won = points == 4;
+            // two wins in a row and the next ones count double
+            let points = if is_arena && won && participant.streak() >= 2 {
+                points * 2
+            } else {
+                points
+            };
+            participant.playing = None;
+            participant.results.push(PlayedGame {
+                color: opponent.is_some().then_some(color),
+                opponent,
+                points,
+                won,
+            });
+        }
+    }
+
+    /// Games of the pairings waiting for a result.
+    fn running_games(&self) -> Vec<RunningGame> {
+        let now = Utc::now();
+        self.pairings
+            .iter()
+            .enumerate()
+            .filter(|(_, pairing)| pairing.result.is_none())
+            .filter_map(|(i, pairing)| {
+                Some(RunningGame {
+                    tournament_id: self.id,
+                    pairing_index: i,
+                    game_id: pairing.game_id?,
+                    is_overdue: pairing.deadline.is_some_and(|deadline| now >= deadline),
+                })
+            })
+            .collect()
+    }
+
+    /// Score a game that ended, unless its pairing was scored in the meantime.
+    fn record_game_result(&mut self, game: &RunningGame, result: PairingResult) {
+        let is_running = self
+            .pairings
+            .get(game.pairing_index)
+            .is_some_and(|pairing| {
+                pairing.game_id == Some(game.game_id) && pairing.result.is_none()
+            });
+        if is_running {
+            info!(tournament_id = %self.id, game_id = %game.game_id, ?result, "tournament game ended");
+            self.record_result(game.pairing_index, result);
+        }
+    }
+
+    /// Add a pairing to start at the end of the tick, a bye is scored right away.
+    fn add_pairing(&mut self, mut pairing: Pairing, config: &TournamentsConfig) -> usize {
+        let after = |delay: Duration| {
+            chrono::Duration::from_std(delay)
+                .ok()
+                .map(|delay| Utc::now() + delay)
+        };
+        pairing.join_deadline = after(config.join_deadline());
+        pairing.deadline = after(config.round_time_limit());
+        let is_bye = pairing.black.is_none();
+        self.pairings.push(pairing);
+        let pairing_index = self.pairings.len() - 1;
+        if is_bye {
+            self.record_result(pairing_index, PairingResult::Bye);
+        }
+        pairing_index
+    }
+
+    /// Take the players of the pending pairings whose game can start.
+    fn take_game_starts(&mut self) -> Vec<GameStart> {
+        let pending: Vec<usize> = self
+            .pairings
+            .iter()
+            .enumerate()
+            .filter(|(_, pairing)| pairing.is_pending())
+            .map(|(i, _)| i)
+            .collect();
+        pending
+            .into_iter()
+            .filter_map(|i| self.prepare_pairing(i))
+            .collect()
+    }
+
+    /// Take the players of a pairing once both are in the lobby. The missing players lose by
+    /// forfeit after the join deadline.
+    fn prepare_pairing(&mut self, pairing_index: usize) -> Option<GameStart> {
+        let pairing = &self.pairings[pairing_index];
+        let white = pairing.white.clone();
+        let black = pairing.black.clone()?;
+        let can_wait = pairing.can_wait();
+
+        let state = |user_id: &str| {
+            self.participant(user_id)
+                .map_or((true, false), |participant| {
+                    (participant.withdrawn, participant.ready.is_some())
+                })
+        };
+        let (white_withdrawn, white_ready) = state(&white);
+        let (black_withdrawn, black_ready) = state(&black);
+
+        let result = match (white_withdrawn, black_withdrawn) {
+            (true, true) => PairingResult::DoubleForfeit,
+            (true, false) => PairingResult::Forfeit {
+                winner: Color::Black,
+            },
+            (false, true) => PairingResult::Forfeit {
+                winner: Color::White,
+            },
+            (false, false) if white_ready && black_ready => {
+                let white_player = self.participant_mut(&white)?.ready.take()?;
+                let black_player = self.participant_mut(&black)?.ready.take()?;
+                return Some(GameStart {
+                    tournament_id: self.id,
+                    pairing_index,
+                    white: white_player,
+                    black: black_player,
+                });
+            }
+            _ if can_wait => return None,
+            _ => match (white_ready, black_ready) {
+                (true, _) => PairingResult::Forfeit {
+                    winner: Color::White,
+                },
+                (_, true) => PairingResult::Forfeit {
+                    winner: Color::Black,
+                },
+                _ => PairingResult::DoubleForfeit,
+            },
+        };
+        info!(tournament_id = %self.id, white, black, ?result, "pairing forfeited");
+        self.record_result(pairing_index, result);
+        None
+    }
+
+    /// Follow up on the start of a pairing's game, a player who left loses by forfeit after
+    /// the join deadline.
+    fn game_started(
+        &mut self,
+        pairing_index: usize,
+        game_id: Uuid,
+        started: Result<(), Option<Player>>,
+    ) {
+        let pairing = &self.pairings[pairing_index];
+        let white = pairing.white.clone();
+        let black = pairing.black.clone().unwrap_or_default();
+        let can_wait = pairing.can_wait();
+
+        let result = match started {
+            Ok(()) => {
+                self.pairings[pairing_index].game_id = Some(game_id);
+                for player_id in [&white, &black] {
+                    if let Some(participant) = self.participant_mut(player_id) {
+                        participant.playing = Some(game_id);
+                    }
+                }
+                return;
+            }
+            Err(Some(player)) => {
+                let winner = if player.has_id(&white) {
+                    Color::White
+                } else {
+                    Color::Black
+                };
+                self.return_to_lobby(player);
+                if can_wait {
+                    return;
+                }
+                PairingResult::Forfeit { winner }
+            }
+            Err(None) if can_wait => return,
+            Err(None) => PairingResult::DoubleForfeit,
+        };
+        info!(tournament_id = %self.id, white, black, ?result, "pairing forfeited");
+        self.record_result(pairing_index, result);
+    }
+
+    fn return_to_lobby(&mut self, player: Player) {
+        if let Some(participant) = self.participant_mut(player.player_id()) {
+            participant.ready = Some(player);
+        }
+    }
+
+    fn is_round_over(&self) -> bool {
+        self.pairings.iter().all(|pairing| pairing.result.is_some())
+    }
+
+    /// Pair the next Swiss round once every game of the current one is over.
+    fn tick_swiss(&mut self, rounds: u32, config: &TournamentsConfig) {
+        if !self.is_round_over() {
+            return;
+        }
+        if self.round >= rounds {
+            self.finish();
+            return;
+        }
+        self.round += 1;
+        info!(tournament_id = %self.id, round = self.round, "starting swiss round");
+
+        let active: Vec<&Participant> = self
+            .participants
+            .iter()
+            .filter(|participant| participant.is_active())
+            .collect();
+        let pairings: Vec<(String, Option<String>)> = pairing::swiss_pairings(&active)
+            .into_iter()
+            .map(|(white, black)| {
+                (
+                    active[white].user_id.clone(),
+                    black.map(|black| active[black].user_id.clone()),
+                )
+            })
+            .collect();
+
+        for (white, black) in pairings {
+            self.add_pairing(Pairing::new(self.round, white, black), config);
+        }
+    }
+
+    /// Pair the arena players waiting in the lobby, until the time runs out.
+    fn tick_arena(&mut self, config: &TournamentsConfig) {
+        let is_over = self.ends_at.map_or(true, |ends_at| Utc::now() >= ends_at);
+        if is_over {
+            if self.is_round_over() {
+                self.finish();
+            }
+            return;
+        }
+
+        let waiting: Vec<&Participant> = self
+            .participants
+            .iter()
+            .filter(|participant| participant.is_active() && participant.ready.is_some())
+            .collect();
+        let pairings: Vec<(String, String)> = pairing::arena_pairings(&waiting)
+            .into_iter()
+            .map(|(white, black)| {
+                (
+                    waiting[white].user_id.clone(),
+                    waiting[black].user_id.clone(),
+                )
+            })
+            .collect();
+        for (white, black) in pairings {
+            self.add_pairing(Pairing::new(self.round, white, Some(black)), config);
+        }
+    }
+
+    /// Pair the next round-robin round once the current one is over and its time has come.
+    fn tick_round_robin(&mut self, config: &TournamentsConfig) {
+        if !self.is_round_over() {
+            return;
+        }
+        if self.round as usize >= self.round_robin.len() {
+            self.finish();
+            return;
+        }
+        if self
+            .round_start(self.round + 1)
+            .is_some_and(|start| Utc::now() < start)
+        {
+            return;
+        }
+        self.round += 1;
+        info!(tournament_id = %self.id, round = self.round, "starting round-robin round");
+
+        let pairings: Vec<(String, Option<String>)> = self.round_robin[self.round as usize - 1]
+            .iter()
+            .map(|&(white, black)| {
+                (
+                    self.participants[white].user_id.clone(),
+                    black.map(|black| self.participants[black].user_id.clone()),
+                )
+            })
+            .collect();
+        for (white, black) in pairings {
+            self.add_pairing(Pairing::new(self.round, white, black), config);
+        }
+    }
+
+    /// Decide the bracket matches whose players are known: byes go through, drawn games are
+    /// replayed with the colours swapped, and new matches are paired once their round starts.
+    fn tick_bracket(&mut self, config: &TournamentsConfig) {
+        let players = self.participants.len();
+        for id in 0..self.bracket.len() {
+            let bracket_match = &self.bracket[id];
+            if bracket_match.outcome.is_some() {
+                continue;
+            }
+            let [Some(first), Some(second)] = bracket_match
+                .slots
+                .map(|slot| bracket::resolve(&self.bracket, slot, players))
+            else {
+                continue;
+            };
+            let outcome = match (first, second) {
+                (Some(first), Some(second)) => {
+                    match self.play_bracket_match(id, first, second, config) {
+                        Some(outcome) => outcome,
+                        None => continue,
+                    }
+                }
+                (Some(player), None) | (None, Some(player)) => Outcome {
+                    winner: Some(player),
+                    loser: None,
+                },
+                (None, None) => Outcome {
+                    winner: None,
+                    loser: None,
+                },
+            };
+            self.bracket[id].outcome = Some(outcome);
+        }
+
+        if self
+            .bracket
+            .last()
+            .is_some_and(|bracket_match| bracket_match.outcome.is_some())
+        {
+            self.finish();
+        }
+    }
+
+    /// Outcome of a bracket match between two seeds, `None` while it is played.
+    fn play_bracket_match(
+        &mut self,
+        id: usize,
+        first: usize,
+        second: usize,
+        config: &TournamentsConfig,
+    ) -> Option<Outcome> {
+        let games: Vec<&Pairing> = self
+            .pairings
+            .iter()
+            .filter(|pairing| pairing.bracket_match == Some(id))
+            .collect();
+        let first_id = &self.participants[first].user_id;
+        let second_id = &self.participants[second].user_id;
+
+        let winner = match games.last().map(|pairing| (pairing.result, pairing)) {
+            Some((None, _)) => return None,
+            Some((Some(result), pairing)) => match result {
+                PairingResult::Win(color)
+                | PairingResult::Stalemate { winner: color }
+                | PairingResult::Forfeit { winner: color } => {
+                    let white_won = color == Color::White;
+                    Some((pairing.white == *first_id) == white_won)
+                }
+                // only a player still in the tournament goes through, the slot is a bye when
+                // there is none
+                PairingResult::DoubleForfeit => {
+                    let active = [first, second].map(|seed| self.participants[seed].is_active());
+                    return Some(match active {
+                        [true, false] => Outcome {
+                            winner: Some(first),
+                            loser: Some(second),
+                        },
+                        [false, true] => Outcome {
+                            winner: Some(second),
+                            loser: Some(first),
+                        },
+                        _ => Outcome {
+                            winner: None,
+                            loser: None,
+                        },
+                    });
+                }
+                PairingResult::Draw | PairingResult::Bye => None,
+            },
+            None => None,
+        };
+        if let Some(first_won) = winner {
+            let (winner, loser) = if first_won {
+                (first, second)
+            } else {
+                (second, first)
+            };
+            return Some(Outcome {
+                winner: Some(winner),
+                loser: Some(loser),
+            });
+        }
+
+        let scheduled_round = self.bracket[id].scheduled_round;
+        if games.is_empty()
+            && self
+                .round_start(scheduled_round)
+                .is_some_and(|start| Utc::now() < start)
+        {
+            return None;
+        }
+        // colours alternate between the games of a match
+        let (white, black) = if games.len() % 2 == 0 {
+            (first_id.clone(), second_id.clone())
+        } else {
+            (second_id.clone(), first_id.clone())
+        };
+        self.round = self.round.max(scheduled_round);
+        let mut pairing = Pairing::new(scheduled_round, white, Some(black));
+        pairing.bracket_match = Some(id);
+        self.add_pairing(pairing, config);
+        None
+    }
+
+    fn finish(&mut self) {
+        info!(tournament_id = %self.id, "tournament finished");
+        self.status = TournamentStatus::Finished;
+        self.finished_at = Some(Utc::now());
+        for participant in &mut self.participants {
+            participant.ready = None;
+        }
+    }
+}
+
+pub fn end_result(end: GameEnd) -> PairingResult {
+    match end {
+        GameEnd::Win(color) => PairingResult::Win(color),
+        GameEnd::Draw => PairingResult::Draw,
+        GameEnd::Stalemate { winner } => PairingResult::Stalemate { winner },
+    }
+}
+
+/// Result of a game removed from the game server, abandoned and terminated games count as a
+/// loss for both players.
+pub fn record_result(record: &GameRecord) -> PairingResult {
+    match record.outcome.as_str() {
+        "timeout" => match record.board.get_player_turn() {
+            Color::White => PairingResult::Win(Color::Black),
+            Color::Black => PairingResult::Win(Color::White),
+        },
+        _ => record
+            .board
+            .is_end()
+            .map_or(PairingResult::DoubleForfeit, end_result),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TournamentError {
+    InvalidTournamentId(Uuid),
+    InvalidName,
+    InvalidFormat {
+        max_rounds: u32,
+        max_minutes: u32,
+        max_round_minutes: u32,
+    },
+    RegistrationClosed,
+    TournamentFull,
+    NotRegistered,
+    AlreadyStarted,
+    NotEnoughPlayers,
+}
+
+impl Display for TournamentError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TournamentError::InvalidTournamentId(id) => {
+                write!(f, "tournament with id {} don't exist.", id)
+            }
+            TournamentError::InvalidName => f.write_str("the tournament needs a name."),
+            TournamentError::InvalidFormat {
+                max_rounds,
+                max_minutes,
+                max_round_minutes,
+            } => write!(
+                f,
+                "swiss tournaments have 1 to {} rounds, arenas last 1 to {} minutes and scheduled rounds are 1 to {} minutes apart.",
+                max_rounds, max_minutes, max_round_minutes
+            ),
+            TournamentError::RegistrationClosed => f.write_str("registration is closed."),
+            TournamentError::TournamentFull => f.write_str("the tournament is full."),
+            TournamentError::NotRegistered => {
+                f.write_str("you are not registered in this tournament.")
+            }
+            TournamentError::AlreadyStarted => f.write_str("the tournament already started."),
+            TournamentError::NotEnoughPlayers => {
+                f.write_str("a tournament needs at least 2 players.")
+            }
+        }
+    }
+}
+
+impl actix_web::ResponseError for TournamentError {
+    fn status_code(&self) -> http::StatusCode {
+        match self {
+            TournamentError::InvalidTournamentId(_) => http::StatusCode::NOT_FOUND,
+            TournamentError::NotRegistered => http::StatusCode::FORBIDDEN,
+            _ => http::StatusCode::BAD_REQUEST,
+        }
+    }
+}
+
+impl std::error::Error for TournamentError {}
+
+pub struct Tournaments {
+    tournaments: Mutex<HashMap<Uuid, Tournament>>,
+    config: TournamentsConfig,
+}
+
+impl Tournaments {
+    pub fn new(config: TournamentsConfig) -> Self {
+        Tournaments {
+            tournaments: Mutex::new(HashMap::new()),
+            config,
+        }
+    }
+
+    /// Running tournaments first, then the ones open for registration, newest first.
+    pub async fn summaries(&self) -> Vec<TournamentSummary> {
+        let tournaments = self.tournaments.lock().await;
+        let mut tournaments: Vec<&Tournament> = tournaments.values().collect();
+        tournaments.sort_by_key(|tournament| {
+            let status = match tournament.status {
+                TournamentStatus::Running => 0,
+                TournamentStatus::Registration => 1,
+                TournamentStatus::Finished => 2,
+            };
+            (status, std::cmp::Reverse(tournament.created_at))
+        });
+        tournaments.into_iter().map(Tournament::summary).collect()
+    }
+
+    pub async fn details(
+        &self,
+        id: Uuid,
+        user_id: Option<&str>,
+        is_admin: bool,
+    ) -> Result<TournamentDetails, TournamentError> {
+        let tournaments = self.tournaments.lock().await;
+        let tournament = tournaments
+            .get(&id)
+            .ok_or(TournamentError::InvalidTournamentId(id))?;
+        let is_registered = user_id
+            .and_then(|user_id| tournament.participant(user_id))
+            .is_some_and(Participant::is_active);
+        Ok(TournamentDetails {
+            summary: tournament.summary(),
+            standings: tournament.standings(),
+            pairings: tournament.pairing_summaries(),
+            bracket: tournament.bracket_summaries(),
+            is_registered,
+            is_admin,
+        })
+    }
+
+    #[instrument(skip(self))]
+    pub async fn create(
+        &self,
+        name: String,
+        format: TournamentFormat,
+        admin_id: &str,
+    ) -> Result<Uuid, TournamentError> {
+        let name = name.trim().to_owned();
+        if name.is_empty() {
+            return Err(TournamentError::InvalidName);
+        }
+        let is_valid = match format {
+            TournamentFormat::Swiss { rounds } => {
+                (1..=self.config.max_swiss_rounds).contains(&rounds)
+            }
+            TournamentFormat::Arena { minutes } => {
+                (1..=self.config.max_arena_minutes).contains(&minutes)
+            }
+            TournamentFormat::RoundRobin { round_minutes }
+            | TournamentFormat::SingleElimination { round_minutes }
+            | TournamentFormat::DoubleElimination { round_minutes } => {
+                (1..=self.config.max_round_minutes).contains(&round_minutes)
+            }
+        };
+        if !is_valid {
+            return Err(TournamentError::InvalidFormat {
+                max_rounds: self.config.max_swiss_rounds,
+                max_minutes: self.config.max_arena_minutes,
+                max_round_minutes: self.config.max_round_minutes,
+            });
+        }
+
+        let id = Uuid::new_v4();
+        let tournament = Tournament {
+            id,
+            name,
+            format,
+            status: TournamentStatus::Registration,
+            participants: vec![],
+            pairings: vec![],
+            round: 0,
+            started_at: None,
+            ends_at: None,
+            created_at: Utc::now(),
+            finished_at: None,
+            round_robin: vec![],
+            bracket: vec![],
+        };
+        self.tournaments.lock().await.insert(id, tournament);
+        info!(tournament_id = %id, "created tournament");
+        Ok(id)
+    }
+
+    #[instrument(skip(self))]
+    pub async fn start(&self, id: Uuid) -> Result<(), TournamentError> {
+        let mut tournaments = self.tournaments.lock().await;
+        let tournament = tournaments
+            .get_mut(&id)
+            .ok_or(TournamentError::InvalidTournamentId(id))?;
+        if tournament.status != TournamentStatus::Registration {
+            return Err(TournamentError::AlreadyStarted);
+        }
+        let players = tournament
+            .participants
+            .iter()
+            .filter(|p| p.is_active())
+            .count();
+        if players < 2 {
+            return Err(TournamentError::NotEnoughPlayers);
+        }
+        tournament.status = TournamentStatus::Running;
+        tournament.started_at = Some(Utc::now());
+        // seeds follow the registration order
+        match tournament.format {
+            TournamentFormat::Arena { minutes } => {
+                tournament.ends_at = Some(Utc::now() + chrono::Duration::minutes(minutes.into()));
+            }
+            TournamentFormat::RoundRobin { .. } => {
+                tournament.round_robin = pairing::round_robin_rounds(players);
+            }
+            TournamentFormat::SingleElimination { .. } => {
+                tournament.bracket = bracket::single_elimination(players);
+            }
+            TournamentFormat::DoubleElimination { .. } => {
+                tournament.bracket = bracket::double_elimination(players);
+            }
+            TournamentFormat::Swiss { .. } => {}
+        }
+        info!(players, "started tournament");
+        Ok(())
+    }
+
+    #[instrument(skip(self, username))]
+    pub async fn register(
+        &self,
+        id: Uuid,
+        user_id: String,
+        username: String,
+    ) -> Result<(), TournamentError> {
+        let mut tournaments = self.tournaments.lock().await;
+        let tournament = tournaments
+            .get_mut(&id)
+            .ok_or(TournamentError::InvalidTournamentId(id))?;
+        if !tournament.is_registration_open() {
+            return Err(TournamentError::RegistrationClosed);
+        }
+        if let Some(participant) = tournament.participant_mut(&user_id) {
+            participant.withdrawn = false;
+            return Ok(());
+        }
+        if tournament.participants.len() >= self.config.max_players {
+            return Err(TournamentError::TournamentFull);
+        }
+        let seed = tournament.participants.len();
+        tournament
+            .participants
+            .push(Participant::new(user_id, username, seed));
+        info!("player registered in tournament");
+        Ok(())
+    }
+
+    /// Leave a tournament, players who already played stay in the standings.
+    #[instrument(skip(self))]
+    pub async fn withdraw(&self, id: Uuid, user_id: &str) -> Result<(), TournamentError> {
+        let mut tournaments = self.tournaments.lock().await;
+        let tournament = tournaments
+            .get_mut(&id)
+            .ok_or(TournamentError::InvalidTournamentId(id))?;
+        if tournament.status == TournamentStatus::Registration {
+            tournament
+                .participants
+                .retain(|participant| participant.user_id != user_id);
+            for (seed, participant) in tournament.participants.iter_mut().enumerate() {
+                participant.seed = seed;
+            }
+            return Ok(());
+        }
+        let participant = tournament
+            .participant_mut(user_id)
+            .ok_or(TournamentError::NotRegistered)?;
+        participant.withdrawn = true;
+        participant.ready = None;
+        info!("player withdrew from tournament");
+        Ok(())
+    }
+
+    /// Wait in the lobby of a tournament for the next pairing, players who are in a game of
+    /// the tournament are sent back to it.
+    pub async fn join_lobby(
+        &self,
+        id: Uuid,
+        user_id: String,
+    ) -> Result<sse::Sse<sse::ChannelStream>, TournamentError> {
+        let mut tournaments = self.tournaments.lock().await;
+        let tournament = tournaments
+            .get_mut(&id)
+            .ok_or(TournamentError::InvalidTournamentId(id))?;
+        let participant = tournament
+            .participant_mut(&user_id)
+            .filter(|participant| participant.is_active())
+            .ok_or(TournamentError::NotRegistered)?;
+        let playing = participant.playing;
+
+        let (player, stream) = Player::new_with_stream(user_id);
+        match playing {
+            Some(game_id) => {
+                let color = tournament
+                    .pairings
+                    .iter()
+                    .find(|pairing| pairing.game_id == Some(game_id))
+                    .map_or(Color::White, |pairing| {
+                        if player.has_id(&pairing.white) {
+                            Color::White
+                        } else {
+                            Color::Black
+                        }
+                    });
+                let _ = player.send(&GameEvent::start(game_id, color)).await;
+            }
+            None => {
+                let _ = player.send(&GameEvent::WaitingForOpponent).await;
+                if tournament.status != TournamentStatus::Finished {
+                    if let Some(participant) = tournament.participant_mut(player.player_id()) {
+                        participant.ready = Some(player);
+                    }
+                }
+            }
+        }
+        Ok(stream)
+    }
+
+    /// Results of every game as CSV, in the order they were paired.
+    pub async fn results_csv(&self, id: Uuid) -> Result<String, TournamentError> {
+        let tournaments = self.tournaments.lock().await;
+        let tournament = tournaments
+            .get(&id)
+            .ok_or(TournamentError::InvalidTournamentId(id))?;
+        let mut csv = String::from("round,white,black,result,white_points,black_points,game_id\n");
+        for pairing in &tournament.pairings {
+            let (white_points, black_points) =
+                pairing
+                    .result
+                    .map_or((String::new(), String::new()), |result| {
+                        let (white, black) = result.points();
+                        (display_points(white), display_points(black))
+                    });
+            let row = [
+                pairing.round.to_string(),
+                tournament.username(&pairing.white),
+                pairing
+                    .black
+                    .as_deref()
+                    .map_or_else(String::new, |black| tournament.username(black)),
+                pairing
+                    .result
+                    .map_or_else(String::new, |result| result.display().to_owned()),
+                white_points,
+                black_points,
+                pairing
+                    .game_id
+                    .map_or_else(String::new, |id| id.to_string()),
+            ];
+            push_csv_row(&mut csv, &row);
+        }
+        Ok(csv)
+    }
+
+    /// Standings as CSV, points in decimal.
+    pub async fn standings_csv(&self, id: Uuid) -> Result<String, TournamentError> {
+        let tournaments = self.tournaments.lock().await;
+        let tournament = tournaments
+            .get(&id)
+            .ok_or(TournamentError::InvalidTournamentId(id))?;
+        let mut csv =
+            String::from("rank,player,points,games,wins,buchholz,sonneborn_berger,withdrawn\n");
+        for standing in tournament.standings() {
+            let row = [
+                standing.rank.to_string(),
+                standing.username,
+                display_points(standing.points),
+                standing.games.to_string(),
+                standing.wins.to_string(),
+                display_points(standing.buchholz),
+                display_points(standing.sonneborn_berger),
+                standing.withdrawn.to_string(),
+            ];
+            push_csv_row(&mut csv, &row);
+        }
+        Ok(csv)
+    }
+
+    pub async fn tournament_of_game(&self, game_id: Uuid) -> Option<Uuid> {
+        let tournaments = self.tournaments.lock().await;
+        tournaments
+            .values()
+            .find(|tournament| {
+                tournament
+                    .pairings
+                    .iter()
+                    .any(|pairing| pairing.game_id == Some(game_id))
+            })
+            .map(|tournament| tournament.id)
+    }
+
+    /// Score the finished games of the running tournaments and make the next pairings. The
+    /// games are looked at and started without holding the lock on the tournaments.
+    pub async fn tick(&self, games: &Games, pool: &PgPool) {
+        let running: Vec<RunningGame> = self
+            .tournaments
+            .lock()
+            .await
+            .values()
+            .filter(|tournament| tournament.status == TournamentStatus::Running)
+            .flat_map(Tournament::running_games)
+            .collect();
+        let mut results = vec![];
+        for game in running {
+            if let Some(result) = game_result(games, pool, &game).await {
+                results.push((game, result));
+            }
+        }
+
+        let mut tournaments = self.tournaments.lock().await;
+        for (game, result) in &results {
+            if let Some(tournament) = tournaments.get_mut(&game.tournament_id) {
+                tournament.record_game_result(game, *result);
+            }
+        }
+        self.evict_finished(&mut tournaments);
+        if games.is_shutting_down() {
+            return;
+        }
+        let mut starts = vec![];
+        for tournament in tournaments.values_mut() {
+            if tournament.status != TournamentStatus::Running {
+                continue;
+            }
+            match tournament.format {
+                TournamentFormat::Swiss { rounds } => tournament.tick_swiss(rounds, &self.config),
+                TournamentFormat::Arena { .. } => tournament.tick_arena(&self.config),
+                TournamentFormat::RoundRobin { .. } => tournament.tick_round_robin(&self.config),
+                TournamentFormat::SingleElimination { .. }
+                | TournamentFormat::DoubleElimination { .. } => {
+                    tournament.tick_bracket(&self.config)
+                }
+            }
+            starts.extend(tournament.take_game_starts());
+        }
+        drop(tournaments);
+
+        let mut started = vec![];
+        for start in starts {
+            let game_id = Uuid::new_v4();
+            let result = games
+                .start_new_game(start.white, start.black, game_id)
+                .await;
+            started.push((start.tournament_id, start.pairing_index, game_id, result));
+        }
+        if started.is_empty() {
+            return;
+        }
+        let mut tournaments = self.tournaments.lock().await;
+        for (tournament_id, pairing_index, game_id, result) in started {
+            if let Some(tournament) = tournaments.get_mut(&tournament_id) {
+                tournament.game_started(pairing_index, game_id, result);
+            }
+        }
+    }
+
+    /// Remove the tournaments finished for longer than `finished_ttl`.
+    fn evict_finished(&self, tournaments: &mut HashMap<Uuid, Tournament>) {
+        let ttl = self.config.finished_ttl();
+        let now = Utc::now();
+        tournaments.retain(|id, tournament| {
+            let Some(finished_at) = tournament.finished_at else {
+                return true;
+            };
+            let keep = (now - finished_at).to_std().map_or(true, |age| age < ttl);
+            if !keep {
+                info!(tournament_id = %id, "removed finished tournament");
+            }
+            keep
+        });
+    }
+}
+
+/// Result of a tournament game, `None` while it is played. Games past the round time limit
+/// are adjudicated and their record saved.
+async fn game_result(games: &Games, pool: &PgPool, game: &RunningGame) -> Option<PairingResult> {
+    let game_id = game.game_id;
+    if game.is_overdue {
+        if let Some(record) = games.adjudicate_game(game_id).await {
+            if let Err(err) = record.insert(pool).await {
+                warn!(%game_id, error = %err, "could not save game record");
+            }
+            return Some(record_result(&record));
+        }
+    }
+    games.game_result(game_id, pool).await
+}
+
+/// Spawn the background task that runs the tournaments.
+pub fn spawn_tournament_ticks(
+    tournaments: web::Data<Tournaments>,
+    games: web::Data<Games>,
+    pool: web::Data<PgPool>,
+) {
+    actix_web::rt::spawn(async move {
+        let mut interval = actix_web::rt::time::interval(tournaments.config.tick_interval());
+        loop {
+            interval.tick().await;
+            tournaments.tick(&games, &pool).await;
+        }
+    });
+}
+
+pub async fn get_tournaments(cx: leptos::Scope) -> Result<web::Data<Tournaments>, ServerFnError> {
+    extract(cx, |tournaments: web::Data<Tournaments>| async move {
+        tournaments
+    })
+    .await
+}
+
+/// Whether the current user is an admin, logged out users are not.
+pub async fn is_admin(cx: leptos::Scope) -> Result<bool, ServerFnError> {
+    let Some(user_id) = crate::server::profile::server::get_user_id(cx).await? else {
+        return Ok(false);
+    };
+    let pool = crate::server::get_pool(cx).await?;
+    match User::is_admin_from_user_id(&user_id, &pool).await {
+        Ok(is_admin) => Ok(is_admin),
+        Err(err) => {
+            warn!(error = %err, "could not check if user is admin");
+            Ok(false)
+        }
+    }
+}
+
+/// Quote the fields holding separators and prefix the ones a spreadsheet would run as a
+/// formula, usernames are free text.
+fn push_csv_row(csv: &mut String, fields: &[String]) {
+    let fields: Vec<String> = fields
+        .iter()
+        .map(|field| {
+            let field = if field.starts_with(['=', '+', '-', '@', '\t', '\r']) {
+                format!("'{}", field)
+            } else {
+                field.clone()
+            };
+            if field.contains([',', '"', '\n', '\r']) {
+                format!("\"{}\"", field.replace('"', "\"\""))
+            } else {
+                field
+            }
+        })
+        .collect();
+    csv.push_str(&fields.join(","));
+    csv.push('\n');
+}
+
+fn csv_response(csv: String, filename: &str) -> HttpResponse {
+    HttpResponse::Ok()
+        .content_type("text/csv; charset=utf-8")
+        .insert_header((
+            http::header::CONTENT_DISPOSITION,
+            format!("attachment; filename=\"{}\"", filename),
+        ))
+        .body(csv)
+}
+
+#[get("{id}/results.csv")]
+async fn results_csv(
+    tournaments: web::Data<Tournaments>,
+    id: web::Path<Uuid>,
+) -> actix_web::Result<HttpResponse> {
+    let csv = tournaments.results_csv(*id).await?;
+    Ok(csv_response(csv, &format!("tournament-{}-results.csv", id)))
+}
+
+#[get("{id}/standings.csv")]
+async fn standings_csv(
+    tournaments: web::Data<Tournaments>,
+    id: web::Path<Uuid>,
+) -> actix_web::Result<HttpResponse> {
+    let csv = tournaments.standings_csv(*id).await?;
+    Ok(csv_response(
+        csv,
+        &format!("tournament-{}-standings.csv", id),
+    ))
+}
+
+#[get("{id}/lobby")]
+#[instrument(skip_all, fields(tournament_id = %id))]
+async fn lobby(
+    tournaments: web::Data<Tournaments>,
+    id: web::Path<Uuid>,
+    user_id: MaybeUserId,
+) -> actix_web::Result<sse::Sse<sse::ChannelStream>> {
+    let user_id = check_id(user_id)?;
+    Ok(tournaments.join_lobby(id.into_inner(), user_id).await?)
+}
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(lobby)
+        .service(results_csv)
+        .service(standings_csv);
+}
@@ -0,0 +1,21 @@
+#[cfg(feature = "hydrate")]
+pub fn get_item(key: &str) -> Option<String> {
+    let storage = leptos::window().local_storage().ok()??;
+    storage.get_item(key).ok()?
+}
+
+#[cfg(not(feature = "hydrate"))]
+pub fn get_item(_key: &str) -> Option<String> {
+    None
+}
+
+#[cfg(feature = "hydrate")]
+pub fn set_item(key: &str, value: &str) -> Option<()> {
+    let storage = leptos::window().local_storage().ok()??;
+    storage.set_item(key, value).ok()
+}
+
+#[cfg(not(feature = "hydrate"))]
+pub fn set_item(_key: &str, _value: &str) -> Option<()> {
+    None
+}
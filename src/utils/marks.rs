@@ -0,0 +1,191 @@
+//! Arrows and highlighted cells drawn on the board with the right mouse button, written in
+//! exports like the `[%csl Gf5]` and `[%cal Rf5f7]` comment commands of chess notation.
+
+use hex_chess_core::hex_coord::HexVector;
+
+use super::notation::cell_name;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MarkColor {
+    Green,
+    Red,
+    Blue,
+    Yellow,
+}
+
+impl MarkColor {
+    pub const ALL: [MarkColor; 4] = [
+        MarkColor::Green,
+        MarkColor::Red,
+        MarkColor::Blue,
+        MarkColor::Yellow,
+    ];
+
+    /// Green by default, shift for red, alt for blue and ctrl for yellow.
+    pub fn from_modifiers(shift: bool, alt: bool, ctrl: bool) -> Self {
+        match (shift, alt, ctrl) {
+            (true, _, _) => MarkColor::Red,
+            (_, true, _) => MarkColor::Blue,
+            (_, _, true) => MarkColor::Yellow,
+            _ => MarkColor::Green,
+        }
+    }
+
+    pub fn letter(self) -> char {
+        match self {
+            MarkColor::Green => 'G',
+            MarkColor::Red => 'R',
+            MarkColor::Blue => 'B',
+            MarkColor::Yellow => 'Y',
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            MarkColor::Green => "green",
+            MarkColor::Red => "red",
+            MarkColor::Blue => "blue",
+            MarkColor::Yellow => "yellow",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Arrow {
+    pub from: HexVector,
+    pub to: HexVector,
+    pub color: MarkColor,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Marks {
+    pub highlights: Vec<(HexVector, MarkColor)>,
+    pub arrows: Vec<Arrow>,
+}
+
+impl Marks {
+    pub fn is_empty(&self) -> bool {
+        self.highlights.is_empty() && self.arrows.is_empty()
+    }
+
+    /// Add a highlight, or remove it when the cell already has one of this colour.
+    pub fn toggle_highlight(&mut self, cell: HexVector, color: MarkColor) {
+        let previous = self.highlights.iter().position(|&(other, _)| other == cell);
+        if let Some(index) = previous {
+            let (_, previous_color) = self.highlights.remove(index);
+            if previous_color == color {
+                return;
+            }
+        }
+        self.highlights.push((cell, color));
+    }
+
+    /// Add an arrow, or remove it when there is already one of this colour.
+    pub fn toggle_arrow(&mut self, from: HexVector, to: HexVector, color: MarkColor) {
+        let previous = self
+            .arrows
+            .iter()
+            .position(|arrow| arrow.from == from && arrow.to == to);
+        if let Some(index) = previous {
+            if self.arrows.remove(index).color == color {
+                return;
+            }
+        }
+        self.arrows.push(Arrow { from, to, color });
+    }
+
+    /// Comment commands of the marks, empty when there are none.
+    pub fn to_notation(&self) -> String {
+        let mut notation = String::new();
+        if !self.highlights.is_empty() {
+            let highlights: Vec<String> = self
+                .highlights
+                .iter()
+                .filter_map(|&(cell, color)| {
+                    Some(format!("{}{}", color.letter(), cell_name(cell)?))
+                })
+                .collect();
+            notation.push_str(&format!("[%csl {}]", highlights.join(",")));
+        }
+        if !self.arrows.is_empty() {
+            let arrows: Vec<String> = self
+                .arrows
+                .iter()
+                .filter_map(|arrow| {
+                    Some(format!(
+                        "{}{}{}",
+                        arrow.color.letter(),
+                        cell_name(arrow.from)?,
+                        cell_name(arrow.to)?
+                    ))
+                })
+                .collect();
+            notation.push_str(&format!("[%cal {}]", arrows.join(",")));
+        }
+        notation
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::notation::parse_cell;
+
+    fn cell(name: &str) -> HexVector {
+        parse_cell(name).unwrap()
+    }
+
+    #[test]
+    fn toggle_highlight_twice_removes_it() {
+        let mut marks = Marks::default();
+        marks.toggle_highlight(cell("f5"), MarkColor::Green);
+        assert_eq!(marks.highlights, vec![(cell("f5"), MarkColor::Green)]);
+        marks.toggle_highlight(cell("f5"), MarkColor::Green);
+        assert!(marks.is_empty());
+    }
+
+    #[test]
+    fn toggle_arrow_twice_removes_it() {
+        let mut marks = Marks::default();
+        marks.toggle_arrow(cell("f5"), cell("f7"), MarkColor::Red);
+        marks.toggle_arrow(cell("f5"), cell("f7"), MarkColor::Red);
+        assert!(marks.is_empty());
+    }
+
+    #[test]
+    fn other_color_replaces_mark() {
+        let mut marks = Marks::default();
+        marks.toggle_highlight(cell("f5"), MarkColor::Green);
+        marks.toggle_highlight(cell("f5"), MarkColor::Blue);
+        assert_eq!(marks.highlights, vec![(cell("f5"), MarkColor::Blue)]);
+
+        marks.toggle_arrow(cell("f5"), cell("f7"), MarkColor::Green);
+        marks.toggle_arrow(cell("f5"), cell("f7"), MarkColor::Yellow);
+        assert_eq!(
+            marks.arrows,
+            vec![Arrow {
+                from: cell("f5"),
+                to: cell("f7"),
+                color: MarkColor::Yellow,
+            }]
+        );
+
+        // the reversed arrow is another arrow
+        marks.toggle_arrow(cell("f7"), cell("f5"), MarkColor::Yellow);
+        assert_eq!(marks.arrows.len(), 2);
+    }
+
+    #[test]
+    fn notation_lists_highlights_then_arrows() {
+        let mut marks = Marks::default();
+        assert_eq!(marks.to_notation(), "");
+
+        marks.toggle_arrow(cell("f5"), cell("f7"), MarkColor::Red);
+        assert_eq!(marks.to_notation(), "[%cal Rf5f7]");
+
+        marks.toggle_highlight(cell("f5"), MarkColor::Green);
+        marks.toggle_highlight(cell("l6"), MarkColor::Yellow);
+        marks.toggle_arrow(cell("a1"), cell("b1"), MarkColor::Blue);
+        assert_eq!(marks.to_notation(), "[%csl Gf5,Yl6][%cal Rf5f7,Ba1b1]");
+    }
+}
@@ -1,3 +1,9 @@
 pub mod clipboard;
 pub mod i18n;
+pub mod local_storage;
+pub mod marks;
+pub mod notation;
+pub mod push;
+pub mod rate_limit;
 pub mod url;
+pub mod variations;
@@ -0,0 +1,167 @@
+//! Names of cells and moves in Glinski's notation: files `a` to `l` without `j` from the left
+//! of white, ranks from 1 on the side of white in each file. A move is written `f5-f6`, with
+//! `=Q` when a pawn promotes.
+
+use hex_chess_core::{hex_coord::HexVector, piece::PieceKind};
+
+const FILES: [char; 11] = ['a', 'b', 'c', 'd', 'e', 'f', 'g', 'h', 'i', 'k', 'l'];
+
+/// Cells of the file `q` in axial coordinates, from rank 1.
+fn file_cells(q: isize) -> impl Iterator<Item = HexVector> {
+    let first = 5.min(5 - q);
+    let last = (-5).max(-5 - q);
+    (last..=first)
+        .rev()
+        .map(move |r| HexVector::new_axial(q, r))
+}
+
+/// Every cell of the board with its file and rank.
+fn cells() -> impl Iterator<Item = (HexVector, char, usize)> {
+    FILES.into_iter().zip(-5..=5).flat_map(|(file, q)| {
+        file_cells(q)
+            .enumerate()
+            .map(move |(i, cell)| (cell, file, i + 1))
+    })
+}
+
+pub fn cell_name(cell: HexVector) -> Option<String> {
+    cells()
+        .find(|(other, ..)| *other == cell)
+        .map(|(_, file, rank)| format!("{}{}", file, rank))
+}
+
+pub fn parse_cell(name: &str) -> Option<HexVector> {
+    let mut chars = name.chars();
+    let file = chars.next()?.to_ascii_lowercase();
+    let rank: usize = chars.as_str().parse().ok()?;
+    cells()
+        .find(|(_, other_file, other_rank)| *other_file == file && *other_rank == rank)
+        .map(|(cell, ..)| cell)
+}
+
+pub fn piece_letter(kind: PieceKind) -> char {
+    match kind {
+        PieceKind::OriginalPawn | PieceKind::Pawn => 'P',
+        PieceKind::Knight => 'N',
+        PieceKind::Bishop => 'B',
+        PieceKind::Rook => 'R',
+        PieceKind::Queen => 'Q',
+        PieceKind::King => 'K',
+    }
+}
+
+pub fn move_text(from: HexVector, to: HexVector, promote_to: Option<PieceKind>) -> String {
+    let mut text = format!(
+        "{}-{}",
+        cell_name(from).unwrap_or_default(),
+        cell_name(to).unwrap_or_default()
+    );
+    if let Some(kind) = promote_to {
+        text.push('=');
+        text.push(piece_letter(kind));
+    }
+    text
+}
+
+fn parse_piece_letter(letter: char) -> Option<PieceKind> {
+    match letter.to_ascii_uppercase() {
+        'N' => Some(PieceKind::Knight),
+        'B' => Some(PieceKind::Bishop),
+        'R' => Some(PieceKind::Rook),
+        'Q' => Some(PieceKind::Queen),
+        _ => None,
+    }
+}
+
+/// Move typed by a player: `f5-f6`, `f5 f6` or `f5f6`, with `=Q` or `Q` for a promotion.
+pub fn parse_move(text: &str) -> Option<(HexVector, HexVector, Option<PieceKind>)> {
+    let text: String = text
+        .chars()
+        .filter(|c| !c.is_whitespace() && !matches!(c, '-' | '=' | 'x'))
+        .collect();
+    // the second cell starts at the second letter
+    let second = text
+        .char_indices()
+        .skip(1)
+        .find(|(_, c)| c.is_ascii_alphabetic())?
+        .0;
+    let (from, rest) = text.split_at(second);
+    let (to, promote_to) = match rest
+        .char_indices()
+        .skip(1)
+        .find(|(_, c)| c.is_ascii_alphabetic())
+    {
+        Some((index, letter)) if index == rest.len() - 1 => {
+            (&rest[..index], Some(parse_piece_letter(letter)?))
+        }
+        Some(_) => return None,
+        None => (rest, None),
+    };
+    Some((parse_cell(from)?, parse_cell(to)?, promote_to))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn all_cells() -> Vec<HexVector> {
+        cells().map(|(cell, ..)| cell).collect()
+    }
+
+    #[test]
+    fn board_has_91_named_cells() {
+        let cells = all_cells();
+        assert_eq!(cells.len(), 91);
+        for cell in cells {
+            let name = cell_name(cell).unwrap();
+            assert!(parse_cell(&name) == Some(cell), "{}", name);
+        }
+    }
+
+    #[test]
+    fn parse_move_reads_move_text() {
+        let cells = all_cells();
+        for &from in &cells {
+            for &to in &cells {
+                let text = move_text(from, to, None);
+                assert!(parse_move(&text) == Some((from, to, None)), "{}", text);
+            }
+        }
+        let (from, to) = (parse_cell("f10").unwrap(), parse_cell("f11").unwrap());
+        for kind in [
+            PieceKind::Knight,
+            PieceKind::Bishop,
+            PieceKind::Rook,
+            PieceKind::Queen,
+        ] {
+            let text = move_text(from, to, Some(kind));
+            assert!(
+                parse_move(&text) == Some((from, to, Some(kind))),
+                "{}",
+                text
+            );
+        }
+    }
+
+    #[test]
+    fn parse_move_accepts_loose_input() {
+        let (from, to) = (parse_cell("f5").unwrap(), parse_cell("f6").unwrap());
+        for text in ["f5-f6", "f5 f6", "f5f6", " F5 - F6 ", "f5xf6"] {
+            assert!(parse_move(text) == Some((from, to, None)), "{}", text);
+        }
+        let queen = Some(PieceKind::Queen);
+        for text in ["f5-f6=Q", "f5f6q", "f5 f6 Q"] {
+            assert!(parse_move(text) == Some((from, to, queen)), "{}", text);
+        }
+    }
+
+    #[test]
+    fn parse_move_rejects_invalid_input() {
+        for text in [
+            "", "f5", "=Q", "f5-", "-f6", "j5-f6", "f5-j6", "f0-f6", "f12-f6", "a7-a1", "f5-f6=K",
+            "f5-f6=P", "f5-f6QQ", "f5-f6-f7", "55-66",
+        ] {
+            assert!(parse_move(text).is_none(), "{}", text);
+        }
+    }
+}
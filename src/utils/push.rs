@@ -0,0 +1,54 @@
+//! Subscription of the browser to the Web Push messages of the server.
+
+use serde::Deserialize;
+
+/// Push subscription of the browser, as needed by `SavePushSubscription`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct BrowserPushSubscription {
+    pub endpoint: String,
+    pub p256dh: String,
+    pub auth: String,
+}
+
+#[cfg(feature = "hydrate")]
+mod js {
+    use wasm_bindgen::prelude::*;
+
+    #[wasm_bindgen(inline_js = r#"
+export async function subscribe_to_push(publicKey) {
+    if (!("serviceWorker" in navigator) || !("PushManager" in window)) {
+        return null;
+    }
+    if ((await Notification.requestPermission()) !== "granted") {
+        return null;
+    }
+    const registration = await navigator.serviceWorker.register("/notifications-sw.js");
+    await navigator.serviceWorker.ready;
+    const padding = "=".repeat((4 - (publicKey.length % 4)) % 4);
+    const raw = atob((publicKey + padding).replace(/-/g, "+").replace(/_/g, "/"));
+    const subscription = await registration.pushManager.subscribe({
+        userVisibleOnly: true,
+        applicationServerKey: Uint8Array.from(raw, (c) => c.charCodeAt(0)),
+    });
+    const json = subscription.toJSON();
+    return JSON.stringify({ endpoint: json.endpoint, p256dh: json.keys.p256dh, auth: json.keys.auth });
+}
+"#)]
+    extern "C" {
+        #[wasm_bindgen(catch)]
+        pub async fn subscribe_to_push(public_key: &str) -> Result<JsValue, JsValue>;
+    }
+}
+
+/// Ask the user for the permission to show notifications and subscribe the browser with the
+/// VAPID `public_key` of the server, `None` if the browser can't or the user refused.
+#[cfg(feature = "hydrate")]
+pub async fn subscribe_to_push(public_key: &str) -> Option<BrowserPushSubscription> {
+    let subscription = js::subscribe_to_push(public_key).await.ok()?.as_string()?;
+    serde_json::from_str(&subscription).ok()
+}
+
+#[cfg(not(feature = "hydrate"))]
+pub async fn subscribe_to_push(_public_key: &str) -> Option<BrowserPushSubscription> {
+    None
+}
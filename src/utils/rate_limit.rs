@@ -0,0 +1,44 @@
+use leptos::*;
+
+/// Set while the server is throttling this client, shown by the layout.
+#[derive(Debug, Clone, Copy)]
+pub struct RateLimited(pub RwSignal<bool>);
+
+pub fn provide_rate_limit_context(cx: Scope) {
+    provide_context(cx, RateLimited(create_rw_signal(cx, false)));
+}
+
+pub fn use_rate_limited(cx: Scope) -> RwSignal<bool> {
+    use_context::<RateLimited>(cx)
+        .expect("rate limit context is provided by the App")
+        .0
+}
+
+/// Ask the server if this client is throttled after a request failed, and raise the notice
+/// until the `Retry-After` delay is over if it is.
+#[cfg(all(feature = "hydrate", not(feature = "ssr")))]
+pub fn check_rate_limit(cx: Scope) {
+    use std::time::Duration;
+
+    const DEFAULT_RETRY_AFTER: Duration = Duration::from_secs(5);
+
+    let rate_limited = use_rate_limited(cx);
+    spawn_local(async move {
+        let Ok(res) = gloo_net::http::Request::get("/api/rate_limit").send().await else {
+            return;
+        };
+        if res.status() != 429 {
+            return;
+        }
+        let retry_after = res
+            .headers()
+            .get("retry-after")
+            .and_then(|value| value.parse().ok())
+            .map_or(DEFAULT_RETRY_AFTER, Duration::from_secs);
+        rate_limited.set(true);
+        set_timeout(move || rate_limited.set(false), retry_after);
+    });
+}
+
+#[cfg(not(all(feature = "hydrate", not(feature = "ssr"))))]
+pub fn check_rate_limit(_cx: Scope) {}
@@ -0,0 +1,510 @@
+//! Tree of the lines played on the analysis board, exported with the moves in the notation of
+//! [`super::notation`]: `1. f5-f6! {comment} 1... f7-f5 (1... e7-e6 2. b1-c2) 2. c1-d2`.
+//! The marks drawn on a position go in its comment, as in `{[%cal Gf6f7] comment}`.
+
+use hex_chess_core::{board::Board, hex_coord::HexVector, piece::PieceKind};
+use serde::{Deserialize, Serialize};
+
+use super::{marks::Marks, notation::move_text};
+
+pub type NodeId = usize;
+
+/// Go back to the first position of the history.
+pub fn rewind(board: &mut Board) {
+    while board.get_last_played_move().is_some() {
+        board.back_one_turn();
+    }
+}
+
+/// Moves of a game from the history of its board. A promotion is found from the piece on the
+/// destination, which is no longer the pawn that moved.
+pub fn game_moves(board: &Board) -> Vec<PlayedMove> {
+    let is_pawn = |kind| matches!(kind, PieceKind::Pawn | PieceKind::OriginalPawn);
+    let mut board = board.clone();
+    rewind(&mut board);
+    let mut moves = vec![];
+    while !board.get_next_moves().is_empty() {
+        let before = board.clone();
+        board.advance_history();
+        let Some(mov) = board.get_last_played_move() else {
+            break;
+        };
+        let promote_to = match (before.get_piece_at(mov.from), board.get_piece_at(mov.to)) {
+            (Some(moved), Some(piece)) if is_pawn(moved.kind) && !is_pawn(piece.kind) => {
+                Some(piece.kind)
+            }
+            _ => None,
+        };
+        moves.push(PlayedMove {
+            from: mov.from,
+            to: mov.to,
+            promote_to,
+        });
+    }
+    moves
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlayedMove {
+    pub from: HexVector,
+    pub to: HexVector,
+    pub promote_to: Option<PieceKind>,
+}
+
+impl PlayedMove {
+    pub fn text(self) -> String {
+        move_text(self.from, self.to, self.promote_to)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Annotation {
+    Good,
+    Mistake,
+    Interesting,
+}
+
+impl Annotation {
+    pub const ALL: [Annotation; 3] = [
+        Annotation::Good,
+        Annotation::Mistake,
+        Annotation::Interesting,
+    ];
+
+    pub fn symbol(self) -> &'static str {
+        match self {
+            Annotation::Good => "!",
+            Annotation::Mistake => "?",
+            Annotation::Interesting => "!?",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Node {
+    /// `None` for the starting position.
+    mov: Option<PlayedMove>,
+    parent: Option<NodeId>,
+    /// The first child continues the line, the others are variations.
+    children: Vec<NodeId>,
+    annotation: Option<Annotation>,
+    comment: String,
+    marks: Marks,
+}
+
+/// Piece of the tree as written in the notation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Token {
+    MoveNumber(String),
+    Move {
+        id: NodeId,
+        text: String,
+    },
+    Comment(String),
+    /// Comment commands of the marks, only written in the notation.
+    Marks(String),
+    StartVariation,
+    EndVariation,
+}
+
+/// Deleted lines stay in the arena but are no longer reachable from the root.
+#[derive(Debug, Clone)]
+pub struct Variations {
+    nodes: Vec<Node>,
+}
+
+impl Default for Variations {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Variations {
+    pub const ROOT: NodeId = 0;
+
+    pub fn new() -> Self {
+        Variations {
+            nodes: vec![Node {
+                mov: None,
+                parent: None,
+                children: vec![],
+                annotation: None,
+                comment: String::new(),
+                marks: Marks::default(),
+            }],
+        }
+    }
+
+    /// Tree with a single line, such as the moves of a game.
+    pub fn from_moves(moves: impl IntoIterator<Item = PlayedMove>) -> Self {
+        let mut variations = Self::new();
+        moves
+            .into_iter()
+            .fold(Self::ROOT, |node, mov| variations.play(node, mov));
+        variations
+    }
+
+    /// Play a move after `node`, a move that was already played there is reused. Returns the
+    /// node of the move.
+    pub fn play(&mut self, node: NodeId, mov: PlayedMove) -> NodeId {
+        if let Some(&child) = self.nodes[node]
+            .children
+            .iter()
+            .find(|&&child| self.nodes[child].mov == Some(mov))
+        {
+            return child;
+        }
+        let child = self.nodes.len();
+        self.nodes.push(Node {
+            mov: Some(mov),
+            parent: Some(node),
+            children: vec![],
+            annotation: None,
+            comment: String::new(),
+            marks: Marks::default(),
+        });
+        self.nodes[node].children.push(child);
+        child
+    }
+
+    pub fn parent(&self, node: NodeId) -> Option<NodeId> {
+        self.nodes[node].parent
+    }
+
+    /// Next move of the line.
+    pub fn main_child(&self, node: NodeId) -> Option<NodeId> {
+        self.nodes[node].children.first().copied()
+    }
+
+    /// Last move of the line going through `node`.
+    pub fn line_end(&self, mut node: NodeId) -> NodeId {
+        while let Some(child) = self.main_child(node) {
+            node = child;
+        }
+        node
+    }
+
+    pub fn mov(&self, node: NodeId) -> Option<PlayedMove> {
+        self.nodes[node].mov
+    }
+
+    /// Moves from the starting position to `node`.
+    pub fn moves(&self, mut node: NodeId) -> Vec<PlayedMove> {
+        let mut moves = vec![];
+        while let Some(parent) = self.nodes[node].parent {
+            moves.extend(self.nodes[node].mov);
+            node = parent;
+        }
+        moves.reverse();
+        moves
+    }
+
+    /// Position after the move of `node`.
+    pub fn board_at(&self, node: NodeId) -> Board {
+        let mut board = Board::new();
+        for mov in self.moves(node) {
+            // the moves were legal when they were added
+            let _ = board.play_move(mov.from, mov.to, mov.promote_to);
+        }
+        board
+    }
+
+    pub fn annotation(&self, node: NodeId) -> Option<Annotation> {
+        self.nodes[node].annotation
+    }
+
+    pub fn set_annotation(&mut self, node: NodeId, annotation: Option<Annotation>) {
+        self.nodes[node].annotation = annotation;
+    }
+
+    pub fn comment(&self, node: NodeId) -> &str {
+        &self.nodes[node].comment
+    }
+
+    pub fn set_comment(&mut self, node: NodeId, comment: String) {
+        self.nodes[node].comment = comment;
+    }
+
+    pub fn marks(&self, node: NodeId) -> &Marks {
+        &self.nodes[node].marks
+    }
+
+    pub fn set_marks(&mut self, node: NodeId, marks: Marks) {
+        self.nodes[node].marks = marks;
+    }
+
+    /// Whether `node` is in a variation rather than in the main line.
+    pub fn is_variation(&self, node: NodeId) -> bool {
+        self.branch_point(node).is_some()
+    }
+
+    /// First ancestor of `node`, or `node` itself, that is not the first child of its parent.
+    fn branch_point(&self, mut node: NodeId) -> Option<NodeId> {
+        loop {
+            let parent = self.nodes[node].parent?;
+            if self.nodes[parent].children[0] != node {
+                return Some(node);
+            }
+            node = parent;
+        }
+    }
+
+    /// Make the variation holding `node` the line it branches from.
+    pub fn promote(&mut self, node: NodeId) {
+        let Some(branch) = self.branch_point(node) else {
+            return;
+        };
+        let parent = self.nodes[branch].parent.unwrap();
+        let children = &mut self.nodes[parent].children;
+        if let Some(index) = children.iter().position(|&child| child == branch) {
+            let branch = children.remove(index);
+            children.insert(0, branch);
+        }
+    }
+
+    /// Remove the move of `node` and everything after it, returns the node before it.
+    pub fn delete(&mut self, node: NodeId) -> NodeId {
+        let Some(parent) = self.nodes[node].parent else {
+            return node;
+        };
+        self.nodes[parent].children.retain(|&child| child != node);
+        parent
+    }
+
+    /// Whole tree in reading order.
+    pub fn tokens(&self) -> Vec<Token> {
+        let mut tokens = vec![];
+        self.push_marks(&mut tokens, Self::ROOT);
+        self.push_line(&mut tokens, Self::ROOT, 0, true);
+        tokens
+    }
+
+    /// The line after `node`, whose next move is the ply `ply` from 0, with its variations.
+    fn push_line(
+        &self,
+        tokens: &mut Vec<Token>,
+        mut node: NodeId,
+        mut ply: usize,
+        mut number: bool,
+    ) {
+        while let Some((&main, variations)) = self.nodes[node].children.split_first() {
+            self.push_move(tokens, main, ply, number);
+            number = self.has_comment(main);
+            for &variation in variations {
+                tokens.push(Token::StartVariation);
+                self.push_move(tokens, variation, ply, true);
+                let variation_number = self.has_comment(variation);
+                self.push_line(tokens, variation, ply + 1, variation_number);
+                tokens.push(Token::EndVariation);
+                number = true;
+            }
+            node = main;
+            ply += 1;
+        }
+    }
+
+    fn push_move(&self, tokens: &mut Vec<Token>, node: NodeId, ply: usize, number: bool) {
+        let move_number = ply / 2 + 1;
+        if ply % 2 == 0 {
+            tokens.push(Token::MoveNumber(format!("{}.", move_number)));
+        } else if number {
+            tokens.push(Token::MoveNumber(format!("{}...", move_number)));
+        }
+        let node_ref = &self.nodes[node];
+        let mut text = node_ref.mov.map(PlayedMove::text).unwrap_or_default();
+        if let Some(annotation) = node_ref.annotation {
+            text.push_str(annotation.symbol());
+        }
+        tokens.push(Token::Move { id: node, text });
+        self.push_marks(tokens, node);
+        if !node_ref.comment.is_empty() {
+            tokens.push(Token::Comment(node_ref.comment.clone()));
+        }
+    }
+
+    fn push_marks(&self, tokens: &mut Vec<Token>, node: NodeId) {
+        let marks = &self.nodes[node].marks;
+        if !marks.is_empty() {
+            tokens.push(Token::Marks(marks.to_notation()));
+        }
+    }
+
+    /// Whether a comment is written after the move of `node`, which then needs its number again.
+    fn has_comment(&self, node: NodeId) -> bool {
+        !self.nodes[node].comment.is_empty() || !self.nodes[node].marks.is_empty()
+    }
+
+    /// The tree in the notation, comments are wrapped in braces after the marks of the move.
+    pub fn to_notation(&self) -> String {
+        let mut notation = String::new();
+        let mut tokens = self.tokens().into_iter().peekable();
+        while let Some(token) = tokens.next() {
+            let text = match token {
+                Token::MoveNumber(text) | Token::Move { text, .. } => text,
+                Token::Marks(marks) => {
+                    match tokens.next_if(|token| matches!(token, Token::Comment(_))) {
+                        Some(Token::Comment(comment)) => {
+                            format!("{{{} {}}}", marks, comment.replace(['{', '}'], ""))
+                        }
+                        _ => format!("{{{}}}", marks),
+                    }
+                }
+                Token::Comment(comment) => format!("{{{}}}", comment.replace(['{', '}'], "")),
+                Token::StartVariation => "(".into(),
+                Token::EndVariation => {
+                    notation.push(')');
+                    continue;
+                }
+            };
+            if !notation.is_empty() && !notation.ends_with('(') {
+                notation.push(' ');
+            }
+            notation.push_str(&text);
+        }
+        notation
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::notation::parse_move;
+
+    fn mov(text: &str) -> PlayedMove {
+        let (from, to, promote_to) = parse_move(text).unwrap();
+        PlayedMove {
+            from,
+            to,
+            promote_to,
+        }
+    }
+
+    /// Plays `moves` one after the other from `node`, returns the node of each move.
+    fn play_line(variations: &mut Variations, mut node: NodeId, moves: &[&str]) -> Vec<NodeId> {
+        moves
+            .iter()
+            .map(|text| {
+                node = variations.play(node, mov(text));
+                node
+            })
+            .collect()
+    }
+
+    #[test]
+    fn play_reuses_existing_move() {
+        let mut variations = Variations::new();
+        let first = variations.play(Variations::ROOT, mov("a1-a2"));
+        let other = variations.play(Variations::ROOT, mov("b1-b2"));
+        assert_eq!(variations.play(Variations::ROOT, mov("a1-a2")), first);
+        assert_eq!(variations.play(Variations::ROOT, mov("b1-b2")), other);
+        assert_eq!(variations.main_child(Variations::ROOT), Some(first));
+        assert!(variations.is_variation(other));
+        assert_eq!(variations.to_notation(), "1. a1-a2 (1. b1-b2)");
+    }
+
+    #[test]
+    fn promote_nested_variation() {
+        let mut variations = Variations::new();
+        let main = play_line(
+            &mut variations,
+            Variations::ROOT,
+            &["a1-a2", "b1-b2", "c1-c2"],
+        );
+        let variation = play_line(&mut variations, main[0], &["d1-d2", "e1-e2"]);
+        let nested = variations.play(variation[0], mov("f1-f2"));
+        assert_eq!(
+            variations.to_notation(),
+            "1. a1-a2 b1-b2 (1... d1-d2 2. e1-e2 (2. f1-f2)) 2. c1-c2"
+        );
+
+        // the nested variation first replaces the line it branches from
+        variations.promote(nested);
+        assert_eq!(variations.main_child(variation[0]), Some(nested));
+        assert!(variations.is_variation(nested));
+        assert_eq!(variations.line_end(Variations::ROOT), main[2]);
+
+        // then its parent variation replaces the main line
+        variations.promote(nested);
+        assert!(!variations.is_variation(nested));
+        assert!(variations.is_variation(main[1]));
+        assert_eq!(variations.line_end(Variations::ROOT), nested);
+        assert_eq!(
+            variations.moves(nested),
+            vec![mov("a1-a2"), mov("d1-d2"), mov("f1-f2")]
+        );
+        assert_eq!(
+            variations.to_notation(),
+            "1. a1-a2 d1-d2 (1... b1-b2 2. c1-c2) 2. f1-f2 (2. e1-e2)"
+        );
+
+        // the main line has nothing to promote
+        variations.promote(nested);
+        assert_eq!(variations.line_end(Variations::ROOT), nested);
+    }
+
+    #[test]
+    fn delete_line() {
+        let mut variations = Variations::new();
+        let main = play_line(
+            &mut variations,
+            Variations::ROOT,
+            &["a1-a2", "b1-b2", "c1-c2"],
+        );
+        let variation = play_line(&mut variations, main[0], &["d1-d2", "e1-e2"]);
+
+        assert_eq!(variations.delete(variation[0]), main[0]);
+        assert_eq!(variations.to_notation(), "1. a1-a2 b1-b2 2. c1-c2");
+
+        // deleting the main line makes its first variation the main line
+        let variation = variations.play(main[0], mov("d1-d2"));
+        assert_eq!(variations.delete(main[1]), main[0]);
+        assert_eq!(variations.main_child(main[0]), Some(variation));
+        assert!(!variations.is_variation(variation));
+        assert_eq!(variations.to_notation(), "1. a1-a2 d1-d2");
+
+        assert_eq!(variations.delete(Variations::ROOT), Variations::ROOT);
+    }
+
+    #[test]
+    fn black_move_numbered_after_variation_or_comment() {
+        let mut variations = Variations::new();
+        play_line(
+            &mut variations,
+            Variations::ROOT,
+            &["a1-a2", "b1-b2", "c1-c2"],
+        );
+        play_line(&mut variations, Variations::ROOT, &["e1-e2", "f1-f2"]);
+        assert_eq!(
+            variations.to_notation(),
+            "1. a1-a2 (1. e1-e2 f1-f2) 1... b1-b2 2. c1-c2"
+        );
+
+        let mut variations = Variations::new();
+        let main = play_line(
+            &mut variations,
+            Variations::ROOT,
+            &["a1-a2", "b1-b2", "c1-c2", "d1-d2"],
+        );
+        variations.set_comment(main[2], "a comment".into());
+        assert_eq!(
+            variations.to_notation(),
+            "1. a1-a2 b1-b2 2. c1-c2 {a comment} 2... d1-d2"
+        );
+    }
+
+    #[test]
+    fn notation_of_annotated_tree() {
+        let mut variations = Variations::new();
+        let main = play_line(&mut variations, Variations::ROOT, &["a1-a2", "b1-b2"]);
+        let variation = variations.play(main[0], mov("c1-c2"));
+        variations.set_annotation(main[0], Some(Annotation::Good));
+        variations.set_comment(main[0], "see {the} line".into());
+        variations.set_annotation(variation, Some(Annotation::Interesting));
+        variations.set_comment(variation, "}{".into());
+        variations.set_annotation(main[1], Some(Annotation::Mistake));
+        assert_eq!(
+            variations.to_notation(),
+            "1. a1-a2! {see the line} 1... b1-b2? (1... c1-c2!? {})"
+        );
+    }
+}